@@ -0,0 +1,402 @@
+//! `#[derive(Finitio)]`, see the `finitio::derive` module for the attributes
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    parse_macro_input, spanned::Spanned, Attribute, Data, DeriveInput, Error, Expr, Fields, GenericArgument, Lit,
+    LitStr, Meta, PathArguments, Type, Variant,
+};
+
+#[proc_macro_derive(Finitio, attributes(finitio, serde))]
+pub fn derive_finitio(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match derive(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// The `#[finitio(...)]` (and relevant `#[serde(...)]`) attributes of an
+/// item, field or variant
+#[derive(Default)]
+struct Attrs {
+    name: Option<String>,
+    constraints: Vec<String>,
+    meta: Option<String>,
+    skip: bool,
+    set: bool,
+    nullable: bool,
+    extra: bool,
+    untagged: bool,
+    /// `#[serde(rename_all = "...")]`, one of `RENAME_RULES`
+    rename_all: Option<String>,
+    /// `#[serde(default)]` or `#[serde(skip_serializing_if = "...")]`, the
+    /// attribute(s) may be absent
+    optional: bool,
+}
+
+/// The `rename_all` rules of serde
+const RENAME_RULES: [&str; 8] = [
+    "lowercase",
+    "UPPERCASE",
+    "PascalCase",
+    "camelCase",
+    "snake_case",
+    "SCREAMING_SNAKE_CASE",
+    "kebab-case",
+    "SCREAMING-KEBAB-CASE",
+];
+
+/// The serde attributes that leave the serialized representation unchanged
+const IGNORED_SERDE: [&str; 6] = ["deny_unknown_fields", "bound", "crate", "expecting", "borrow", "alias"];
+
+impl Attrs {
+    fn parse(attrs: &[Attribute]) -> Result<Self, Error> {
+        let mut parsed = Attrs::default();
+        let mut docs = vec![];
+        for attr in attrs.iter() {
+            if attr.path().is_ident("doc") {
+                if let Meta::NameValue(nv) = &attr.meta {
+                    if let Expr::Lit(lit) = &nv.value {
+                        if let Lit::Str(s) = &lit.lit {
+                            docs.push(s.value().trim().to_string());
+                        }
+                    }
+                }
+            } else if attr.path().is_ident("finitio") {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("name") || meta.path.is_ident("rename") {
+                        parsed.name = Some(meta.value()?.parse::<LitStr>()?.value());
+                    } else if meta.path.is_ident("constraint") {
+                        let lit: LitStr = meta.value()?.parse()?;
+                        let constraint = lit.value();
+                        match constraint.split_once('|') {
+                            Some((param, expr)) if is_identifier(param.trim()) && !expr.trim().is_empty() => {},
+                            _ => return Err(Error::new(lit.span(), "expected a constraint like `param | expr`")),
+                        }
+                        parsed.constraints.push(constraint);
+                    } else if meta.path.is_ident("skip") {
+                        parsed.skip = true;
+                    } else if meta.path.is_ident("set") {
+                        parsed.set = true;
+                    } else if meta.path.is_ident("nullable") {
+                        parsed.nullable = true;
+                    } else if meta.path.is_ident("extra") {
+                        parsed.extra = true;
+                    } else if meta.path.is_ident("untagged") {
+                        parsed.untagged = true;
+                    } else {
+                        return Err(meta.error("unknown finitio attribute"));
+                    }
+                    Ok(())
+                })?;
+            } else if attr.path().is_ident("serde") {
+                // The serde attributes changing the representation of values
+                // are either honored or rejected, so that the derived type
+                // never silently differs from the serde representation
+                attr.parse_nested_meta(|meta| {
+                    let key = meta.path.get_ident().map(|ident| ident.to_string()).unwrap_or_default();
+                    let is_assigned = meta.input.peek(syn::Token![=]);
+                    match key.as_str() {
+                        "rename" if is_assigned => {
+                            let name = meta.value()?.parse::<LitStr>()?.value();
+                            parsed.name.get_or_insert(name);
+                        },
+                        "rename_all" if is_assigned => {
+                            let lit: LitStr = meta.value()?.parse()?;
+                            if !RENAME_RULES.contains(&lit.value().as_str()) {
+                                return Err(Error::new(lit.span(), "unknown rename_all rule"));
+                            }
+                            parsed.rename_all = Some(lit.value());
+                        },
+                        "rename" | "rename_all" => {
+                            let message = format!("only `#[serde({} = \"...\")]` is supported by derive(Finitio)", key);
+                            return Err(meta.error(message));
+                        },
+                        "skip" => parsed.skip = true,
+                        "untagged" => parsed.untagged = true,
+                        "default" | "skip_serializing_if" => {
+                            if is_assigned {
+                                meta.value()?.parse::<Expr>()?;
+                            }
+                            parsed.optional = true;
+                        },
+                        key if IGNORED_SERDE.contains(&key) => {
+                            if is_assigned {
+                                meta.value()?.parse::<Expr>()?;
+                            } else if meta.input.peek(syn::token::Paren) {
+                                meta.parse_nested_meta(|nested| {
+                                    if nested.input.peek(syn::Token![=]) {
+                                        nested.value()?.parse::<Expr>()?;
+                                    }
+                                    Ok(())
+                                })?;
+                            }
+                        },
+                        _ => return Err(meta.error(format!("`#[serde({})]` is not supported by derive(Finitio)", key))),
+                    }
+                    Ok(())
+                })?;
+            }
+        }
+        let docs: Vec<String> = docs.into_iter().filter(|doc| !doc.is_empty()).collect();
+        if !docs.is_empty() {
+            parsed.meta = Some(format!(" {} ", docs.join(" ")));
+        }
+        Ok(parsed)
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
+/// Renames a field (in snake_case) or a variant (in PascalCase) following a
+/// `rename_all` rule, as serde does
+fn rename(name: &str, rule: &str, variant: bool) -> String {
+    if variant {
+        let snake: String = name
+            .chars()
+            .enumerate()
+            .flat_map(|(i, c)| {
+                let sep = if i > 0 && c.is_uppercase() { Some('_') } else { None };
+                sep.into_iter().chain(c.to_lowercase())
+            })
+            .collect();
+        match rule {
+            "lowercase" => name.to_ascii_lowercase(),
+            "UPPERCASE" => name.to_ascii_uppercase(),
+            "camelCase" => uncapitalize(name),
+            "snake_case" => snake,
+            "SCREAMING_SNAKE_CASE" => snake.to_ascii_uppercase(),
+            "kebab-case" => snake.replace('_', "-"),
+            "SCREAMING-KEBAB-CASE" => snake.to_ascii_uppercase().replace('_', "-"),
+            _ => name.to_string(),
+        }
+    } else {
+        let pascal: String = name.split('_').map(capitalize).collect();
+        match rule {
+            "UPPERCASE" | "SCREAMING_SNAKE_CASE" => name.to_ascii_uppercase(),
+            "PascalCase" => pascal,
+            "camelCase" => uncapitalize(&pascal),
+            "kebab-case" => name.replace('_', "-"),
+            "SCREAMING-KEBAB-CASE" => name.to_ascii_uppercase().replace('_', "-"),
+            _ => name.to_string(),
+        }
+    }
+}
+
+fn option_tokens(value: &Option<String>) -> TokenStream {
+    match value {
+        Some(value) => quote!(::core::option::Option::Some(#value)),
+        None => quote!(::core::option::Option::None),
+    }
+}
+
+/// The `T` of a `Wrapper<T>` type
+fn inner_type<'a>(ty: &'a Type, wrapper: &str) -> Option<&'a Type> {
+    let path = match ty {
+        Type::Path(path) if path.qself.is_none() => &path.path,
+        _ => return None,
+    };
+    let segment = path.segments.last()?;
+    if segment.ident != wrapper {
+        return None;
+    }
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) if args.args.len() == 1 => match &args.args[0] {
+            GenericArgument::Type(inner) => Some(inner),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn finitio_type(ty: &Type) -> TokenStream {
+    quote!(<#ty as ::finitio::derive::Finitio>::finitio_type(defs))
+}
+
+/// Applies the constraints of some attributes to a type
+fn constrained(t: TokenStream, attrs: &Attrs, hint: &str) -> TokenStream {
+    let constraints = &attrs.constraints;
+    quote! {
+        {
+            let t = #t;
+            #( let t = ::finitio::derive::subtype(defs, t, #constraints, #hint); )*
+            t
+        }
+    }
+}
+
+/// The type of a field, `Vec`s are sets with `set`
+fn field_type(ty: &Type, attrs: &Attrs) -> Result<TokenStream, Error> {
+    if !attrs.set {
+        return Ok(finitio_type(ty));
+    }
+    match inner_type(ty, "Vec") {
+        Some(elm) => {
+            let elm = finitio_type(elm);
+            Ok(quote!(::finitio::derive::set(#elm)))
+        },
+        None => Err(Error::new(ty.span(), "`set` applies to `Vec` fields only")),
+    }
+}
+
+/// The attributes of a tuple type for named fields, `parent` holds the
+/// `rename_all` and `default` of the struct or variant
+fn attributes(fields: &syn::FieldsNamed, hint: &str, parent: &Attrs) -> Result<Vec<TokenStream>, Error> {
+    let mut attributes = vec![];
+    for field in fields.named.iter() {
+        let attrs = Attrs::parse(&field.attrs)?;
+        if attrs.skip {
+            continue;
+        }
+        let ident = field.ident.as_ref().unwrap().to_string();
+        let ident = ident.trim_start_matches("r#");
+        let name = match (&attrs.name, &parent.rename_all) {
+            (Some(name), _) => name.clone(),
+            (None, Some(rule)) => rename(ident, rule, false),
+            (None, None) => ident.to_string(),
+        };
+        // `Option` fields are optional attributes, unless nullable, and so
+        // are fields serde may leave out
+        let (ty, optional) = match inner_type(&field.ty, "Option") {
+            Some(inner) if !attrs.nullable => (inner, true),
+            _ => (&field.ty, false),
+        };
+        let optional = optional || attrs.optional || parent.optional;
+        let att_hint = format!("{}{}", hint, capitalize(&name));
+        let t = constrained(field_type(ty, &attrs)?, &attrs, &att_hint);
+        let meta = option_tokens(&attrs.meta);
+        attributes.push(quote!(::finitio::derive::attribute(#name, #t, #optional, #meta)));
+    }
+    Ok(attributes)
+}
+
+fn capitalize(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) => c.to_ascii_uppercase().to_string() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+fn uncapitalize(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) => c.to_ascii_lowercase().to_string() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+/// The type of the fields of a struct or variant, as serde represents them
+fn fields_type(fields: &Fields, hint: &str, attrs: &Attrs) -> Result<TokenStream, Error> {
+    match fields {
+        Fields::Named(named) => {
+            let attributes = attributes(named, hint, attrs)?;
+            let extra = if attrs.extra {
+                quote!(::core::option::Option::Some(::finitio::derive::any()))
+            } else {
+                quote!(::core::option::Option::None)
+            };
+            Ok(quote!(::finitio::derive::tuple(vec![#(#attributes),*], #extra)))
+        },
+        // Newtypes are represented by their content
+        Fields::Unnamed(unnamed) if unnamed.unnamed.len() == 1 => {
+            let field = &unnamed.unnamed[0];
+            let attrs = Attrs::parse(&field.attrs)?;
+            Ok(constrained(field_type(&field.ty, &attrs)?, &attrs, hint))
+        },
+        Fields::Unnamed(unnamed) => {
+            let mut elements = vec![];
+            for (index, field) in unnamed.unnamed.iter().enumerate() {
+                let attrs = Attrs::parse(&field.attrs)?;
+                let t = constrained(field_type(&field.ty, &attrs)?, &attrs, &format!("{}{}", hint, index + 1));
+                elements.push(t);
+            }
+            Ok(quote!(::finitio::derive::r#struct(vec![#(#elements),*])))
+        },
+        Fields::Unit => Ok(quote!(::finitio::derive::nil())),
+    }
+}
+
+fn variant_name(variant: &Variant, attrs: &Attrs, enum_attrs: &Attrs) -> String {
+    match (&attrs.name, &enum_attrs.rename_all) {
+        (Some(name), _) => name.clone(),
+        (None, Some(rule)) => rename(&variant.ident.to_string(), rule, true),
+        (None, None) => variant.ident.to_string(),
+    }
+}
+
+/// The candidates of an enum, as serde represents it
+fn enum_type(name: &str, data: &syn::DataEnum, attrs: &Attrs) -> Result<TokenStream, Error> {
+    let mut units = vec![];
+    let mut candidates = vec![];
+    for variant in data.variants.iter() {
+        let variant_attrs = Attrs::parse(&variant.attrs)?;
+        if variant_attrs.skip {
+            continue;
+        }
+        let variant_name = variant_name(variant, &variant_attrs, attrs);
+        // Type names follow the Rust identifiers, whatever the serde names
+        let hint = format!("{}{}", name, variant.ident);
+        if attrs.untagged {
+            let mut t = fields_type(&variant.fields, &hint, &variant_attrs)?;
+            if let Fields::Named(_) = variant.fields {
+                t = quote!({ let t = #t; defs.hoist(t, #hint) });
+            }
+            candidates.push(constrained(t, &variant_attrs, &hint));
+        } else if let Fields::Unit = variant.fields {
+            // Unit variants are represented by their name
+            units.push(variant_name);
+        } else {
+            // Other variants by a tuple with the name as only attribute
+            let t = constrained(fields_type(&variant.fields, &hint, &variant_attrs)?, &variant_attrs, &hint);
+            let meta = option_tokens(&variant_attrs.meta);
+            candidates.push(quote! {
+                defs.define_new(#hint, #meta, |defs| {
+                    ::finitio::derive::tuple(vec![::finitio::derive::attribute(#variant_name, #t, false, ::core::option::Option::None)], ::core::option::Option::None)
+                })
+            });
+        }
+    }
+    if !units.is_empty() {
+        candidates.push(quote!(::finitio::derive::strings(&[#(#units),*])));
+    }
+    Ok(quote! {
+        {
+            let candidates = vec![#(#candidates),*];
+            ::finitio::derive::union(defs, candidates, #name)
+        }
+    })
+}
+
+fn derive(input: &DeriveInput) -> Result<TokenStream, Error> {
+    if !input.generics.params.is_empty() {
+        return Err(Error::new(input.generics.span(), "Finitio cannot be derived for generic types"));
+    }
+    let attrs = Attrs::parse(&input.attrs)?;
+    let ident = &input.ident;
+    let name = attrs.name.clone().unwrap_or_else(|| ident.to_string());
+    if !is_identifier(&name) {
+        return Err(Error::new(ident.span(), format!("`{}` is not a valid finitio type name", name)));
+    }
+
+    let target = match &input.data {
+        Data::Struct(data) => fields_type(&data.fields, &name, &attrs)?,
+        Data::Enum(data) => enum_type(&name, data, &attrs)?,
+        Data::Union(_) => return Err(Error::new(ident.span(), "Finitio cannot be derived for unions")),
+    };
+    let target = constrained(target, &attrs, &format!("{}Base", name));
+    let meta = option_tokens(&attrs.meta);
+
+    Ok(quote! {
+        impl ::finitio::derive::Finitio for #ident {
+            fn finitio_type(defs: &mut ::finitio::derive::TypeDefs) -> ::finitio::fio::Type {
+                defs.define::<Self, _>(#name, #meta, |defs| #target)
+            }
+        }
+    })
+}
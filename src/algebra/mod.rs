@@ -0,0 +1,392 @@
+//! Relational algebra over dressed relation values.
+//!
+//! Operators compute the heading of their result from the headings of their
+//! operands, and fail when they refer to attributes that do not exist or when
+//! the types of shared attributes are not compatible.
+
+mod summarize;
+
+pub use summarize::Aggregate;
+
+use std::collections::{HashMap, HashSet};
+
+use snafu::{Whatever, whatever, ResultExt};
+
+use crate::js::canonical_json;
+use crate::schema::{
+    constraint::ConstraintExecute,
+    heading::{Attribute, Heading},
+    is_subtype,
+    relation::Relation,
+    Attributes, Constraint, FinitioValue, Type, TypeDef, TypeDress, TypeUndress,
+};
+
+/// Maximum number of references followed to find the heading of a relation
+/// type
+const MAX_DEPTH: usize = 64;
+
+/// The tuples of a relation together with their heading. The heading may
+/// refer to type definitions, the schema they belong to must outlive it.
+#[derive(Clone, Debug)]
+pub struct RelationValue {
+    pub heading: Heading,
+    /// The tuples, in their original order, without duplicates
+    pub tuples: Vec<Attributes>,
+}
+
+impl RelationValue {
+    /// Dresses json data with a relation type (possibly a sub type of one, or
+    /// a reference to one)
+    pub fn dress(typedef: &TypeDef, v: &serde_json::Value) -> Result<Self, Whatever> {
+        let heading = match typedef_heading(typedef) {
+            Some(heading) => heading,
+            None => whatever!("Type `{}` is not a relation type", typedef.name()),
+        };
+        let value = typedef.dress(v)?;
+        Self::new(heading, &value)
+    }
+
+    /// `value` must be a relation whose tuples have the given heading
+    pub fn new(heading: Heading, value: &FinitioValue) -> Result<Self, Whatever> {
+        match value.untagged() {
+            FinitioValue::Relation(tuples) => Ok(Self::from_tuples(heading, tuples.iter().cloned())),
+            value => whatever!("Not a relation: {:?}", value),
+        }
+    }
+
+    fn from_tuples<I: IntoIterator<Item = Attributes>>(heading: Heading, tuples: I) -> Self {
+        let mut seen = HashSet::new();
+        let tuples = tuples
+            .into_iter()
+            .filter(|tuple| seen.insert(encoding(tuple)))
+            .collect();
+        Self { heading, tuples }
+    }
+
+    /// The relation type of the value
+    pub fn relation_type(&self) -> Relation {
+        Relation {
+            heading: self.heading.clone(),
+            keys: vec![],
+            position: self.heading.position.clone(),
+        }
+    }
+
+    pub fn to_value(&self) -> FinitioValue {
+        FinitioValue::Relation(self.tuples.clone())
+    }
+
+    /// The json representation of the value, as undressed by its heading
+    pub fn to_json(&self) -> Result<serde_json::Value, Whatever> {
+        self.relation_type().undress(&self.to_value())
+    }
+
+    fn attribute(&self, name: &str) -> Result<&Attribute, Whatever> {
+        match self.heading.attributes.get(name) {
+            Some(attribute) => Ok(attribute),
+            None => whatever!("No such attribute `{}`", name),
+        }
+    }
+
+    fn with_attributes<I: IntoIterator<Item = Attribute>>(&self, attributes: I) -> Heading {
+        Heading {
+            attributes: attributes.into_iter().map(|att| (att.name.clone(), att)).collect(),
+            allow_extra: None,
+            position: self.heading.position.clone(),
+        }
+    }
+
+    /// Keeps the given attributes only
+    pub fn project(&self, names: &[&str]) -> Result<Self, Whatever> {
+        let attributes = names
+            .iter()
+            .map(|name| self.attribute(name).cloned())
+            .collect::<Result<Vec<_>, _>>()?;
+        let tuples = self.tuples.iter().map(|tuple| {
+            tuple
+                .iter()
+                .filter(|(name, _)| names.contains(&name.as_str()))
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .collect()
+        });
+        Ok(Self::from_tuples(self.with_attributes(attributes), tuples))
+    }
+
+    /// Keeps the tuples satisfying a constraint expression, e.g.
+    /// `restrict("t", "t.age >= 18")`
+    pub fn restrict(&self, param: &str, expr: &str) -> Result<Self, Whatever> {
+        let mut constraint = Constraint::new(param.to_string(), expr.to_string(), self.heading.position.clone());
+        constraint.compile()?;
+        for name in constraint.attributes() {
+            self.attribute(&name)?;
+        }
+
+        let mut tuples = vec![];
+        for tuple in self.tuples.iter() {
+            let v = FinitioValue::Tuple(tuple.clone()).to_json();
+            let keep = (&constraint)
+                .execute(&v)
+                .with_whatever_context(|_| format!("Unable to restrict tuple: {}", v))?;
+            if keep {
+                tuples.push(tuple.clone());
+            }
+        }
+        Ok(Self { heading: self.heading.clone(), tuples })
+    }
+
+    /// Renames attributes, given as `(old, new)` pairs
+    pub fn rename(&self, renaming: &[(&str, &str)]) -> Result<Self, Whatever> {
+        let new_name = |name: &str| -> String {
+            renaming
+                .iter()
+                .find(|(old, _)| *old == name)
+                .map_or(name, |(_, new)| *new)
+                .to_string()
+        };
+        for (old, _) in renaming.iter() {
+            self.attribute(old)?;
+        }
+
+        let mut attributes = HashMap::new();
+        for attribute in self.heading.attributes.values() {
+            let name = new_name(&attribute.name);
+            let renamed = Attribute { name: name.clone(), ..attribute.clone() };
+            if attributes.insert(name.clone(), renamed).is_some() {
+                whatever!("Renaming yields duplicated attribute `{}`", name)
+            }
+        }
+        let heading = Heading { attributes, ..self.heading.clone() };
+        let tuples = self.tuples
+            .iter()
+            .map(|tuple| tuple.iter().map(|(name, value)| (new_name(name), value.clone())).collect())
+            .collect();
+        Ok(Self { heading, tuples })
+    }
+
+    /// Natural join: pairs of tuples agreeing on the common attributes, an
+    /// absent (optional) attribute only matches an absent one
+    pub fn join(&self, other: &Self) -> Result<Self, Whatever> {
+        let mut common = vec![];
+        let mut attributes: Vec<Attribute> = self.heading.attributes.values().cloned().collect();
+        for attribute in other.heading.attributes.values() {
+            match self.heading.attributes.get(&attribute.name) {
+                Some(mine) => {
+                    attributes.retain(|att| att.name != attribute.name);
+                    attributes.push(common_attribute(mine, attribute, false)?);
+                    common.push(attribute.name.clone());
+                },
+                None => attributes.push(attribute.clone()),
+            }
+        }
+
+        let mut by_key: HashMap<String, Vec<&Attributes>> = HashMap::new();
+        for tuple in other.tuples.iter() {
+            by_key.entry(key(tuple, &common)).or_default().push(tuple);
+        }
+        let mut tuples = vec![];
+        for tuple in self.tuples.iter() {
+            for matching in by_key.get(&key(tuple, &common)).into_iter().flatten() {
+                let mut joined = tuple.clone();
+                joined.extend(matching.iter().map(|(name, value)| (name.clone(), value.clone())));
+                tuples.push(joined);
+            }
+        }
+        Ok(Self::from_tuples(self.with_attributes(attributes), tuples))
+    }
+
+    /// The tuples of both relations, which must have the same attributes
+    pub fn union(&self, other: &Self) -> Result<Self, Whatever> {
+        let attributes = self.same_attributes(other)?;
+        let tuples = self.tuples.iter().chain(other.tuples.iter()).cloned();
+        Ok(Self::from_tuples(self.with_attributes(attributes), tuples))
+    }
+
+    /// The tuples not in the other relation, which must have the same
+    /// attributes
+    pub fn minus(&self, other: &Self) -> Result<Self, Whatever> {
+        self.same_attributes(other)?;
+        let others: HashSet<String> = other.tuples.iter().map(encoding).collect();
+        let tuples = self.tuples
+            .iter()
+            .filter(|tuple| !others.contains(&encoding(tuple)))
+            .cloned()
+            .collect();
+        Ok(Self { heading: self.heading.clone(), tuples })
+    }
+
+    /// The attributes of the union of two relations
+    fn same_attributes(&self, other: &Self) -> Result<Vec<Attribute>, Whatever> {
+        let mine: HashSet<&String> = self.heading.attributes.keys().collect();
+        let theirs: HashSet<&String> = other.heading.attributes.keys().collect();
+        if mine != theirs {
+            let mut names: Vec<&str> = mine.symmetric_difference(&theirs).map(|name| name.as_str()).collect();
+            names.sort();
+            whatever!("Relations have different attributes: {}", names.join(", "))
+        }
+        self.heading.attributes
+            .values()
+            .map(|attribute| common_attribute(attribute, &other.heading.attributes[&attribute.name], true))
+            .collect()
+    }
+}
+
+/// The attribute shared by two relations: the wider of the two types, and
+/// optional if any of them is (`either`) or if both are
+fn common_attribute(a: &Attribute, b: &Attribute, either: bool) -> Result<Attribute, Whatever> {
+    let att_type = if is_subtype(&b.att_type, &a.att_type).is_ok() {
+        a.att_type.clone()
+    } else if is_subtype(&a.att_type, &b.att_type).is_ok() {
+        b.att_type.clone()
+    } else {
+        whatever!("Attribute `{}` has incompatible types", a.name)
+    };
+    let optional = if either { a.optional || b.optional } else { a.optional && b.optional };
+    Ok(Attribute { name: a.name.clone(), att_type, optional, meta: a.meta.clone() })
+}
+
+/// Identifies a tuple, dressed values are compared by their canonical
+/// encoding, in which nested sets and relations are sorted
+fn encoding(tuple: &Attributes) -> String {
+    canonical_json(&FinitioValue::Tuple(tuple.clone())).to_string()
+}
+
+/// Identifies the values of some attributes of a tuple, absent ones included
+fn key(tuple: &Attributes, names: &[String]) -> String {
+    let values: Vec<serde_json::Value> = names
+        .iter()
+        .map(|name| tuple.get(name).map_or(serde_json::Value::Null, |value| {
+            serde_json::json!([canonical_json(value)])
+        }))
+        .collect();
+    serde_json::Value::Array(values).to_string()
+}
+
+fn typedef_heading(typedef: &TypeDef) -> Option<Heading> {
+    match typedef {
+        TypeDef::RelationType(t) => Some(t.target.borrow().heading.clone()),
+        TypeDef::SubType(t) => type_heading(&t.target.borrow().base_type, 0),
+        TypeDef::RefType(t) => type_heading(&t.target.borrow().target, 0),
+        _ => None,
+    }
+}
+
+fn type_heading(t: &Type, depth: usize) -> Option<Heading> {
+    if depth > MAX_DEPTH {
+        return None;
+    }
+    match t {
+        Type::Relation(relation) => Some(relation.heading.clone()),
+        Type::Sub(sub) => type_heading(&sub.base_type, depth + 1),
+        Type::Ref(r) => type_heading(&r.target()?, depth + 1),
+        _ => None,
+    }
+}
+
+/// Dresses the values with the given relation types, and returns the schema
+/// along as the resulting headings refer to it
+#[cfg(test)]
+pub(crate) fn relation_values(source: &str, values: &[(&str, serde_json::Value)]) -> (crate::schema::Schema, Vec<RelationValue>) {
+    let schema = crate::schema::schema::test_schema(source);
+    let values = values
+        .iter()
+        .map(|(name, v)| RelationValue::dress(&schema.types[*name], v).unwrap())
+        .collect();
+    (schema, values)
+}
+
+#[cfg(test)]
+pub(crate) fn sorted_json(relation: &RelationValue) -> Vec<String> {
+    let mut tuples: Vec<String> = relation.tuples
+        .iter()
+        .map(|tuple| FinitioValue::Tuple(tuple.clone()).to_json().to_string())
+        .collect();
+    tuples.sort();
+    tuples
+}
+
+#[cfg(test)]
+const SUPPLIERS: &str = "
+Number = .Number
+String = .String
+PosNumber = Number(n | n > 0)
+Suppliers = {{ sid: String, name: String, city: String }}
+Parts = {{ pid: String, name: String, weight: PosNumber }}
+Supplies = {{ sid: String, pid: String, qty: Number }}
+";
+
+#[test]
+fn test_project_restrict_rename() {
+    let (_schema, rels) = relation_values(SUPPLIERS, &[
+        ("Suppliers", serde_json::json!([
+            { "sid": "S1", "name": "Smith", "city": "London" },
+            { "sid": "S2", "name": "Jones", "city": "Paris" },
+            { "sid": "S3", "name": "Blake", "city": "Paris" },
+        ])),
+    ]);
+    let suppliers = &rels[0];
+
+    let cities = suppliers.project(&["city"]).unwrap();
+    assert_eq!(sorted_json(&cities), vec![r#"{"city":"London"}"#, r#"{"city":"Paris"}"#]);
+    assert_eq!(cities.heading.attributes.keys().collect::<Vec<_>>(), vec!["city"]);
+
+    let parisians = suppliers.restrict("s", "s.city == \"Paris\"").unwrap();
+    assert_eq!(parisians.tuples.len(), 2);
+
+    let renamed = suppliers.rename(&[("city", "town")]).unwrap();
+    assert!(renamed.heading.attributes.contains_key("town"));
+    assert!(renamed.tuples.iter().all(|tuple| tuple.contains_key("town")));
+
+    assert_eq!(suppliers.project(&["town"]).unwrap_err().to_string(), "No such attribute `town`");
+    assert_eq!(suppliers.restrict("s", "s.town == \"Paris\"").unwrap_err().to_string(), "No such attribute `town`");
+    assert_eq!(
+        suppliers.rename(&[("city", "name")]).unwrap_err().to_string(),
+        "Renaming yields duplicated attribute `name`"
+    );
+}
+
+#[test]
+fn test_join_union_minus() {
+    let (_schema, rels) = relation_values(SUPPLIERS, &[
+        ("Suppliers", serde_json::json!([
+            { "sid": "S1", "name": "Smith", "city": "London" },
+            { "sid": "S2", "name": "Jones", "city": "Paris" },
+        ])),
+        ("Supplies", serde_json::json!([
+            { "sid": "S1", "pid": "P1", "qty": 300 },
+            { "sid": "S1", "pid": "P2", "qty": 200 },
+            { "sid": "S3", "pid": "P1", "qty": 100 },
+        ])),
+        ("Parts", serde_json::json!([
+            { "pid": "P1", "name": "Nut", "weight": 12 },
+        ])),
+    ]);
+    let (suppliers, supplies, parts) = (&rels[0], &rels[1], &rels[2]);
+
+    let joined = suppliers.join(supplies).unwrap();
+    assert_eq!(sorted_json(&joined), vec![
+        r#"{"city":"London","name":"Smith","pid":"P1","qty":300,"sid":"S1"}"#,
+        r#"{"city":"London","name":"Smith","pid":"P2","qty":200,"sid":"S1"}"#,
+    ]);
+    assert_eq!(joined.heading.attributes.len(), 5);
+    assert!(joined.to_json().is_ok());
+
+    let sids = suppliers.project(&["sid"]).unwrap();
+    let supplying = supplies.project(&["sid"]).unwrap();
+    assert_eq!(sorted_json(&sids.union(&supplying).unwrap()).len(), 3);
+    assert_eq!(sorted_json(&sids.minus(&supplying).unwrap()), vec![r#"{"sid":"S2"}"#]);
+
+    assert_eq!(
+        suppliers.union(supplies).unwrap_err().to_string(),
+        "Relations have different attributes: city, name, pid, qty"
+    );
+
+    // `name` of parts is a String as well, `weight` and `qty` are numbers
+    let weights = parts.rename(&[("weight", "qty")]).unwrap().project(&["qty"]).unwrap();
+    let quantities = supplies.project(&["qty"]).unwrap();
+    assert_eq!(sorted_json(&quantities.union(&weights).unwrap()).len(), 4);
+    let names = parts.rename(&[("name", "qty")]).unwrap().project(&["qty"]).unwrap();
+    assert_eq!(
+        quantities.union(&names).unwrap_err().to_string(),
+        "Attribute `qty` has incompatible types"
+    );
+}
@@ -0,0 +1,312 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use snafu::{Whatever, whatever};
+
+use crate::schema::{
+    builtin::Builtin,
+    heading::{Attribute, Heading},
+    relation::Relation,
+    Attributes, FinitioValue, Type,
+};
+
+use super::{key, RelationValue};
+
+/// Computes an attribute of the summary of a group of tuples
+#[derive(Clone, Debug, PartialEq)]
+pub enum Aggregate {
+    /// The number of tuples
+    Count,
+    /// The sum of the (numeric) values of an attribute
+    Sum(String),
+    /// The average of the (numeric) values of an attribute
+    Avg(String),
+    /// The smallest value of an attribute
+    Min(String),
+    /// The largest value of an attribute
+    Max(String),
+}
+
+impl Aggregate {
+    fn attribute(&self) -> Option<&str> {
+        match self {
+            Aggregate::Count => None,
+            Aggregate::Sum(name) | Aggregate::Avg(name) | Aggregate::Min(name) | Aggregate::Max(name) => Some(name),
+        }
+    }
+
+    /// The attribute computed by the aggregate, `source` is the aggregated one
+    fn result(&self, name: &str, source: Option<&Attribute>, heading: &Heading) -> Attribute {
+        let number = || Type::Builtin(Builtin {
+            target: String::from("Number"),
+            position: heading.position.clone(),
+        });
+        match (self, source) {
+            // The extremes of an optional attribute are absent when no tuple
+            // of the group has it
+            (Aggregate::Min(_) | Aggregate::Max(_), Some(source)) => Attribute {
+                name: name.to_string(),
+                att_type: source.att_type.clone(),
+                optional: source.optional,
+                meta: None,
+            },
+            (Aggregate::Avg(_), Some(source)) => Attribute {
+                name: name.to_string(),
+                att_type: number(),
+                optional: source.optional,
+                meta: None,
+            },
+            _ => Attribute { name: name.to_string(), att_type: number(), optional: false, meta: None },
+        }
+    }
+
+    fn compute(&self, tuples: &[&Attributes]) -> Result<Option<FinitioValue>, Whatever> {
+        let values: Vec<&FinitioValue> = match self.attribute() {
+            Some(name) => tuples.iter().filter_map(|tuple| tuple.get(name)).collect(),
+            None => vec![],
+        };
+        let value = match self {
+            Aggregate::Count => Some(FinitioValue::Number(tuples.len().into())),
+            Aggregate::Sum(_) => Some(FinitioValue::Number(sum(&values)?)),
+            Aggregate::Avg(_) if values.is_empty() => None,
+            Aggregate::Avg(_) => {
+                let total = sum(&values)?.as_f64().unwrap_or(f64::NAN);
+                number(total / values.len() as f64)?.map(FinitioValue::Number)
+            },
+            Aggregate::Min(_) => extreme(&values, Ordering::Less)?.cloned(),
+            Aggregate::Max(_) => extreme(&values, Ordering::Greater)?.cloned(),
+        };
+        Ok(value)
+    }
+}
+
+fn number(f: f64) -> Result<Option<serde_json::Number>, Whatever> {
+    match serde_json::Number::from_f64(f) {
+        Some(n) => Ok(Some(n)),
+        None => whatever!("Aggregate is not a finite number: {}", f),
+    }
+}
+
+/// Sums integers exactly, as long as they fit
+fn sum(values: &[&FinitioValue]) -> Result<serde_json::Number, Whatever> {
+    let mut integer: Option<i64> = Some(0);
+    let mut float = 0.0;
+    for value in values.iter() {
+        let n = match value.untagged() {
+            FinitioValue::Number(n) => n,
+            value => whatever!("Not a number: {}", value.to_json()),
+        };
+        integer = match (integer, n.as_i64()) {
+            (Some(total), Some(i)) => total.checked_add(i),
+            _ => None,
+        };
+        float += n.as_f64().unwrap_or(f64::NAN);
+    }
+    match integer {
+        Some(total) => Ok(total.into()),
+        None => Ok(number(float)?.unwrap()),
+    }
+}
+
+fn compare(a: &FinitioValue, b: &FinitioValue) -> Option<Ordering> {
+    match (a.untagged(), b.untagged()) {
+        (FinitioValue::Number(a), FinitioValue::Number(b)) => a.as_f64()?.partial_cmp(&b.as_f64()?),
+        (FinitioValue::String(a), FinitioValue::String(b)) => Some(a.cmp(b)),
+        (FinitioValue::Boolean(a), FinitioValue::Boolean(b)) => Some(a.cmp(b)),
+        (FinitioValue::Date(a), FinitioValue::Date(b)) => Some(a.cmp(b)),
+        (FinitioValue::DateTime(a), FinitioValue::DateTime(b)) => Some(a.cmp(b)),
+        _ => None,
+    }
+}
+
+fn extreme<'a>(values: &[&'a FinitioValue], wanted: Ordering) -> Result<Option<&'a FinitioValue>, Whatever> {
+    let mut best: Option<&FinitioValue> = None;
+    for value in values.iter() {
+        best = match best {
+            None => Some(value),
+            Some(current) => match compare(value, current) {
+                Some(ordering) if ordering == wanted => Some(value),
+                Some(_) => Some(current),
+                None => whatever!("Values are not comparable: {} and {}", value.to_json(), current.to_json()),
+            },
+        };
+    }
+    Ok(best)
+}
+
+impl RelationValue {
+    /// Groups tuples by their values for the `by` attributes, each group is
+    /// summarized by the `aggregates`, given as `(name, aggregate)`
+    pub fn summarize(&self, by: &[&str], aggregates: &[(&str, Aggregate)]) -> Result<Self, Whatever> {
+        let mut attributes = vec![];
+        for name in by.iter() {
+            attributes.push(self.attribute(name)?.clone());
+        }
+        for (name, aggregate) in aggregates.iter() {
+            if by.contains(name) {
+                whatever!("Summary attribute `{}` is also a grouping one", name)
+            }
+            let source = match aggregate.attribute() {
+                Some(source) => Some(self.attribute(source)?),
+                None => None,
+            };
+            attributes.push(aggregate.result(name, source, &self.heading));
+        }
+
+        let by: Vec<String> = by.iter().map(|name| name.to_string()).collect();
+        let groups = self.groups(&by);
+        let mut tuples = vec![];
+        for (grouping, group) in groups.iter() {
+            let mut tuple = grouping.clone();
+            for (name, aggregate) in aggregates.iter() {
+                if let Some(value) = aggregate.compute(group)? {
+                    tuple.insert(name.to_string(), value);
+                }
+            }
+            tuples.push(tuple);
+        }
+        Ok(Self::from_tuples(self.with_attributes(attributes), tuples))
+    }
+
+    /// Nests the given attributes in a relation-valued attribute `as_name`,
+    /// one tuple per distinct values of the other attributes
+    pub fn group(&self, names: &[&str], as_name: &str) -> Result<Self, Whatever> {
+        let mut nested = vec![];
+        for name in names.iter() {
+            nested.push(self.attribute(name)?.clone());
+        }
+        let by: Vec<String> = self.heading.attributes
+            .keys()
+            .filter(|name| !names.contains(&name.as_str()))
+            .cloned()
+            .collect();
+        if by.iter().any(|name| name == as_name) {
+            whatever!("Group attribute `{}` already exists", as_name)
+        }
+
+        let nested_heading = self.with_attributes(nested);
+        let mut attributes: Vec<Attribute> = by.iter().map(|name| self.heading.attributes[name].clone()).collect();
+        attributes.push(Attribute {
+            name: as_name.to_string(),
+            att_type: Type::Relation(Relation {
+                heading: nested_heading.clone(),
+                keys: vec![],
+                position: self.heading.position.clone(),
+            }),
+            optional: false,
+            meta: None,
+        });
+
+        let mut tuples = vec![];
+        for (grouping, group) in self.groups(&by).iter() {
+            let inner = group.iter().map(|tuple| {
+                tuple
+                    .iter()
+                    .filter(|(name, _)| names.contains(&name.as_str()))
+                    .map(|(name, value)| (name.clone(), value.clone()))
+                    .collect()
+            });
+            let inner = RelationValue::from_tuples(nested_heading.clone(), inner);
+            let mut tuple = grouping.clone();
+            tuple.insert(as_name.to_string(), inner.to_value());
+            tuples.push(tuple);
+        }
+        Ok(Self::from_tuples(self.with_attributes(attributes), tuples))
+    }
+
+    /// The tuples by their values for the `by` attributes, in order of first
+    /// appearance
+    fn groups(&self, by: &[String]) -> Vec<(Attributes, Vec<&Attributes>)> {
+        let mut indexes: HashMap<String, usize> = HashMap::new();
+        let mut groups: Vec<(Attributes, Vec<&Attributes>)> = vec![];
+        for tuple in self.tuples.iter() {
+            let index = *indexes.entry(key(tuple, by)).or_insert_with(|| {
+                let grouping = tuple
+                    .iter()
+                    .filter(|(name, _)| by.contains(name))
+                    .map(|(name, value)| (name.clone(), value.clone()))
+                    .collect();
+                groups.push((grouping, vec![]));
+                groups.len() - 1
+            });
+            groups[index].1.push(tuple);
+        }
+        groups
+    }
+}
+
+#[cfg(test)]
+use super::{relation_values, sorted_json, SUPPLIERS};
+
+#[test]
+fn test_summarize() {
+    let (_schema, rels) = relation_values(SUPPLIERS, &[
+        ("Supplies", serde_json::json!([
+            { "sid": "S1", "pid": "P1", "qty": 300 },
+            { "sid": "S1", "pid": "P2", "qty": 200 },
+            { "sid": "S2", "pid": "P1", "qty": 100 },
+        ])),
+    ]);
+    let supplies = &rels[0];
+
+    let summary = supplies.summarize(&["sid"], &[
+        ("count", Aggregate::Count),
+        ("total", Aggregate::Sum("qty".to_string())),
+        ("avg", Aggregate::Avg("qty".to_string())),
+        ("first", Aggregate::Min("pid".to_string())),
+    ]).unwrap();
+    assert_eq!(sorted_json(&summary), vec![
+        r#"{"avg":100.0,"count":1,"first":"P1","sid":"S2","total":100}"#,
+        r#"{"avg":250.0,"count":2,"first":"P1","sid":"S1","total":500}"#,
+    ]);
+    assert!(summary.to_json().is_ok());
+
+    let all = supplies.summarize(&[], &[("max", Aggregate::Max("qty".to_string()))]).unwrap();
+    assert_eq!(sorted_json(&all), vec![r#"{"max":300}"#]);
+
+    assert_eq!(
+        supplies.summarize(&["sid"], &[("total", Aggregate::Sum("pid".to_string()))]).unwrap_err().to_string(),
+        r#"Not a number: "P1""#
+    );
+    assert_eq!(
+        supplies.summarize(&["sid"], &[("total", Aggregate::Sum("price".to_string()))]).unwrap_err().to_string(),
+        "No such attribute `price`"
+    );
+}
+
+#[test]
+fn test_group() {
+    let (_schema, rels) = relation_values(SUPPLIERS, &[
+        ("Supplies", serde_json::json!([
+            { "sid": "S1", "pid": "P1", "qty": 300 },
+            { "sid": "S1", "pid": "P2", "qty": 200 },
+            { "sid": "S2", "pid": "P1", "qty": 100 },
+        ])),
+    ]);
+    let supplies = &rels[0];
+
+    let grouped = supplies.group(&["pid", "qty"], "parts").unwrap();
+    assert_eq!(sorted_json(&grouped), vec![
+        r#"{"parts":[{"pid":"P1","qty":100}],"sid":"S2"}"#,
+        r#"{"parts":[{"pid":"P1","qty":300},{"pid":"P2","qty":200}],"sid":"S1"}"#,
+    ]);
+    assert!(matches!(grouped.heading.attributes["parts"].att_type, Type::Relation(_)));
+    assert!(grouped.to_json().is_ok());
+
+    // Groups are equal whatever the order of their tuples
+    let (_schema, rels) = relation_values(SUPPLIERS, &[
+        ("Supplies", serde_json::json!([
+            { "sid": "S2", "pid": "P1", "qty": 100 },
+            { "sid": "S1", "pid": "P2", "qty": 200 },
+            { "sid": "S1", "pid": "P1", "qty": 300 },
+        ])),
+    ]);
+    let regrouped = rels[0].group(&["pid", "qty"], "parts").unwrap();
+    assert!(grouped.minus(&regrouped).unwrap().tuples.is_empty());
+    assert_eq!(grouped.union(&regrouped).unwrap().tuples.len(), 2);
+
+    assert_eq!(
+        supplies.group(&["pid"], "sid").unwrap_err().to_string(),
+        "Group attribute `sid` already exists"
+    );
+}
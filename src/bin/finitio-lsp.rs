@@ -0,0 +1,4 @@
+/// Language server for finitio schemas (.fio), speaking LSP over stdin/stdout
+fn main() -> Result<(), Box<dyn std::error::Error + Sync + Send>> {
+    finitio::lsp::run()
+}
@@ -0,0 +1,256 @@
+//! Compiles finitio schemas from the `build.rs` of a crate: the schemas are
+//! parsed and resolved at build time, and Rust types generated for them
+//! (see [`crate::codegen::rust`]) are written to `OUT_DIR`, along with the
+//! schema itself.
+//!
+//! ```no_run
+//! // In the `main` of build.rs
+//! if let Err(e) = finitio::build::compile(&["schemas/person.fio"]) {
+//!     panic!("{}", e);
+//! }
+//! ```
+//!
+//! The generated file is named after the schema and included in a module:
+//!
+//! ```ignore
+//! mod person {
+//!     include!(concat!(env!("OUT_DIR"), "/person.rs"));
+//! }
+//!
+//! let schema = person::finitio_schema();
+//! ```
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use snafu::{Whatever, whatever};
+
+use crate::codegen::{rust, Unchecked};
+use crate::fio;
+use crate::lsp::{Problem, Workspace};
+use crate::schema::Schema;
+
+#[derive(Clone, Debug, Default)]
+pub struct Options {
+    pub rust: rust::Options,
+    /// Where the generated files are written, `OUT_DIR` by default
+    pub out_dir: Option<PathBuf>,
+}
+
+/// A schema compiled to Rust
+#[derive(Clone, Debug)]
+pub struct Compiled {
+    /// The schema file and all the files it imports
+    pub dependencies: Vec<PathBuf>,
+    /// The generated types, the embedded schema and its loader
+    pub code: String,
+    pub unchecked: Vec<Unchecked>,
+}
+
+/// Compiles schemas with the default options, see [`compile_with`]
+pub fn compile<P: AsRef<Path>>(schemas: &[P]) -> Result<(), Whatever> {
+    compile_with(schemas, &Options::default())
+}
+
+/// Compiles each schema to `<out_dir>/<name>.rs`, tells cargo to rerun the
+/// build script when any of their files changes, and warns about the
+/// constraints the generated types do not check
+pub fn compile_with<P: AsRef<Path>>(schemas: &[P], options: &Options) -> Result<(), Whatever> {
+    let out_dir = match &options.out_dir {
+        Some(out_dir) => out_dir.clone(),
+        None => match env::var_os("OUT_DIR") {
+            Some(out_dir) => PathBuf::from(out_dir),
+            None => whatever!("OUT_DIR is not set, finitio::build must be called from a build script"),
+        },
+    };
+
+    let mut outputs: HashMap<PathBuf, &Path> = HashMap::new();
+    for path in schemas.iter() {
+        let path = path.as_ref();
+        let output = match path.file_stem() {
+            Some(stem) => out_dir.join(stem).with_extension("rs"),
+            None => whatever!("Not a schema file: {}", path.display()),
+        };
+        if let Some(other) = outputs.insert(output.clone(), path) {
+            whatever!("{} and {} would both be compiled to {}", other.display(), path.display(), output.display())
+        }
+
+        let compiled = compile_schema(path, &options.rust);
+        // Schema errors must be fixed in any of the files
+        let dependencies = match &compiled {
+            Ok(compiled) => compiled.dependencies.clone(),
+            Err(_) => Workspace::default().load(path).sources.into_keys().collect(),
+        };
+        for dependency in dependencies.iter() {
+            println!("cargo:rerun-if-changed={}", dependency.display());
+        }
+        let compiled = compiled?;
+        for unchecked in compiled.unchecked.iter() {
+            println!("cargo:warning={}: {}", path.display(), unchecked);
+        }
+        if let Err(e) = fs::write(&output, &compiled.code) {
+            whatever!("Cannot write {}: {}", output.display(), e)
+        }
+    }
+    Ok(())
+}
+
+/// Parses and resolves a schema and the schemas it imports, and generates
+/// Rust types for it. Errors are reported like rustc ones, with the
+/// offending line of the schema.
+pub fn compile_schema(path: &Path, options: &rust::Options) -> Result<Compiled, Whatever> {
+    let loaded = Workspace::default().load(path);
+    if !loaded.sources.contains_key(path) {
+        whatever!("Cannot read schema {}", path.display())
+    }
+
+    let mut problems = loaded.problems.clone();
+    let mut schema = None;
+    if problems.is_empty() {
+        let (validated, validation_problems) = loaded.validate();
+        schema = validated;
+        problems.extend(validation_problems);
+    }
+    let schema = match schema {
+        Some(schema) if problems.is_empty() => schema,
+        _ => {
+            let diagnostics: Vec<String> = problems
+                .iter()
+                .map(|problem| diagnostic(problem, loaded.sources.get(&problem.path)))
+                .collect();
+            whatever!("Invalid finitio schema {}\n\n{}", path.display(), diagnostics.join("\n"))
+        },
+    };
+
+    let generated = rust::generate(&schema, options);
+    let embedded = match serde_json::to_string(&resolvable(&loaded.fios, path)) {
+        Ok(json) => json,
+        Err(e) => whatever!("Cannot embed schema {}: {}", path.display(), e),
+    };
+    let code = format!(
+        "{}\n\
+        /// The finitio schema of the types above, resolved at build time\n\
+        pub const FINITIO_SCHEMA: &str = {:?};\n\
+        \n\
+        /// Loads the finitio schema of the types above\n\
+        pub fn finitio_schema() -> finitio::schema::Schema {{\n    \
+            finitio::build::embedded(FINITIO_SCHEMA)\n\
+        }}\n",
+        generated.code,
+        embedded
+    );
+
+    let mut dependencies: Vec<PathBuf> = loaded.sources.into_keys().collect();
+    dependencies.sort();
+    Ok(Compiled { dependencies, code, unchecked: generated.unchecked })
+}
+
+/// The type definitions of a schema and the schemas it imports, as a single
+/// schema that resolves without its files
+fn resolvable(fios: &HashMap<PathBuf, fio::Schema>, path: &Path) -> fio::Schema {
+    let entry = &fios[path];
+    let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+    let mut type_defs = entry.type_defs.clone();
+    for import in entry.imports.iter() {
+        if let Some(imported) = fios.get(&base_dir.join(&import.filename)) {
+            type_defs.extend(imported.type_defs.iter().cloned());
+        }
+    }
+    fio::Schema { imports: vec![], type_defs }
+}
+
+/// Loads a schema embedded by [`compile`]
+pub fn embedded(json: &str) -> Schema {
+    let fschema: fio::Schema = serde_json::from_str(json).expect("Embedded finitio schema is valid json");
+    let path = PathBuf::from("embedded.fio");
+    let mut schemas = Schema::from_fios(HashMap::from([(path.clone(), fschema)]))
+        .expect("Embedded finitio schema was resolved at build time");
+    schemas.remove(&path).expect("Found embedded schema in map of validated schemas")
+}
+
+/// A problem as reported by rustc
+fn diagnostic(problem: &Problem, source: Option<&String>) -> String {
+    let line = problem.position.line as usize;
+    let column = problem.position.column;
+    let mut diagnostic = format!(
+        "error: {}\n --> {}:{}:{}\n",
+        problem.message,
+        problem.path.display(),
+        line,
+        column
+    );
+    let text = source.and_then(|source| source.lines().nth(line.saturating_sub(1)));
+    if let Some(text) = text {
+        let gutter = " ".repeat(line.to_string().len());
+        diagnostic.push_str(&format!(
+            "{} |\n{} | {}\n{} | {}^\n",
+            gutter,
+            line,
+            text,
+            gutter,
+            " ".repeat(column.saturating_sub(1))
+        ));
+    }
+    diagnostic
+}
+
+#[cfg(test)]
+fn schema_dir(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = env::temp_dir().join(format!("finitio-build-{}-{}", name, std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    for (file, text) in files {
+        fs::write(dir.join(file), text).unwrap();
+    }
+    dir
+}
+
+#[test]
+fn test_compile_schema() {
+    let dir = schema_dir("compile", &[
+        ("main.fio", "@import scalars.fio\nPerson = { name: Name, age :? .Number }\n"),
+        ("scalars.fio", "Name = .String(s | len(s) > 0)\n"),
+    ]);
+    let compiled = compile_schema(&dir.join("main.fio"), &rust::Options::default()).unwrap();
+    assert_eq!(compiled.dependencies, vec![dir.join("main.fio"), dir.join("scalars.fio")]);
+    assert!(compiled.code.contains("pub struct Person {"));
+    assert!(compiled.code.contains("pub struct Name(String);"));
+    assert!(compiled.code.contains("pub fn finitio_schema() -> finitio::schema::Schema {"));
+
+    let json = compiled.code
+        .lines()
+        .find_map(|line| line.strip_prefix("pub const FINITIO_SCHEMA: &str = "))
+        .and_then(|literal| literal.strip_suffix(';'))
+        .unwrap();
+    let json: String = serde_json::from_str(json).unwrap();
+    let schema = embedded(&json);
+    assert_eq!(schema.types.keys().collect::<Vec<_>>(), vec!["Name", "Person"]);
+
+    let out_dir = dir.join("out");
+    fs::create_dir_all(&out_dir).unwrap();
+    let options = Options { out_dir: Some(out_dir.clone()), ..Options::default() };
+    compile_with(&[dir.join("main.fio")], &options).unwrap();
+    assert_eq!(fs::read_to_string(out_dir.join("main.rs")).unwrap(), compiled.code);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_compile_schema_errors() {
+    let dir = schema_dir("errors", &[
+        ("main.fio", "@import scalars.fio\nPerson = { name: Name, age: Age }\n"),
+        ("scalars.fio", "Name = .String\n"),
+        ("broken.fio", "@import scalars.fio\nPerson = { name: Name\n"),
+    ]);
+    let error = compile_schema(&dir.join("main.fio"), &rust::Options::default()).unwrap_err();
+    assert_eq!(error.to_string(), format!(
+        "Invalid finitio schema {}\n\nerror: no such type `Age`\n --> {}:2:29\n  |\n2 | Person = {{ name: Name, age: Age }}\n  |                             ^\n",
+        dir.join("main.fio").display(),
+        dir.join("main.fio").display(),
+    ));
+
+    let error = compile_schema(&dir.join("broken.fio"), &rust::Options::default()).unwrap_err();
+    assert!(error.to_string().contains(&format!(" --> {}:2:", dir.join("broken.fio").display())));
+    assert!(compile_schema(&dir.join("missing.fio"), &rust::Options::default()).is_err());
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,267 @@
+//! Generators of source code mirroring the types of a finitio schema
+
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::schema::{heading::Heading, Constraint, Type};
+
+pub mod python;
+pub mod rust;
+pub mod typescript;
+
+/// Generated source code, and the constraints it does not check
+#[derive(Clone, Debug)]
+pub struct Generated {
+    pub code: String,
+    pub unchecked: Vec<Unchecked>,
+}
+
+/// A constraint of a finitio type that the generated code does not check, it
+/// accepts more values than the finitio type
+#[derive(Clone, Debug, PartialEq)]
+pub struct Unchecked {
+    /// The name of the type definition
+    pub type_name: String,
+    pub constraint: String,
+}
+
+impl fmt::Display for Unchecked {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: `{}` is not checked by the generated code", self.type_name, self.constraint)
+    }
+}
+
+/// What the value of a sub type is made of, as far as constraints are
+/// concerned
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Kind {
+    Number,
+    String,
+    Collection,
+}
+
+/// What a constraint compares with a literal: the value, its length or its
+/// remainder by a divisor
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Subject {
+    Value,
+    Length,
+    Remainder(f64),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Literal {
+    Number(f64),
+    String(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Comparison {
+    pub subject: Subject,
+    /// One of `<=`, `>=`, `==`, `!=`, `<` and `>`
+    pub op: &'static str,
+    pub literal: Literal,
+}
+
+/// Translates constraints made of comparisons of the value (its length, or
+/// its remainder) with literals, joined by `&&` or by `||`, as alternatives of
+/// conjunctions of comparisons. None if some comparison is something else, or
+/// makes no sense for the kind of value.
+pub(crate) fn comparisons(constraint: &Constraint, kind: Kind) -> Option<Vec<Vec<Comparison>>> {
+    // The expression engine does not give `&&` precedence over `||`, mixing
+    // them is left unchecked rather than translated with another meaning
+    if constraint.expr.contains("&&") && constraint.expr.contains("||") {
+        return None;
+    }
+    let mut alternatives = vec![];
+    for alternative in constraint.expr.split("||") {
+        let mut parts = vec![];
+        for part in alternative.split("&&") {
+            parts.push(comparison(unparenthesize(part.trim()), &constraint.param, kind)?);
+        }
+        alternatives.push(parts);
+    }
+    Some(alternatives)
+}
+
+/// Renders alternatives of conjunctions of comparisons with the given `and`
+/// and `or` operators
+pub(crate) fn render<F>(alternatives: &[Vec<Comparison>], and: &str, or: &str, comparison: F) -> String
+where
+    F: Fn(&Comparison) -> String,
+{
+    let conjunctions: Vec<String> = alternatives
+        .iter()
+        .map(|parts| parts.iter().map(&comparison).collect::<Vec<_>>().join(&format!(" {} ", and)))
+        .collect();
+    if conjunctions.len() == 1 {
+        return conjunctions[0].clone();
+    }
+    conjunctions.iter().map(|c| format!("({})", c)).collect::<Vec<_>>().join(&format!(" {} ", or))
+}
+
+fn unparenthesize(expr: &str) -> &str {
+    match expr.strip_prefix('(').and_then(|e| e.strip_suffix(')')) {
+        Some(inner) if !inner.contains(['(', ')']) => inner.trim(),
+        _ => expr,
+    }
+}
+
+fn comparison(expr: &str, param: &str, kind: Kind) -> Option<Comparison> {
+    let (lhs, op, rhs) = ["<=", ">=", "==", "!=", "<", ">"]
+        .iter()
+        .find_map(|op| expr.split_once(op).map(|(lhs, rhs)| (lhs.trim(), *op, rhs.trim())))?;
+    let length = format!("len({})", param);
+    let remainder = format!("{} %", param);
+    let is_subject = |s: &str| s == param || s == length || s.starts_with(&remainder);
+    // Literals on the left are flipped to the right
+    let (subject, op, literal) = if is_subject(lhs) {
+        (lhs, op, rhs)
+    } else {
+        let flipped = match op {
+            "<=" => ">=",
+            ">=" => "<=",
+            "<" => ">",
+            ">" => "<",
+            op => op,
+        };
+        (rhs, flipped, lhs)
+    };
+
+    let subject = if subject == param {
+        Subject::Value
+    } else if subject == length {
+        Subject::Length
+    } else {
+        let divisor = subject.strip_prefix(&remainder)?;
+        Subject::Remainder(divisor.trim().parse().ok()?)
+    };
+    let literal = match (kind, &subject) {
+        (Kind::String | Kind::Collection, Subject::Length) => {
+            let n: usize = literal.parse().ok()?;
+            Literal::Number(n as f64)
+        },
+        (Kind::Number, Subject::Value | Subject::Remainder(_)) => Literal::Number(literal.parse().ok()?),
+        (Kind::String, Subject::Value) if op == "==" || op == "!=" => {
+            Literal::String(serde_json::from_str(literal).ok()?)
+        },
+        _ => return None,
+    };
+    Some(Comparison { subject, op, literal })
+}
+
+/// The names of the type definitions a type refers to
+pub(crate) fn references(t: &Type, refs: &mut Vec<String>) {
+    let heading = |heading: &Heading, refs: &mut Vec<String>| {
+        for attribute in heading.attributes.values() {
+            references(&attribute.att_type, refs);
+        }
+        if let Some(extra) = &heading.allow_extra {
+            references(extra, refs);
+        }
+    };
+    match t {
+        Type::Any(_) | Type::Nil(_) | Type::Builtin(_) => {},
+        Type::Ref(r) => refs.push(r.name().to_string()),
+        Type::Seq(s) => references(&s.elm_type, refs),
+        Type::Set(s) => references(&s.elm_type, refs),
+        Type::Union(u) => u.candidates.iter().for_each(|c| references(c, refs)),
+        Type::Struct(s) => s.elements.iter().for_each(|elm| references(elm, refs)),
+        Type::Sub(s) => references(&s.base_type, refs),
+        Type::Tuple(t) => heading(&t.heading, refs),
+        Type::Relation(r) => heading(&r.heading, refs),
+    }
+}
+
+/// The words of an identifier: `firstName`, `first_name` and `first.name`
+/// are all made of `first` and `name`
+fn words(name: &str) -> Vec<String> {
+    let chars: Vec<char> = name.chars().collect();
+    let mut words = vec![];
+    let mut word = String::new();
+    for (i, c) in chars.iter().enumerate() {
+        if !c.is_ascii_alphanumeric() {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            continue;
+        }
+        // A new word starts at `fooBar`, and at the last capital of `URLPath`
+        let prev = if i > 0 { Some(chars[i - 1]) } else { None };
+        let next = chars.get(i + 1);
+        let starts = c.is_ascii_uppercase()
+            && match prev {
+                Some(p) if p.is_ascii_lowercase() || p.is_ascii_digit() => true,
+                Some(p) if p.is_ascii_uppercase() => next.is_some_and(|n| n.is_ascii_lowercase()),
+                _ => false,
+            };
+        if starts && !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+        word.push(*c);
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+/// `first_name` as `FirstName`
+pub(crate) fn pascal_case(name: &str) -> String {
+    words(name)
+        .iter()
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(c) => c.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+/// `firstName` as `first_name`
+pub(crate) fn snake_case(name: &str) -> String {
+    words(name).iter().map(|word| word.to_ascii_lowercase()).collect::<Vec<_>>().join("_")
+}
+
+/// The first of `name`, `name2`, `name3`... that is not taken yet
+pub(crate) fn unique_name(names: &mut HashSet<String>, name: &str) -> String {
+    let mut unique = name.to_string();
+    let mut index = 1;
+    while names.contains(&unique) {
+        index += 1;
+        unique = format!("{}{}", name, index);
+    }
+    names.insert(unique.clone());
+    unique
+}
+
+#[test]
+fn test_case_conversions() {
+    assert_eq!(pascal_case("person"), "Person");
+    assert_eq!(pascal_case("first_name"), "FirstName");
+    assert_eq!(pascal_case("Shape1"), "Shape1");
+    assert_eq!(pascal_case("geo.Point"), "GeoPoint");
+    assert_eq!(snake_case("firstName"), "first_name");
+    assert_eq!(snake_case("URLPath"), "url_path");
+    assert_eq!(snake_case("id"), "id");
+    assert_eq!(snake_case("a.b"), "a_b");
+}
+
+#[test]
+fn test_comparisons_mixed_operators() {
+    let constraint = |expr: &str| Constraint::new(
+        "n".to_string(),
+        expr.to_string(),
+        crate::common::FilePosition { line: 1, column: 1 },
+    );
+    let shape = |expr: &str| comparisons(&constraint(expr), Kind::Number)
+        .map(|alternatives| alternatives.iter().map(Vec::len).collect::<Vec<_>>());
+
+    assert_eq!(shape(" n > 0 && n < 10"), Some(vec![2]));
+    assert_eq!(shape(" n < 0 || n > 10 || n == 5"), Some(vec![1, 1, 1]));
+    // `&&` does not bind tighter than `||` for the expression engine
+    assert_eq!(shape(" n > 10 && n < 0 || n == 5"), None);
+    assert_eq!(shape(" n == 5 || n > 10 && n < 0"), None);
+}
@@ -0,0 +1,609 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use crate::schema::{heading::Heading, relation::Relation, sub::Sub, tuple::Tuple, Constraint, Schema, Type};
+
+use super::{comparisons, pascal_case, references, render, snake_case, unique_name, Generated, Kind, Literal, Subject, Unchecked};
+
+/// Maximum number of references followed to find what a type is made of
+const MAX_DEPTH: usize = 64;
+
+/// Keywords, and names of the `BaseModel` API, suffixed by `_` when used as
+/// field names
+const RESERVED: [&str; 41] = [
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del",
+    "elif", "else", "except", "finally", "for", "from", "global", "if", "import", "in", "is", "lambda", "nonlocal",
+    "not", "or", "pass", "raise", "return", "try", "while", "with", "yield", "construct", "copy", "dict", "json",
+    "schema", "validate",
+];
+
+/// The names imported by the generated code, that types must not shadow
+const IMPORTED: [&str; 11] = [
+    "AfterValidator", "Annotated", "Any", "BaseModel", "ConfigDict", "Dict", "Field", "List", "Optional", "Tuple",
+    "Union",
+];
+
+/// Generates pydantic (v2) models for all the type definitions of a schema,
+/// for the JSON data the finitio types include.
+///
+/// Tuples are models with `None` defaults for their optional attributes,
+/// unions are `Union`s, seqs are lists, sets are lists of distinct elements,
+/// structs are tuples and relations are lists of distinct row models. Sub types are `Annotated` aliases of their base type, whose
+/// validator checks the constraints it can translate. Attributes that are not
+/// valid Python names are aliased, models are dumped with `by_alias=True` and
+/// `exclude_unset=True` to get the original JSON back.
+///
+/// Models are strict, as finitio does not coerce values, and sets and
+/// relations reject duplicated elements. Strict models only accept dates and
+/// tuples from JSON, so data is validated with `model_validate_json` (or
+/// `TypeAdapter.validate_json`).
+pub fn generate(schema: &Schema) -> Generated {
+    let mut names: HashSet<String> = IMPORTED.iter().map(|name| name.to_string()).collect();
+    let py_names: HashMap<String, String> = schema.types
+        .keys()
+        .map(|name| (name.clone(), unique_name(&mut names, &pascal_case(name))))
+        .collect();
+    let mut generator = Generator {
+        schema,
+        py_names,
+        names,
+        emitted: HashSet::new(),
+        items: vec![],
+        typing: BTreeSet::new(),
+        pydantic: BTreeSet::new(),
+        datetime: false,
+        distinct: None,
+        unchecked: vec![],
+        type_name: String::new(),
+    };
+    for name in schema.types.keys() {
+        generator.emit(name, &mut HashSet::new());
+    }
+
+    let mut code = String::from("# Generated by finitio, do not edit\n\nfrom __future__ import annotations\n\n");
+    if generator.datetime {
+        code.push_str("import datetime\n");
+    }
+    if !generator.typing.is_empty() {
+        let typing: Vec<&str> = generator.typing.iter().copied().collect();
+        code.push_str(&format!("from typing import {}\n", typing.join(", ")));
+    }
+    if !generator.pydantic.is_empty() {
+        let pydantic: Vec<&str> = generator.pydantic.iter().copied().collect();
+        code.push_str(&format!("\nfrom pydantic import {}\n", pydantic.join(", ")));
+    }
+    if let Some(distinct) = &generator.distinct {
+        code.push_str(&format!(
+            "\n\ndef {}(value: List[Any]) -> List[Any]:\n    \"\"\"Rejects duplicated elements, that sets and relations cannot hold\"\"\"\n    \
+            if any(element in value[:index] for index, element in enumerate(value)):\n        \
+            raise ValueError(f\"{{value!r}} contains duplicated elements\")\n    return value\n",
+            distinct
+        ));
+    }
+    for item in generator.items.iter() {
+        code.push_str("\n\n");
+        code.push_str(item);
+    }
+    Generated { code, unchecked: generator.unchecked }
+}
+
+fn field_name(name: &str) -> String {
+    let name = snake_case(name);
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{}", name)
+    } else if RESERVED.contains(&name.as_str()) {
+        format!("{}_", name)
+    } else {
+        name
+    }
+}
+
+/// A Python string literal
+fn string(s: &str) -> String {
+    serde_json::Value::String(s.to_string()).to_string()
+}
+
+/// A docstring for a `/- meta -/`
+fn docstring(meta: Option<&str>, indent: &str) -> String {
+    let meta = match meta.map(str::trim) {
+        Some(meta) if !meta.is_empty() => meta.replace('\\', "\\\\").replace("\"\"\"", "\\\"\\\"\\\""),
+        _ => return String::new(),
+    };
+    let lines: Vec<&str> = meta.lines().map(str::trim).collect();
+    if lines.len() == 1 {
+        return format!("{}\"\"\"{}\"\"\"\n", indent, lines[0]);
+    }
+    let mut docstring = format!("{}\"\"\"{}\n", indent, lines[0]);
+    for line in lines[1..].iter() {
+        docstring.push_str(&format!("{}{}\n", indent, line).replace(&format!("{}\n", indent), "\n"));
+    }
+    docstring.push_str(&format!("{}\"\"\"\n", indent));
+    docstring
+}
+
+/// The type made optional by a union with Nil
+fn optional(candidates: &[Type]) -> Option<&Type> {
+    match candidates {
+        [Type::Nil(_), t] | [t, Type::Nil(_)] if !matches!(t, Type::Nil(_)) => Some(t),
+        _ => None,
+    }
+}
+
+struct Generator<'a> {
+    schema: &'a Schema,
+    /// The Python names of the type definitions
+    py_names: HashMap<String, String>,
+    /// All the Python names in use
+    names: HashSet<String>,
+    /// The type definitions generated so far
+    emitted: HashSet<String>,
+    items: Vec<String>,
+    /// The names imported from `typing` and `pydantic`
+    typing: BTreeSet<&'static str>,
+    pydantic: BTreeSet<&'static str>,
+    datetime: bool,
+    /// The name of the validator rejecting duplicated elements, once used
+    distinct: Option<String>,
+    unchecked: Vec<Unchecked>,
+    /// The type definition being generated
+    type_name: String,
+}
+
+impl<'a> Generator<'a> {
+    fn report(&mut self, constraint: String) {
+        self.unchecked.push(Unchecked { type_name: self.type_name.clone(), constraint });
+    }
+
+    fn target(&self, name: &str) -> Option<Type> {
+        self.schema.types.get(name).map(|typedef| typedef.target_type())
+    }
+
+    /// Generates a type definition after the ones it refers to, since type
+    /// aliases are evaluated when defined
+    fn emit(&mut self, name: &str, visiting: &mut HashSet<String>) {
+        if self.emitted.contains(name) || !visiting.insert(name.to_string()) {
+            return;
+        }
+        let typedef = match self.schema.types.get(name) {
+            Some(typedef) => typedef,
+            None => return,
+        };
+        let target = typedef.target_type();
+        let mut refs = vec![];
+        references(&target, &mut refs);
+        for r in refs.iter() {
+            self.emit(r, visiting);
+        }
+
+        self.type_name = name.to_string();
+        let py_name = self.py_names[name].clone();
+        let docs = docstring(typedef.meta(), "");
+        self.typedef(&py_name, &target, &docs);
+        self.emitted.insert(name.to_string());
+    }
+
+    fn typedef(&mut self, name: &str, t: &Type, docs: &str) {
+        match t {
+            Type::Tuple(tuple) => self.tuple(name, tuple, docs),
+            Type::Sub(sub) => self.sub(name, sub, docs),
+            Type::Relation(relation) => {
+                let list = self.relation(name, relation);
+                self.items.push(format!("{} = {}\n{}", name, list, docs));
+            },
+            t => {
+                let py_type = self.py_type(t, name);
+                self.items.push(format!("{} = {}\n{}", name, py_type, docs));
+            },
+        }
+    }
+
+    fn typing(&mut self, name: &'static str) -> &'static str {
+        self.typing.insert(name);
+        name
+    }
+
+    fn pydantic(&mut self, name: &'static str) -> &'static str {
+        self.pydantic.insert(name);
+        name
+    }
+
+    /// The list type of a set or relation, whose elements must be distinct
+    fn distinct(&mut self, elm_type: &str) -> String {
+        let distinct = match &self.distinct {
+            Some(distinct) => distinct.clone(),
+            None => {
+                self.typing("Any");
+                let distinct = unique_name(&mut self.names, "_distinct");
+                self.distinct = Some(distinct.clone());
+                distinct
+            },
+        };
+        let annotated = self.typing("Annotated");
+        let list = self.typing("List");
+        let validator = self.pydantic("AfterValidator");
+        format!("{}[{}[{}], {}({})]", annotated, list, elm_type, validator, distinct)
+    }
+
+    /// The Python type of an inline type, `hint` names the classes and
+    /// aliases generated for it
+    fn py_type(&mut self, t: &Type, hint: &str) -> String {
+        match t {
+            Type::Any(_) => self.typing("Any").to_string(),
+            Type::Nil(_) => String::from("None"),
+            Type::Builtin(b) => match b.target.as_str() {
+                "Number" => String::from("float"),
+                "String" => String::from("str"),
+                "Boolean" => String::from("bool"),
+                "Date" => {
+                    self.datetime = true;
+                    String::from("datetime.date")
+                },
+                "DateTime" => {
+                    self.datetime = true;
+                    String::from("datetime.datetime")
+                },
+                target => {
+                    self.report(format!(".{}", target));
+                    self.typing("Any").to_string()
+                },
+            },
+            Type::Ref(r) => {
+                let name = self.py_names.get(r.name()).cloned().unwrap_or_else(|| pascal_case(r.name()));
+                // Not defined yet, it is part of a cycle
+                if self.emitted.contains(r.name()) {
+                    name
+                } else {
+                    string(&name)
+                }
+            },
+            Type::Seq(s) => {
+                let elm_type = self.py_type(&s.elm_type, &format!("{}Item", hint));
+                format!("{}[{}]", self.typing("List"), elm_type)
+            },
+            Type::Set(s) => {
+                let elm_type = self.py_type(&s.elm_type, &format!("{}Item", hint));
+                self.distinct(&elm_type)
+            },
+            Type::Union(u) => match optional(&u.candidates) {
+                Some(t) => {
+                    let py_type = self.py_type(t, hint);
+                    format!("{}[{}]", self.typing("Optional"), py_type)
+                },
+                None => {
+                    let candidates: Vec<String> = u.candidates
+                        .iter()
+                        .enumerate()
+                        .map(|(index, c)| self.py_type(c, &format!("{}{}", hint, index + 1)))
+                        .collect();
+                    format!("{}[{}]", self.typing("Union"), candidates.join(", "))
+                },
+            },
+            Type::Struct(s) => {
+                let elements: Vec<String> = s.elements
+                    .iter()
+                    .enumerate()
+                    .map(|(index, elm)| self.py_type(elm, &format!("{}{}", hint, index + 1)))
+                    .collect();
+                format!("{}[{}]", self.typing("Tuple"), elements.join(", "))
+            },
+            Type::Sub(s) => self.item(hint, |g, name| g.sub(name, s, "")),
+            Type::Tuple(t) => self.item(hint, |g, name| g.tuple(name, t, "")),
+            Type::Relation(r) => self.relation(hint, r),
+        }
+    }
+
+    /// Generates a class or alias named after `hint` and returns its name
+    fn item<F: FnOnce(&mut Self, &str)>(&mut self, hint: &str, generate: F) -> String {
+        let name = unique_name(&mut self.names, hint);
+        generate(self, &name);
+        name
+    }
+
+    /// Generates the model of a heading
+    fn model(&mut self, name: &str, heading: &Heading, docs: &str) {
+        let mut attribute_names: Vec<&String> = heading.attributes.keys().collect();
+        attribute_names.sort();
+        let mut field_names = HashSet::new();
+        let mut fields = String::new();
+        let mut aliased = false;
+        for attribute_name in attribute_names {
+            let attribute = &heading.attributes[attribute_name];
+            let field = unique_name(&mut field_names, &field_name(attribute_name));
+            let mut py_type = self.py_type(&attribute.att_type, &format!("{}{}", name, pascal_case(attribute_name)));
+            let mut field_args = vec![];
+            if attribute.optional {
+                if !py_type.starts_with("Optional[") {
+                    py_type = format!("{}[{}]", self.typing("Optional"), py_type);
+                }
+                field_args.push(String::from("default=None"));
+            }
+            if &field != attribute_name {
+                aliased = true;
+                field_args.push(format!("alias={}", string(attribute_name)));
+            }
+            let default = match field_args.as_slice() {
+                [] => String::new(),
+                [default] if default == "default=None" => String::from(" = None"),
+                args => format!(" = {}({})", self.pydantic("Field"), args.join(", ")),
+            };
+            fields.push_str(&format!("    {}: {}{}\n", field, py_type, default));
+            fields.push_str(&docstring(attribute.meta.as_deref(), "    "));
+        }
+
+        let mut config = vec![];
+        match &heading.allow_extra {
+            None => config.push(String::from("extra=\"forbid\"")),
+            Some(extra) => {
+                config.push(String::from("extra=\"allow\""));
+                if !matches!(**extra, Type::Any(_)) {
+                    let py_type = self.py_type(extra, &format!("{}Extra", name));
+                    let dict = self.typing("Dict");
+                    let field = self.pydantic("Field");
+                    fields.push_str(&format!("    __pydantic_extra__: {}[str, {}] = {}(init=False)\n", dict, py_type, field));
+                }
+            },
+        }
+        if aliased {
+            config.push(String::from("populate_by_name=True"));
+        }
+        config.push(String::from("strict=True"));
+
+        let mut class = format!("class {}({}):\n", name, self.pydantic("BaseModel"));
+        if !docs.is_empty() {
+            class.push_str(&docs.lines().map(|line| format!("    {}\n", line).replace("    \n", "\n")).collect::<String>());
+            class.push('\n');
+        }
+        class.push_str(&format!("    model_config = {}({})\n", self.pydantic("ConfigDict"), config.join(", ")));
+        if !fields.is_empty() {
+            class.push('\n');
+            class.push_str(&fields);
+        }
+        self.items.push(class);
+    }
+
+    fn tuple(&mut self, name: &str, tuple: &Tuple, docs: &str) {
+        for fk in tuple.foreign_keys.iter() {
+            self.report(format!(
+                "ref({}({}) -> {}({}))",
+                fk.source,
+                fk.source_attributes.join(", "),
+                fk.target,
+                fk.target_attributes.join(", ")
+            ));
+        }
+        self.model(name, &tuple.heading, docs);
+    }
+
+    /// Generates the row model of a relation and returns its list type
+    fn relation(&mut self, name: &str, relation: &Relation) -> String {
+        for key in relation.keys.iter() {
+            self.report(format!("key({})", key.join(", ")));
+        }
+        let row = unique_name(&mut self.names, &format!("{}Row", name));
+        self.model(&row, &relation.heading, "");
+        self.distinct(&row)
+    }
+
+    fn sub(&mut self, name: &str, sub: &Sub, docs: &str) {
+        let base = self.py_type(&sub.base_type, &format!("{}Base", name));
+        let kind = self.kind(&sub.base_type, 0);
+
+        let mut checks = String::new();
+        let mut comments = String::new();
+        for constraint in sub.constraints.iter() {
+            let text = format!("{} | {}", constraint.param, constraint.expr.trim());
+            match kind.and_then(|kind| condition(constraint, kind)) {
+                Some(condition) => checks.push_str(&format!(
+                    "    if not ({}):\n        raise ValueError(f\"{{value!r}} does not satisfy `{}`\")\n",
+                    condition,
+                    text.replace('{', "{{").replace('}', "}}").replace('"', "\\\"")
+                )),
+                None => {
+                    comments.push_str(&format!("# Not checked: `{}`\n", text));
+                    self.report(text);
+                },
+            }
+        }
+
+        if checks.is_empty() {
+            self.items.push(format!("{}{} = {}\n{}", comments, name, base, docs));
+            return;
+        }
+        let check = unique_name(&mut self.names, &format!("_check_{}", snake_case(name)));
+        let validator = self.pydantic("AfterValidator");
+        let annotated = self.typing("Annotated");
+        self.items.push(format!(
+            "def {check}(value: {base}) -> {base}:\n{checks}    return value\n\n\n\
+            {comments}{name} = {annotated}[{base}, {validator}({check})]\n{docs}",
+            check = check,
+            base = base,
+            checks = checks,
+            comments = comments,
+            name = name,
+            annotated = annotated,
+            validator = validator,
+            docs = docs,
+        ));
+    }
+
+    /// What the value of a type is made of
+    fn kind(&self, t: &Type, depth: usize) -> Option<Kind> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        match t {
+            Type::Builtin(b) if b.target == "Number" => Some(Kind::Number),
+            Type::Builtin(b) if b.target == "String" => Some(Kind::String),
+            Type::Seq(_) | Type::Set(_) | Type::Relation(_) => Some(Kind::Collection),
+            Type::Sub(s) => self.kind(&s.base_type, depth + 1),
+            Type::Ref(r) => self.kind(&self.target(r.name())?, depth + 1),
+            _ => None,
+        }
+    }
+}
+
+/// A constraint as a Python condition on `value`
+fn condition(constraint: &Constraint, kind: Kind) -> Option<String> {
+    let alternatives = comparisons(constraint, kind)?;
+    Some(render(&alternatives, "and", "or", |comparison| {
+        let op = comparison.op;
+        match (&comparison.subject, &comparison.literal) {
+            (Subject::Length, Literal::Number(n)) => format!("len(value) {} {}", op, n),
+            (Subject::Remainder(d), Literal::Number(r)) => format!("value % {} {} {}", d, op, r),
+            (_, Literal::Number(n)) => format!("value {} {}", op, n),
+            (_, Literal::String(s)) => format!("value {} {}", op, string(s)),
+        }
+    }))
+}
+
+#[cfg(test)]
+fn generate_source(source: &str) -> Generated {
+    let schema = crate::schema::schema::test_schema(source);
+    generate(&schema)
+}
+
+#[test]
+fn test_generate_models() {
+    let generated = generate_source(r#"
+/- A person -/
+Person = {
+  /- The full name -/
+  name: Name
+  firstName :? .String
+  born: .Date
+  tags: {.String}
+  shape: Shape
+  friends: [Person]
+  ...: .Number
+}
+Name = .String(s | len(s) > 0 && len(s) <= 10)
+Shape = Circle | <.Number, .Number> | Nil
+Circle = { radius: .Number(r | r > 0 || r == 100), center :? { x: .Number, y: .Number } }
+Catalog = {{ id: .String, price: .Number }} key(id)
+Weird = .Number(n | n > other)
+"#);
+    assert_eq!(generated.code, r#"# Generated by finitio, do not edit
+
+from __future__ import annotations
+
+import datetime
+from typing import Annotated, Any, Dict, List, Optional, Tuple, Union
+
+from pydantic import AfterValidator, BaseModel, ConfigDict, Field
+
+
+def _distinct(value: List[Any]) -> List[Any]:
+    """Rejects duplicated elements, that sets and relations cannot hold"""
+    if any(element in value[:index] for index, element in enumerate(value)):
+        raise ValueError(f"{value!r} contains duplicated elements")
+    return value
+
+
+class CatalogRow(BaseModel):
+    model_config = ConfigDict(extra="forbid", strict=True)
+
+    id: str
+    price: float
+
+
+Catalog = Annotated[List[CatalogRow], AfterValidator(_distinct)]
+
+
+class CircleCenter(BaseModel):
+    model_config = ConfigDict(extra="forbid", strict=True)
+
+    x: float
+    y: float
+
+
+def _check_circle_radius(value: float) -> float:
+    if not ((value > 0) or (value == 100)):
+        raise ValueError(f"{value!r} does not satisfy `r | r > 0 || r == 100`")
+    return value
+
+
+CircleRadius = Annotated[float, AfterValidator(_check_circle_radius)]
+
+
+class Circle(BaseModel):
+    model_config = ConfigDict(extra="forbid", strict=True)
+
+    center: Optional[CircleCenter] = None
+    radius: CircleRadius
+
+
+def _check_name(value: str) -> str:
+    if not (len(value) > 0 and len(value) <= 10):
+        raise ValueError(f"{value!r} does not satisfy `s | len(s) > 0 && len(s) <= 10`")
+    return value
+
+
+Name = Annotated[str, AfterValidator(_check_name)]
+
+
+Shape = Union[Circle, Tuple[float, float], None]
+
+
+class Person(BaseModel):
+    """A person"""
+
+    model_config = ConfigDict(extra="allow", populate_by_name=True, strict=True)
+
+    born: datetime.date
+    first_name: Optional[str] = Field(default=None, alias="firstName")
+    friends: List["Person"]
+    name: Name
+    """The full name"""
+    shape: Shape
+    tags: Annotated[List[str], AfterValidator(_distinct)]
+    __pydantic_extra__: Dict[str, float] = Field(init=False)
+
+
+# Not checked: `n | n > other`
+Weird = float
+"#);
+    assert_eq!(generated.unchecked, vec![
+        Unchecked { type_name: String::from("Catalog"), constraint: String::from("key(id)") },
+        Unchecked { type_name: String::from("Weird"), constraint: String::from("n | n > other") },
+    ]);
+}
+
+/// Validates JSON documents with the models generated for a type, needs
+/// `python3` with pydantic installed
+#[cfg(test)]
+fn validate_with_pydantic(source: &str, type_name: &str, documents: &[serde_json::Value]) -> Vec<bool> {
+    use std::process::Command;
+
+    let dir = std::env::temp_dir().join(format!("finitio-python-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("models.py"), generate_source(source).code).unwrap();
+    let script = format!(
+        "import json, sys\nfrom pydantic import TypeAdapter\nfrom models import {}\n\
+        for line in sys.argv[1:]:\n    \
+        try:\n        TypeAdapter({}).validate_json(line)\n        print(\"valid\")\n    \
+        except ValueError:\n        print(\"invalid\")\n",
+        type_name, type_name
+    );
+    let output = Command::new("python3")
+        .current_dir(&dir)
+        .arg("-c")
+        .arg(script)
+        .args(documents.iter().map(|document| document.to_string()))
+        .output()
+        .expect("python3 is not available");
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout).unwrap().lines().map(|line| line == "valid").collect()
+}
+
+#[test]
+#[ignore = "needs python3 with pydantic, run with --ignored"]
+fn test_generated_models_reject_invalid_data() {
+    let source = "Person = { age: .Number, born: .Date, tags: {.String}, friends :? {{ id: .Number }} }";
+    let documents = [
+        serde_json::json!({ "age": 42, "born": "2000-01-01", "tags": ["a", "b"], "friends": [{ "id": 1 }] }),
+        serde_json::json!({ "age": "42", "born": "2000-01-01", "tags": [] }),
+        serde_json::json!({ "age": 42, "born": "2000-01-01", "tags": ["a", "a"] }),
+        serde_json::json!({ "age": 42, "born": "2000-01-01", "tags": [], "friends": [{ "id": 1 }, { "id": 1 }] }),
+    ];
+    assert_eq!(validate_with_pydantic(source, "Person", &documents), vec![true, false, false, false]);
+}
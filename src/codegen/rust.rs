@@ -0,0 +1,643 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::schema::{heading::Heading, r#struct::Struct, relation::Relation, sub::Sub, tuple::Tuple, Constraint, Schema, Type};
+
+use super::{comparisons, pascal_case, references, render, snake_case, unique_name, Generated, Kind, Literal, Subject, Unchecked};
+
+/// Maximum number of references followed to find what a type is made of
+const MAX_DEPTH: usize = 64;
+
+/// Keywords that are prefixed by `r#` when used as field names
+const KEYWORDS: [&str; 42] = [
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn", "else", "enum",
+    "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let", "loop", "macro", "match", "mod", "move",
+    "mut", "override", "priv", "pub", "ref", "return", "static", "struct", "trait", "true", "try", "type", "typeof",
+    "where",
+];
+
+/// Keywords that cannot be raw identifiers, suffixed by `_` instead
+const RESERVED: [&str; 4] = ["crate", "self", "Self", "super"];
+
+const DERIVES: &str = "Clone, Debug, PartialEq";
+/// Derived when the type has no floats nor arbitrary JSON values
+const TOTAL_DERIVES: &str = "Eq, Hash, PartialOrd, Ord";
+
+/// The collection used for sets and relations, whose elements must be totally
+/// ordered (resp. hashable), `Vec` is used otherwise
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SetCollection {
+    #[default]
+    BTreeSet,
+    HashSet,
+}
+
+impl SetCollection {
+    pub const ALL: [SetCollection; 2] = [SetCollection::BTreeSet, SetCollection::HashSet];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SetCollection::BTreeSet => "BTreeSet",
+            SetCollection::HashSet => "HashSet",
+        }
+    }
+}
+
+impl fmt::Display for SetCollection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for SetCollection {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        SetCollection::ALL
+            .iter()
+            .find(|c| c.name() == name)
+            .copied()
+            .ok_or_else(|| format!("Unknown set collection: {}", name))
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Options {
+    pub sets: SetCollection,
+}
+
+/// Generates Rust types for all the type definitions of a schema, with serde
+/// derives matching the JSON data the finitio types include.
+///
+/// Tuples are structs with `Option` fields for their optional attributes,
+/// unions are untagged enums (or `Option` for a union with `Nil`), structs are
+/// tuple structs and sub types are newtypes whose constructor checks the
+/// constraints. The generated code depends on `serde`, `serde_json` and
+/// `chrono` (with its `serde` feature) for dates.
+pub fn generate(schema: &Schema, options: &Options) -> Generated {
+    let mut names = HashSet::new();
+    let rust_names: HashMap<String, String> = schema.types
+        .keys()
+        .map(|name| (name.clone(), unique_name(&mut names, &rust_type_name(name))))
+        .collect();
+    let mut generator = Generator {
+        schema,
+        options,
+        rust_names,
+        names,
+        items: vec![],
+        unchecked: vec![],
+        type_name: String::new(),
+    };
+    for (name, typedef) in schema.types.iter() {
+        generator.type_name = name.clone();
+        let rust_name = generator.rust_names[name].clone();
+        let docs = docs(typedef.meta(), "");
+        generator.typedef(&rust_name, &typedef.target_type(), &docs);
+    }
+
+    let mut code = String::from("// Generated by finitio, do not edit\n");
+    for item in generator.items.iter() {
+        code.push('\n');
+        code.push_str(item);
+    }
+    Generated { code, unchecked: generator.unchecked }
+}
+
+fn rust_type_name(name: &str) -> String {
+    let name = pascal_case(name);
+    if RESERVED.contains(&name.as_str()) {
+        format!("{}_", name)
+    } else {
+        name
+    }
+}
+
+fn field_name(name: &str) -> String {
+    let name = snake_case(name);
+    if RESERVED.contains(&name.as_str()) {
+        format!("{}_", name)
+    } else if KEYWORDS.contains(&name.as_str()) {
+        format!("r#{}", name)
+    } else {
+        name
+    }
+}
+
+/// Doc comments for a `/- meta -/`
+fn docs(meta: Option<&str>, indent: &str) -> String {
+    match meta {
+        Some(meta) => meta
+            .trim()
+            .lines()
+            .map(|line| format!("{}/// {}\n", indent, line.trim()).replace("/// \n", "///\n"))
+            .collect(),
+        None => String::new(),
+    }
+}
+
+fn derives(total: bool) -> String {
+    if total {
+        format!("#[derive({}, {}, serde::Serialize, serde::Deserialize)]\n", DERIVES, TOTAL_DERIVES)
+    } else {
+        format!("#[derive({}, serde::Serialize, serde::Deserialize)]\n", DERIVES)
+    }
+}
+
+/// The type made optional by a union with Nil
+fn optional(candidates: &[Type]) -> Option<&Type> {
+    match candidates {
+        [Type::Nil(_), t] | [t, Type::Nil(_)] if !matches!(t, Type::Nil(_)) => Some(t),
+        _ => None,
+    }
+}
+
+struct Generator<'a> {
+    schema: &'a Schema,
+    options: &'a Options,
+    /// The Rust names of the type definitions
+    rust_names: HashMap<String, String>,
+    /// All the Rust names in use
+    names: HashSet<String>,
+    items: Vec<String>,
+    unchecked: Vec<Unchecked>,
+    /// The type definition being generated
+    type_name: String,
+}
+
+impl<'a> Generator<'a> {
+    fn report(&mut self, constraint: String) {
+        self.unchecked.push(Unchecked { type_name: self.type_name.clone(), constraint });
+    }
+
+    fn target(&self, name: &str) -> Option<Type> {
+        self.schema.types.get(name).map(|typedef| typedef.target_type())
+    }
+
+    /// Generates the item(s) of a type definition
+    fn typedef(&mut self, name: &str, t: &Type, docs: &str) {
+        match t {
+            Type::Union(u) if optional(&u.candidates).is_none() => self.union(name, &u.candidates, docs),
+            Type::Struct(s) => self.r#struct(name, s, docs),
+            Type::Sub(s) => self.sub(name, s, docs),
+            Type::Tuple(t) => self.tuple(name, t, docs),
+            Type::Relation(r) => {
+                let collection = self.relation(name, r);
+                self.items.push(format!("{}pub type {} = {};\n", docs, name, collection));
+            },
+            t => {
+                let rust_type = self.rust_type(t, name, true);
+                self.items.push(format!("{}pub type {} = {};\n", docs, name, rust_type));
+            },
+        }
+    }
+
+    /// The Rust type of an inline type, `hint` names the items generated for
+    /// it. Direct references to recursive types are boxed.
+    fn rust_type(&mut self, t: &Type, hint: &str, direct: bool) -> String {
+        match t {
+            Type::Any(_) => String::from("serde_json::Value"),
+            Type::Nil(_) => String::from("()"),
+            Type::Builtin(b) => match b.target.as_str() {
+                "Number" => String::from("f64"),
+                "String" => String::from("String"),
+                "Boolean" => String::from("bool"),
+                "Date" => String::from("chrono::NaiveDate"),
+                "DateTime" => String::from("chrono::DateTime<chrono::FixedOffset>"),
+                target => {
+                    self.report(format!(".{}", target));
+                    String::from("serde_json::Value")
+                },
+            },
+            Type::Ref(r) => {
+                let name = self.rust_names.get(r.name()).cloned().unwrap_or_else(|| rust_type_name(r.name()));
+                if direct && self.reaches(r.name(), &self.type_name.clone(), &mut HashSet::new()) {
+                    format!("Box<{}>", name)
+                } else {
+                    name
+                }
+            },
+            Type::Seq(s) => format!("Vec<{}>", self.rust_type(&s.elm_type, &format!("{}Item", hint), false)),
+            Type::Set(s) => {
+                let elm_type = self.rust_type(&s.elm_type, &format!("{}Item", hint), false);
+                self.collection(&s.elm_type, elm_type)
+            },
+            Type::Union(u) => match optional(&u.candidates) {
+                Some(t) => format!("Option<{}>", self.rust_type(t, hint, direct)),
+                None => self.item(hint, |g, name| g.union(name, &u.candidates, "")),
+            },
+            Type::Struct(s) if s.elements.len() > 1 => {
+                let elements: Vec<String> = s.elements
+                    .iter()
+                    .enumerate()
+                    .map(|(index, elm)| self.rust_type(elm, &format!("{}{}", hint, index + 1), direct))
+                    .collect();
+                format!("({})", elements.join(", "))
+            },
+            Type::Struct(s) => self.item(hint, |g, name| g.r#struct(name, s, "")),
+            Type::Sub(s) => self.item(hint, |g, name| g.sub(name, s, "")),
+            Type::Tuple(t) => self.item(hint, |g, name| g.tuple(name, t, "")),
+            Type::Relation(r) => self.relation(hint, r),
+        }
+    }
+
+    /// Generates an item named after `hint` and returns its name
+    fn item<F: FnOnce(&mut Self, &str)>(&mut self, hint: &str, generate: F) -> String {
+        let name = unique_name(&mut self.names, hint);
+        generate(self, &name);
+        name
+    }
+
+    fn collection(&self, elm: &Type, elm_type: String) -> String {
+        if !self.total(elm, &mut HashSet::new()) {
+            return format!("Vec<{}>", elm_type);
+        }
+        match self.options.sets {
+            SetCollection::BTreeSet => format!("std::collections::BTreeSet<{}>", elm_type),
+            SetCollection::HashSet => format!("std::collections::HashSet<{}>", elm_type),
+        }
+    }
+
+    /// Whether the values of a type are totally ordered and hashable, i.e.
+    /// not made of floats nor arbitrary JSON values
+    fn total(&self, t: &Type, visited: &mut HashSet<String>) -> bool {
+        match t {
+            Type::Any(_) => false,
+            Type::Nil(_) => true,
+            Type::Builtin(b) => matches!(b.target.as_str(), "String" | "Boolean" | "Date" | "DateTime"),
+            Type::Ref(r) => {
+                if !visited.insert(r.name().to_string()) {
+                    return true;
+                }
+                match self.target(r.name()) {
+                    Some(target) => self.total(&target, visited),
+                    None => false,
+                }
+            },
+            Type::Seq(s) => self.total(&s.elm_type, visited),
+            Type::Set(s) => self.total(&s.elm_type, visited),
+            Type::Union(u) => u.candidates.iter().all(|c| self.total(c, visited)),
+            Type::Struct(s) => s.elements.iter().all(|elm| self.total(elm, visited)),
+            Type::Sub(s) => self.total(&s.base_type, visited),
+            Type::Tuple(t) => self.total_heading(&t.heading, visited),
+            Type::Relation(r) => self.total_heading(&r.heading, visited),
+        }
+    }
+
+    fn total_heading(&self, heading: &Heading, visited: &mut HashSet<String>) -> bool {
+        heading.attributes.values().all(|a| self.total(&a.att_type, visited))
+            && heading.allow_extra.as_ref().is_none_or(|extra| self.total(extra, visited))
+    }
+
+    /// Whether the type definition `from` refers to `to`, directly or not
+    fn reaches(&self, from: &str, to: &str, visited: &mut HashSet<String>) -> bool {
+        if from == to {
+            return true;
+        }
+        if !visited.insert(from.to_string()) {
+            return false;
+        }
+        let mut refs = vec![];
+        if let Some(target) = self.target(from) {
+            references(&target, &mut refs);
+        }
+        refs.iter().any(|name| self.reaches(name, to, visited))
+    }
+
+    fn union(&mut self, name: &str, candidates: &[Type], docs: &str) {
+        let total = candidates.iter().all(|c| self.total(c, &mut HashSet::new()));
+        let mut variant_names = HashSet::new();
+        let mut variants = String::new();
+        for candidate in candidates.iter() {
+            let variant = match candidate {
+                Type::Ref(r) => rust_type_name(r.name()),
+                Type::Builtin(b) => rust_type_name(&b.target),
+                Type::Nil(_) => String::from("Nil"),
+                Type::Any(_) => String::from("Any"),
+                Type::Seq(_) => String::from("Seq"),
+                Type::Set(_) => String::from("Set"),
+                Type::Union(_) => String::from("Union"),
+                Type::Struct(_) => String::from("Struct"),
+                Type::Sub(_) => String::from("Sub"),
+                Type::Tuple(_) => String::from("Tuple"),
+                Type::Relation(_) => String::from("Relation"),
+            };
+            let variant = unique_name(&mut variant_names, &variant);
+            if let Type::Nil(_) = candidate {
+                variants.push_str(&format!("    {},\n", variant));
+            } else {
+                let rust_type = self.rust_type(candidate, &format!("{}{}", name, variant), true);
+                variants.push_str(&format!("    {}({}),\n", variant, rust_type));
+            }
+        }
+        self.items.push(format!(
+            "{}{}#[serde(untagged)]\npub enum {} {{\n{}}}\n",
+            docs,
+            derives(total),
+            name,
+            variants
+        ));
+    }
+
+    fn r#struct(&mut self, name: &str, s: &Struct, docs: &str) {
+        let total = self.total(&Type::Struct(s.clone()), &mut HashSet::new());
+        let elements: Vec<String> = s.elements
+            .iter()
+            .enumerate()
+            .map(|(index, elm)| self.rust_type(elm, &format!("{}{}", name, index + 1), true))
+            .collect();
+        // A single field would be serialized as a newtype, not as an array
+        let fields = match elements.as_slice() {
+            [element] => format!("pub ({},)", element),
+            elements => elements.iter().map(|e| format!("pub {}", e)).collect::<Vec<_>>().join(", "),
+        };
+        self.items.push(format!("{}{}pub struct {}({});\n", docs, derives(total), name, fields));
+    }
+
+    /// The fields of a struct for a heading
+    fn fields(&mut self, name: &str, heading: &Heading) -> String {
+        let mut attribute_names: Vec<&String> = heading.attributes.keys().collect();
+        attribute_names.sort();
+        let mut field_names = HashSet::new();
+        let mut fields = String::new();
+        for attribute_name in attribute_names {
+            let attribute = &heading.attributes[attribute_name];
+            let field = unique_name(&mut field_names, &field_name(attribute_name));
+            let mut rust_type = self.rust_type(&attribute.att_type, &format!("{}{}", name, pascal_case(attribute_name)), true);
+            fields.push_str(&docs(attribute.meta.as_deref(), "    "));
+            if field.trim_start_matches("r#") != attribute_name {
+                fields.push_str(&format!("    #[serde(rename = {:?})]\n", attribute_name));
+            }
+            if attribute.optional {
+                fields.push_str("    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n");
+                if !rust_type.starts_with("Option<") {
+                    rust_type = format!("Option<{}>", rust_type);
+                }
+            }
+            fields.push_str(&format!("    pub {}: {},\n", field, rust_type));
+        }
+        if let Some(extra) = &heading.allow_extra {
+            let field = unique_name(&mut field_names, "extra");
+            let rust_type = self.rust_type(extra, &format!("{}Extra", name), false);
+            fields.push_str("    #[serde(flatten)]\n");
+            fields.push_str(&format!("    pub {}: std::collections::BTreeMap<String, {}>,\n", field, rust_type));
+        }
+        fields
+    }
+
+    fn heading_struct(&mut self, name: &str, heading: &Heading, docs: &str) {
+        let total = self.total_heading(heading, &mut HashSet::new());
+        let fields = self.fields(name, heading);
+        let deny = if heading.allow_extra.is_none() { "#[serde(deny_unknown_fields)]\n" } else { "" };
+        self.items.push(format!("{}{}{}pub struct {} {{\n{}}}\n", docs, derives(total), deny, name, fields));
+    }
+
+    fn tuple(&mut self, name: &str, tuple: &Tuple, docs: &str) {
+        for fk in tuple.foreign_keys.iter() {
+            self.report(format!(
+                "ref({}({}) -> {}({}))",
+                fk.source,
+                fk.source_attributes.join(", "),
+                fk.target,
+                fk.target_attributes.join(", ")
+            ));
+        }
+        self.heading_struct(name, &tuple.heading, docs);
+    }
+
+    /// Generates the row struct of a relation and returns its collection type
+    fn relation(&mut self, name: &str, relation: &Relation) -> String {
+        for key in relation.keys.iter() {
+            self.report(format!("key({})", key.join(", ")));
+        }
+        let row = unique_name(&mut self.names, &format!("{}Row", name));
+        self.heading_struct(&row, &relation.heading, "");
+        self.collection(&Type::Relation(relation.clone()), row)
+    }
+
+    fn sub(&mut self, name: &str, sub: &Sub, docs: &str) {
+        let total = self.total(&sub.base_type, &mut HashSet::new());
+        let base = self.rust_type(&sub.base_type, &format!("{}Base", name), true);
+        let kind = self.kind(&sub.base_type, String::from("value"), 0);
+
+        let mut checks = String::new();
+        for constraint in sub.constraints.iter() {
+            let text = format!("{} | {}", constraint.param, constraint.expr.trim());
+            match kind.as_ref().and_then(|(access, kind)| condition(constraint, access, *kind)) {
+                Some(condition) => checks.push_str(&format!(
+                    "        if !({}) {{\n            return Err(format!(\"{{:?}} does not satisfy `{{}}`\", value, {:?}));\n        }}\n",
+                    condition,
+                    text
+                )),
+                None => {
+                    checks.push_str(&format!("        // Not checked: `{}`\n", text));
+                    self.report(text);
+                },
+            }
+        }
+
+        self.items.push(format!(
+            "{docs}{derives}#[serde(try_from = \"{base}\", into = \"{base}\")]\npub struct {name}({base});\n\
+            \n\
+            impl {name} {{\n    \
+                /// Checks the constraints of the type\n    \
+                pub fn new(value: {base}) -> Result<Self, String> {{\n\
+                {checks}        \
+                    Ok(Self(value))\n    \
+                }}\n\
+                \n    \
+                pub fn value(&self) -> &{base} {{\n        \
+                    &self.0\n    \
+                }}\n\
+                \n    \
+                pub fn into_inner(self) -> {base} {{\n        \
+                    self.0\n    \
+                }}\n\
+            }}\n\
+            \n\
+            impl std::convert::TryFrom<{base}> for {name} {{\n    \
+                type Error = String;\n\
+                \n    \
+                fn try_from(value: {base}) -> Result<Self, String> {{\n        \
+                    Self::new(value)\n    \
+                }}\n\
+            }}\n\
+            \n\
+            impl From<{name}> for {base} {{\n    \
+                fn from(value: {name}) -> Self {{\n        \
+                    value.0\n    \
+                }}\n\
+            }}\n",
+            docs = docs,
+            derives = derives(total),
+            base = base,
+            name = name,
+            checks = checks,
+        ));
+    }
+
+    /// What the value of a type is made of, and the expression accessing it
+    /// from `access` through the newtypes of sub types
+    fn kind(&self, t: &Type, access: String, depth: usize) -> Option<(String, Kind)> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        match t {
+            Type::Builtin(b) if b.target == "Number" => Some((access, Kind::Number)),
+            Type::Builtin(b) if b.target == "String" => Some((access, Kind::String)),
+            Type::Seq(_) | Type::Set(_) | Type::Relation(_) => Some((access, Kind::Collection)),
+            // Inline sub types and sub type definitions are newtypes
+            Type::Sub(s) => self.kind(&s.base_type, format!("{}.0", access), depth + 1),
+            Type::Ref(r) => self.kind(&self.target(r.name())?, access, depth + 1),
+            _ => None,
+        }
+    }
+}
+
+/// A constraint as a Rust condition on the value accessed by `access`
+fn condition(constraint: &Constraint, access: &str, kind: Kind) -> Option<String> {
+    let alternatives = comparisons(constraint, kind)?;
+    Some(render(&alternatives, "&&", "||", |comparison| {
+        let op = comparison.op;
+        match (&comparison.subject, &comparison.literal) {
+            (Subject::Length, Literal::Number(n)) if kind == Kind::String => {
+                format!("{}.chars().count() {} {}", access, op, n)
+            },
+            (Subject::Length, Literal::Number(n)) => format!("{}.len() {} {}", access, op, n),
+            (Subject::Remainder(d), Literal::Number(r)) => format!("{} % {:?} {} {:?}", access, d, op, r),
+            (_, Literal::Number(n)) => format!("{} {} {:?}", access, op, n),
+            (_, Literal::String(s)) => format!("{} {} {:?}", access, op, s),
+        }
+    }))
+}
+
+#[cfg(test)]
+fn generate_source(source: &str) -> Generated {
+    let schema = crate::schema::schema::test_schema(source);
+    generate(&schema, &Options::default())
+}
+
+#[test]
+fn test_generate_tuples() {
+    let generated = generate_source(r#"
+/- A person -/
+Person = {
+  /- The full name -/
+  name: .String
+  firstName :? .String
+  type: .Boolean
+  tags: {.String}
+  scores: {.Number}
+  friends :? [Person]
+}
+Open = { id: .Number, ...: .String }
+"#);
+    assert_eq!(generated.code, r#"// Generated by finitio, do not edit
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Open {
+    pub id: f64,
+    #[serde(flatten)]
+    pub extra: std::collections::BTreeMap<String, String>,
+}
+
+/// A person
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Person {
+    #[serde(rename = "firstName")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub friends: Option<Vec<Person>>,
+    /// The full name
+    pub name: String,
+    pub scores: Vec<f64>,
+    pub tags: std::collections::BTreeSet<String>,
+    pub r#type: bool,
+}
+"#);
+    assert!(generated.unchecked.is_empty());
+}
+
+#[test]
+fn test_generate_unions_structs_and_relations() {
+    let generated = generate_source(r#"
+Point = <.Number, .Number>
+Tree = { value: .String, left: Tree|Nil, right: Tree|Nil }
+Shape = Point | .String | Nil
+Users = {{ id: .String, born :? .Date }} key(id)
+"#);
+    assert_eq!(generated.code, r#"// Generated by finitio, do not edit
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Point(pub f64, pub f64);
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+pub enum Shape {
+    Point(Point),
+    String(String),
+    Nil,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Tree {
+    pub left: Option<Box<Tree>>,
+    pub right: Option<Box<Tree>>,
+    pub value: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UsersRow {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub born: Option<chrono::NaiveDate>,
+    pub id: String,
+}
+
+pub type Users = std::collections::BTreeSet<UsersRow>;
+"#);
+    assert_eq!(
+        generated.unchecked.iter().map(Unchecked::to_string).collect::<Vec<_>>(),
+        vec!["Users: `key(id)` is not checked by the generated code"]
+    );
+}
+
+#[test]
+fn test_generate_sub_types() {
+    let generated = generate_source(r#"
+Age = .Number(n | n >= 0 && n % 1 == 0)
+Adult = Age(a | a >= 18)
+Color = .String(s | s == "red" || s == "green")
+Person = { name: .String, age: Age }
+Named = Person(p | p.name != "")
+Mixed = .Number(n | n > 10 && n < 0 || n == 5)
+"#);
+    assert!(generated.code.contains(r#"#[serde(try_from = "Age", into = "Age")]
+pub struct Adult(Age);
+
+impl Adult {
+    /// Checks the constraints of the type
+    pub fn new(value: Age) -> Result<Self, String> {
+        if !(value.0 >= 18.0) {
+            return Err(format!("{:?} does not satisfy `{}`", value, "a | a >= 18"));
+        }
+        Ok(Self(value))
+    }
+"#));
+    assert!(generated.code.contains("        if !(value >= 0.0 && value % 1.0 == 0.0) {\n"));
+    assert!(generated.code.contains(r#"        if !((value == "red") || (value == "green")) {"#));
+    assert!(generated.code.contains("        // Not checked: `p | p.name != \"\"`\n"));
+    assert!(generated.code.contains("impl std::convert::TryFrom<Person> for Named {"));
+    assert!(generated.code.contains("impl From<Named> for Person {"));
+    assert_eq!(
+        generated.unchecked.iter().map(Unchecked::to_string).collect::<Vec<_>>(),
+        vec![
+            "Mixed: `n | n > 10 && n < 0 || n == 5` is not checked by the generated code",
+            r#"Named: `p | p.name != ""` is not checked by the generated code"#,
+        ]
+    );
+}
@@ -0,0 +1,663 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use crate::schema::{heading::Heading, relation::Relation, Constraint, Schema, Type};
+
+use super::{comparisons, pascal_case, render, unique_name, Generated, Kind, Literal, Subject, Unchecked};
+
+/// Maximum number of references followed to find what a type is made of
+const MAX_DEPTH: usize = 64;
+
+/// The globals used by the generated code, that types must not shadow
+const RESERVED: [&str; 6] = ["Array", "Date", "JSON", "Object", "Record", "Set"];
+
+/// The functions the guards rely on, in the order they are emitted
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Helper {
+    IsRecord,
+    Canonical,
+    IsDistinct,
+    IsUnique,
+    IsDate,
+    IsDateTime,
+}
+
+impl Helper {
+    fn dependencies(&self) -> &'static [Helper] {
+        match self {
+            Helper::IsRecord | Helper::IsDate | Helper::IsDateTime => &[],
+            Helper::Canonical => &[Helper::IsRecord],
+            Helper::IsDistinct => &[Helper::Canonical],
+            Helper::IsUnique => &[Helper::IsRecord, Helper::Canonical],
+        }
+    }
+
+    fn code(&self) -> &'static str {
+        match self {
+            Helper::IsRecord => "\
+function isRecord(value: unknown): value is Record<string, unknown> {
+  return typeof value === \"object\" && value !== null && !Array.isArray(value);
+}
+",
+            Helper::Canonical => "\
+/** The JSON text of a value, with sorted keys */
+function canonical(value: unknown): string {
+  if (Array.isArray(value)) {
+    return `[${value.map(canonical).join(\",\")}]`;
+  }
+  if (isRecord(value)) {
+    const entries = Object.keys(value).sort().map((key) => `${JSON.stringify(key)}:${canonical(value[key])}`);
+    return `{${entries.join(\",\")}}`;
+  }
+  return JSON.stringify(value);
+}
+",
+            Helper::IsDistinct => "\
+function isDistinct(values: unknown[]): boolean {
+  return new Set(values.map(canonical)).size === values.length;
+}
+",
+            Helper::IsUnique => "\
+/** Whether no two rows share the same values for the attributes of a key */
+function isUnique(rows: unknown[], key: string[]): boolean {
+  const seen = new Set<string>();
+  for (const row of rows) {
+    if (!isRecord(row) || !key.every((name) => name in row)) {
+      continue;
+    }
+    const values = canonical(key.map((name) => row[name]));
+    if (seen.has(values)) {
+      return false;
+    }
+    seen.add(values);
+  }
+  return true;
+}
+",
+            Helper::IsDate => "\
+/** ISO 8601 calendar dates, like `2023-01-31` */
+function isDate(value: unknown): value is string {
+  if (typeof value !== \"string\" || !/^\\d{4}-\\d{2}-\\d{2}$/.test(value)) {
+    return false;
+  }
+  const date = new Date(value);
+  return !isNaN(date.getTime()) && date.toISOString().slice(0, 10) === value;
+}
+",
+            Helper::IsDateTime => "\
+/** RFC 3339 date times, like `2023-01-31T10:00:00Z` */
+function isDateTime(value: unknown): value is string {
+  return typeof value === \"string\"
+    && /^\\d{4}-\\d{2}-\\d{2}[Tt ]\\d{2}:\\d{2}:\\d{2}(\\.\\d+)?([Zz]|[+-]\\d{2}:\\d{2})$/.test(value)
+    && !isNaN(Date.parse(value));
+}
+",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Options {
+    /// Generates an `isName(value)` type guard for each type definition
+    pub guards: bool,
+}
+
+/// Generates TypeScript types for all the type definitions of a schema, for
+/// the JSON data the finitio types include.
+///
+/// Tuples are interfaces with optional properties for their optional
+/// attributes, unions are union types, seqs and sets are arrays, structs are
+/// TypeScript tuples and relations are arrays of row interfaces. Sub types are
+/// aliases of their base type, their constraints are only checked by the type
+/// guards, which follow the inclusion rules of [`crate::js`]. Without guards,
+/// no constraint is reported as unchecked.
+pub fn generate(schema: &Schema, options: &Options) -> Generated {
+    let mut names = HashSet::new();
+    let ts_names: HashMap<String, String> = schema.types
+        .keys()
+        .map(|name| (name.clone(), unique_name(&mut names, &ts_type_name(name))))
+        .collect();
+    let mut generator = Generator {
+        schema,
+        options,
+        ts_names,
+        names,
+        items: vec![],
+        helpers: BTreeSet::new(),
+        unchecked: vec![],
+        type_name: String::new(),
+    };
+    for (name, typedef) in schema.types.iter() {
+        generator.type_name = name.clone();
+        let ts_name = generator.ts_names[name].clone();
+        let docs = docs(typedef.meta(), "");
+        generator.typedef(&ts_name, &typedef.target_type(), &docs);
+    }
+
+    let mut code = String::from("// Generated by finitio, do not edit\n");
+    let mut helpers = BTreeSet::new();
+    let mut pending: Vec<Helper> = generator.helpers.iter().copied().collect();
+    while let Some(helper) = pending.pop() {
+        if helpers.insert(helper) {
+            pending.extend(helper.dependencies());
+        }
+    }
+    for helper in helpers.iter() {
+        code.push('\n');
+        code.push_str(helper.code());
+    }
+    for item in generator.items.iter() {
+        code.push('\n');
+        code.push_str(item);
+    }
+    Generated { code, unchecked: generator.unchecked }
+}
+
+fn ts_type_name(name: &str) -> String {
+    let name = pascal_case(name);
+    if RESERVED.contains(&name.as_str()) {
+        format!("{}_", name)
+    } else {
+        name
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+/// A JavaScript string literal
+fn string(s: &str) -> String {
+    serde_json::Value::String(s.to_string()).to_string()
+}
+
+/// The name of a property, quoted when not an identifier
+fn property(name: &str) -> String {
+    if is_identifier(name) {
+        name.to_string()
+    } else {
+        string(name)
+    }
+}
+
+/// The expression accessing a property of an object
+fn access(object: &str, name: &str) -> String {
+    if is_identifier(name) {
+        format!("{}.{}", object, name)
+    } else {
+        format!("{}[{}]", object, string(name))
+    }
+}
+
+/// JSDoc for a `/- meta -/`
+fn docs(meta: Option<&str>, indent: &str) -> String {
+    let meta = match meta.map(str::trim) {
+        Some(meta) if !meta.is_empty() => meta,
+        _ => return String::new(),
+    };
+    let lines: Vec<&str> = meta.lines().map(str::trim).collect();
+    if lines.len() == 1 {
+        return format!("{}/** {} */\n", indent, lines[0]);
+    }
+    let mut docs = format!("{}/**\n", indent);
+    for line in lines {
+        docs.push_str(&format!("{} * {}\n", indent, line).replace(" * \n", " *\n"));
+    }
+    docs.push_str(&format!("{} */\n", indent));
+    docs
+}
+
+/// An array of a type
+fn array(elm_type: &str) -> String {
+    if elm_type.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$') {
+        format!("{}[]", elm_type)
+    } else {
+        format!("Array<{}>", elm_type)
+    }
+}
+
+/// Joins the checks of a guard, `true` when there are none
+fn all(checks: Vec<String>, separator: &str) -> String {
+    if checks.is_empty() {
+        String::from("true")
+    } else {
+        checks.join(separator)
+    }
+}
+
+struct Generator<'a> {
+    schema: &'a Schema,
+    options: &'a Options,
+    /// The TypeScript names of the type definitions
+    ts_names: HashMap<String, String>,
+    /// All the TypeScript names in use
+    names: HashSet<String>,
+    items: Vec<String>,
+    helpers: BTreeSet<Helper>,
+    unchecked: Vec<Unchecked>,
+    /// The type definition being generated
+    type_name: String,
+}
+
+impl<'a> Generator<'a> {
+    fn report(&mut self, constraint: String) {
+        self.unchecked.push(Unchecked { type_name: self.type_name.clone(), constraint });
+    }
+
+    fn target(&self, name: &str) -> Option<Type> {
+        self.schema.types.get(name).map(|typedef| typedef.target_type())
+    }
+
+    fn ts_name(&self, name: &str) -> String {
+        self.ts_names.get(name).cloned().unwrap_or_else(|| ts_type_name(name))
+    }
+
+    /// Generates the type (and guard) of a type definition
+    fn typedef(&mut self, name: &str, t: &Type, docs: &str) {
+        match t {
+            Type::Tuple(tuple) => {
+                let properties = self.properties(&tuple.heading, "  ");
+                let body = if properties.is_empty() { String::new() } else { format!("\n{}", properties) };
+                self.items.push(format!("{}export interface {} {{{}}}\n", docs, name, body));
+            },
+            Type::Relation(relation) => {
+                let row = unique_name(&mut self.names, &format!("{}Row", name));
+                let properties = self.properties(&relation.heading, "  ");
+                self.items.push(format!("export interface {} {{\n{}}}\n", row, properties));
+                self.items.push(format!("{}export type {} = {};\n", docs, name, array(&row)));
+            },
+            t => {
+                let ts_type = self.ts_type(t);
+                self.items.push(format!("{}export type {} = {};\n", docs, name, ts_type));
+            },
+        }
+
+        if self.options.guards {
+            let guard = match t {
+                // The candidates of a union are alternatives, not checks
+                Type::Union(u) => {
+                    let candidates: Vec<String> = u.candidates
+                        .iter()
+                        .map(|c| {
+                            let checks = self.checks(c, "value", 0);
+                            all(checks, " && ")
+                        })
+                        .collect();
+                    candidates.join(" ||\n    ")
+                },
+                t => {
+                    let checks = self.checks(t, "value", 0);
+                    all(checks, " &&\n    ")
+                },
+            };
+            self.items.push(format!(
+                "export function is{}(value: unknown): value is {} {{\n  return {};\n}}\n",
+                name,
+                name,
+                guard
+            ));
+        }
+    }
+
+    /// The TypeScript type of an inline type
+    fn ts_type(&mut self, t: &Type) -> String {
+        match t {
+            Type::Any(_) => String::from("unknown"),
+            Type::Nil(_) => String::from("null"),
+            Type::Builtin(b) => match b.target.as_str() {
+                "Number" => String::from("number"),
+                "String" | "Date" | "DateTime" => String::from("string"),
+                "Boolean" => String::from("boolean"),
+                target => {
+                    self.report(format!(".{}", target));
+                    String::from("unknown")
+                },
+            },
+            Type::Ref(r) => self.ts_name(r.name()),
+            Type::Seq(s) => array(&self.ts_type(&s.elm_type)),
+            Type::Set(s) => array(&self.ts_type(&s.elm_type)),
+            Type::Union(u) => {
+                let candidates: Vec<String> = u.candidates.iter().map(|c| self.ts_type(c)).collect();
+                candidates.join(" | ")
+            },
+            Type::Struct(s) => {
+                let elements: Vec<String> = s.elements.iter().map(|elm| self.ts_type(elm)).collect();
+                format!("[{}]", elements.join(", "))
+            },
+            Type::Sub(s) => self.ts_type(&s.base_type),
+            Type::Tuple(t) => self.object_type(&t.heading),
+            Type::Relation(r) => array(&self.object_type(&r.heading)),
+        }
+    }
+
+    fn object_type(&mut self, heading: &Heading) -> String {
+        let properties = self.properties(heading, "");
+        let properties: Vec<&str> = properties.lines().collect();
+        if properties.is_empty() {
+            String::from("{}")
+        } else {
+            format!("{{ {} }}", properties.join(" ").trim_end_matches(';'))
+        }
+    }
+
+    /// The properties of an interface for a heading, one per line
+    fn properties(&mut self, heading: &Heading, indent: &str) -> String {
+        let mut attribute_names: Vec<&String> = heading.attributes.keys().collect();
+        attribute_names.sort();
+        let mut properties = String::new();
+        for attribute_name in attribute_names {
+            let attribute = &heading.attributes[attribute_name];
+            let ts_type = self.ts_type(&attribute.att_type);
+            // Inline object types are on a single line, without docs
+            if !indent.is_empty() {
+                properties.push_str(&docs(attribute.meta.as_deref(), indent));
+            }
+            let optional = if attribute.optional { "?" } else { "" };
+            properties.push_str(&format!("{}{}{}: {};\n", indent, property(attribute_name), optional, ts_type));
+        }
+        if heading.allow_extra.is_some() {
+            properties.push_str(&format!("{}[key: string]: unknown;\n", indent));
+        }
+        properties
+    }
+
+    /// The checks of a guard for the value `v`, all of them must hold. The
+    /// parameters of nested functions are named after their depth.
+    fn checks(&mut self, t: &Type, v: &str, depth: usize) -> Vec<String> {
+        match t {
+            Type::Any(_) => vec![],
+            Type::Nil(_) => vec![format!("{} === null", v)],
+            Type::Builtin(b) => match b.target.as_str() {
+                "Number" => vec![format!("typeof {} === \"number\"", v)],
+                "String" => vec![format!("typeof {} === \"string\"", v)],
+                "Boolean" => vec![format!("typeof {} === \"boolean\"", v)],
+                "Date" => {
+                    self.helpers.insert(Helper::IsDate);
+                    vec![format!("isDate({})", v)]
+                },
+                "DateTime" => {
+                    self.helpers.insert(Helper::IsDateTime);
+                    vec![format!("isDateTime({})", v)]
+                },
+                // Reported by the type already
+                _ => vec![],
+            },
+            Type::Ref(r) => vec![format!("is{}({})", self.ts_name(r.name()), v)],
+            Type::Seq(s) => self.array_checks(&s.elm_type, v, depth),
+            Type::Set(s) => {
+                let mut checks = self.array_checks(&s.elm_type, v, depth);
+                self.helpers.insert(Helper::IsDistinct);
+                checks.push(format!("isDistinct({})", v));
+                checks
+            },
+            Type::Union(u) => {
+                let candidates: Vec<String> = u.candidates
+                    .iter()
+                    .map(|c| {
+                        let checks = self.checks(c, v, depth);
+                        all(checks, " && ")
+                    })
+                    .collect();
+                vec![format!("({})", candidates.join(" || "))]
+            },
+            Type::Struct(s) => {
+                let mut checks = vec![format!("Array.isArray({})", v), format!("{}.length === {}", v, s.elements.len())];
+                for (index, elm) in s.elements.iter().enumerate() {
+                    checks.extend(self.checks(elm, &format!("{}[{}]", v, index), depth));
+                }
+                checks
+            },
+            Type::Sub(s) => {
+                let mut checks = self.checks(&s.base_type, v, depth);
+                let kind = self.kind(&s.base_type, 0);
+                for constraint in s.constraints.iter() {
+                    match kind.and_then(|kind| condition(constraint, v, kind)) {
+                        Some(condition) => checks.push(condition),
+                        None => self.report(format!("{} | {}", constraint.param, constraint.expr.trim())),
+                    }
+                }
+                checks
+            },
+            Type::Tuple(t) => {
+                for fk in t.foreign_keys.iter() {
+                    self.report(format!(
+                        "ref({}({}) -> {}({}))",
+                        fk.source,
+                        fk.source_attributes.join(", "),
+                        fk.target,
+                        fk.target_attributes.join(", ")
+                    ));
+                }
+                self.heading_checks(&t.heading, v, depth)
+            },
+            Type::Relation(r) => self.relation_checks(r, v, depth),
+        }
+    }
+
+    /// Checks that `v` is an array of values of a type
+    fn array_checks(&mut self, elm: &Type, v: &str, depth: usize) -> Vec<String> {
+        let mut checks = vec![format!("Array.isArray({})", v)];
+        let param = format!("v{}", depth + 1);
+        let elm_checks = self.checks(elm, &param, depth + 1);
+        if !elm_checks.is_empty() {
+            checks.push(format!("{}.every(({}) => {})", v, param, elm_checks.join(" && ")));
+        }
+        checks
+    }
+
+    /// Checks that `v` is an object with the attributes of a heading
+    fn heading_checks(&mut self, heading: &Heading, v: &str, depth: usize) -> Vec<String> {
+        self.helpers.insert(Helper::IsRecord);
+        let mut checks = vec![format!("isRecord({})", v)];
+
+        let mut attribute_names: Vec<&String> = heading.attributes.keys().collect();
+        attribute_names.sort();
+        let known: Vec<String> = attribute_names.iter().map(|name| string(name)).collect();
+        let key = format!("k{}", depth + 1);
+        let is_known = format!("[{}].includes({})", known.join(", "), key);
+        let extra_checks = match &heading.allow_extra {
+            Some(extra) => self.checks(extra, &format!("{}[{}]", v, key), depth + 1),
+            None => vec![],
+        };
+        let extra_check = match (&heading.allow_extra, known.is_empty(), extra_checks.is_empty()) {
+            // Any extra attribute is allowed
+            (Some(_), _, true) => None,
+            (Some(_), true, false) => Some(extra_checks.join(" && ")),
+            (Some(_), false, false) => Some(format!("{} || {}", is_known, extra_checks.join(" && "))),
+            (None, true, _) => None,
+            (None, false, _) => Some(is_known),
+        };
+        match extra_check {
+            Some(check) => checks.push(format!("Object.keys({}).every(({}) => {})", v, key, check)),
+            None if heading.allow_extra.is_none() => checks.push(format!("Object.keys({}).length === 0", v)),
+            None => {},
+        }
+
+        for attribute_name in attribute_names {
+            let attribute = &heading.attributes[attribute_name];
+            let value = access(v, attribute_name);
+            let attribute_checks = self.checks(&attribute.att_type, &value, depth);
+            let present = format!("{} in {}", string(attribute_name), v);
+            match (attribute.optional, attribute_checks.is_empty()) {
+                (true, true) => {},
+                (true, false) => {
+                    let attribute_check = match attribute_checks.as_slice() {
+                        [check] => check.clone(),
+                        checks => format!("({})", checks.join(" && ")),
+                    };
+                    checks.push(format!("(!({}) || {})", present, attribute_check));
+                },
+                (false, _) => {
+                    checks.push(present);
+                    checks.extend(attribute_checks);
+                },
+            }
+        }
+        checks
+    }
+
+    fn relation_checks(&mut self, relation: &Relation, v: &str, depth: usize) -> Vec<String> {
+        let mut checks = vec![format!("Array.isArray({})", v)];
+        let param = format!("v{}", depth + 1);
+        let row_checks = self.heading_checks(&relation.heading, &param, depth + 1);
+        checks.push(format!("{}.every(({}) => {})", v, param, row_checks.join(" && ")));
+        self.helpers.insert(Helper::IsDistinct);
+        checks.push(format!("isDistinct({})", v));
+        for key in relation.keys.iter() {
+            self.helpers.insert(Helper::IsUnique);
+            let names: Vec<String> = key.iter().map(|name| string(name)).collect();
+            checks.push(format!("isUnique({}, [{}])", v, names.join(", ")));
+        }
+        checks
+    }
+
+    /// What the value of a type is made of
+    fn kind(&self, t: &Type, depth: usize) -> Option<Kind> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        match t {
+            Type::Builtin(b) if b.target == "Number" => Some(Kind::Number),
+            Type::Builtin(b) if b.target == "String" => Some(Kind::String),
+            Type::Seq(_) | Type::Set(_) | Type::Relation(_) => Some(Kind::Collection),
+            Type::Sub(s) => self.kind(&s.base_type, depth + 1),
+            Type::Ref(r) => self.kind(&self.target(r.name())?, depth + 1),
+            _ => None,
+        }
+    }
+}
+
+/// A constraint as a TypeScript condition on the value `v`
+fn condition(constraint: &Constraint, v: &str, kind: Kind) -> Option<String> {
+    let alternatives = comparisons(constraint, kind)?;
+    let condition = render(&alternatives, "&&", "||", |comparison| {
+        let op = match comparison.op {
+            "==" => "===",
+            "!=" => "!==",
+            op => op,
+        };
+        match (&comparison.subject, &comparison.literal) {
+            // Strings are compared by code points, like finitio does
+            (Subject::Length, Literal::Number(n)) if kind == Kind::String => format!("[...{}].length {} {}", v, op, n),
+            (Subject::Length, Literal::Number(n)) => format!("{}.length {} {}", v, op, n),
+            (Subject::Remainder(d), Literal::Number(r)) => format!("{} % {} {} {}", v, d, op, r),
+            (_, Literal::Number(n)) => format!("{} {} {}", v, op, n),
+            (_, Literal::String(s)) => format!("{} {} {}", v, op, string(s)),
+        }
+    });
+    if alternatives.len() > 1 {
+        Some(format!("({})", condition))
+    } else {
+        Some(condition)
+    }
+}
+
+#[cfg(test)]
+fn generate_source(source: &str, guards: bool) -> Generated {
+    let schema = crate::schema::schema::test_schema(source);
+    generate(&schema, &Options { guards })
+}
+
+#[test]
+fn test_generate_types() {
+    let generated = generate_source(r#"
+/- A person -/
+Person = {
+  /- The full name -/
+  name: .String
+  nick.name :? .String
+  born: .Date
+  tags: {.String}
+  shape: Shape
+  friends: [Person]
+  position: <.Number, .Number>
+  ...
+}
+Shape = Circle | .String | Nil
+Circle = { radius: .Number, center :? { x: .Number, y: .Number } }
+Catalog = {{ id: .String, price: .Number }}
+Count = .Number(n | n >= 0)
+"#, false);
+    assert_eq!(generated.code, r#"// Generated by finitio, do not edit
+
+export interface CatalogRow {
+  id: string;
+  price: number;
+}
+
+export type Catalog = CatalogRow[];
+
+export interface Circle {
+  center?: { x: number; y: number };
+  radius: number;
+}
+
+export type Count = number;
+
+/** A person */
+export interface Person {
+  born: string;
+  friends: Person[];
+  /** The full name */
+  name: string;
+  "nick.name"?: string;
+  position: [number, number];
+  shape: Shape;
+  tags: string[];
+  [key: string]: unknown;
+}
+
+export type Shape = Circle | string | null;
+"#);
+    assert!(generated.unchecked.is_empty());
+}
+
+#[test]
+fn test_generate_guards() {
+    let generated = generate_source(r#"
+Person = { name: Name, born :? .Date, tags: {.String}, ...: .Number }
+Name = .String(s | len(s) > 0 && len(s) <= 10)
+Shape = Person | <.Number, .Number> | Nil
+Even = .Number(n | n % 2 == 0 || n == 1)
+Catalog = {{ id: .String, price: .Number }} key(id)
+Empty = {}
+Weird = .Number(n | n > other)
+"#, true);
+    assert!(generated.code.contains("function isRecord(value: unknown): value is Record<string, unknown> {"));
+    assert!(generated.code.contains("function isDate(value: unknown): value is string {"));
+    assert!(!generated.code.contains("function isDateTime("));
+    assert!(generated.code.contains(r#"export interface Empty {}
+
+export function isEmpty(value: unknown): value is Empty {
+  return isRecord(value) &&
+    Object.keys(value).length === 0;
+}
+"#));
+    assert!(generated.code.contains(r#"export function isPerson(value: unknown): value is Person {
+  return isRecord(value) &&
+    Object.keys(value).every((k1) => ["born", "name", "tags"].includes(k1) || typeof value[k1] === "number") &&
+    (!("born" in value) || isDate(value.born)) &&
+    "name" in value &&
+    isName(value.name) &&
+    "tags" in value &&
+    Array.isArray(value.tags) &&
+    value.tags.every((v1) => typeof v1 === "string") &&
+    isDistinct(value.tags);
+}
+"#));
+    assert!(generated.code.contains(r#"export function isName(value: unknown): value is Name {
+  return typeof value === "string" &&
+    [...value].length > 0 && [...value].length <= 10;
+}
+"#));
+    assert!(generated.code.contains(r#"export function isShape(value: unknown): value is Shape {
+  return isPerson(value) ||
+    Array.isArray(value) && value.length === 2 && typeof value[0] === "number" && typeof value[1] === "number" ||
+    value === null;
+}
+"#));
+    assert!(generated.code.contains("((value % 2 === 0) || (value === 1))"));
+    assert!(generated.code.contains("    isDistinct(value) &&\n    isUnique(value, [\"id\"]);\n"));
+    assert_eq!(generated.unchecked, vec![Unchecked { type_name: String::from("Weird"), constraint: String::from("n | n > other") }]);
+}
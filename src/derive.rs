@@ -0,0 +1,591 @@
+//! Finitio types of Rust types, for schemas whose source of truth is Rust
+//! code. With the `derive` feature, `#[derive(Finitio)]` implements
+//! [`Finitio`] for structs and enums, matching their serde representation:
+//!
+//! ```ignore
+//! /// A person
+//! #[derive(Finitio, Serialize, Deserialize)]
+//! struct Person {
+//!     name: String,
+//!     #[finitio(constraint = "n | n >= 0")]
+//!     age: Option<u32>,
+//!     #[finitio(set)]
+//!     tags: Vec<String>,
+//! }
+//!
+//! let fio = finitio::derive::fio_source::<Person>();
+//! ```
+//!
+//! Field attributes are `rename = "..."` (`#[serde(rename)]` is honored as
+//! well), `skip`, `set` for a `Vec` of distinct values, `nullable` for an
+//! `Option` that is a required attribute accepting Nil instead of an optional
+//! attribute, and `constraint = "param | expr"`. Container attributes are
+//! `name = "..."`, `constraint = "param | expr"`, `extra` to allow extra
+//! attributes and `untagged` (or `#[serde(untagged)]`) for enums. Doc
+//! comments become metas. Definitions are named after the Rust types, those
+//! of distinct types with the same name get a numeric suffix.
+//!
+//! Serde attributes are honored as long as they have a finitio equivalent:
+//! `rename`, `rename_all`, `skip`, `untagged`, and `default` or
+//! `skip_serializing_if` which make attributes optional. Those leaving the
+//! representation unchanged (`deny_unknown_fields`, `alias`, `bound`...) are
+//! ignored, and the others (`tag`, `content`, `flatten`, `with`...) are
+//! compile errors.
+
+use std::any::TypeId;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use crate::codegen::unique_name;
+use crate::common::FilePosition;
+use crate::fio;
+use crate::fmt::format_schema;
+
+#[cfg(feature = "derive")]
+pub use finitio_derive::Finitio;
+
+/// A Rust type with a finitio equivalent
+pub trait Finitio {
+    /// The finitio type of the values, the type definitions it refers to are
+    /// added to `defs`
+    fn finitio_type(defs: &mut TypeDefs) -> fio::Type;
+}
+
+/// The type definitions of a schema being derived, in order of first use
+#[derive(Clone, Debug, Default)]
+pub struct TypeDefs {
+    type_defs: Vec<fio::TypeDef>,
+    names: HashSet<String>,
+    /// The names of the definitions of Rust types
+    defined: HashMap<TypeId, String>,
+}
+
+impl TypeDefs {
+    /// Defines the type of `T`, unless it already is, and returns a reference
+    /// to it. The definition is named after `name`, suffixed with a number
+    /// when another Rust type (or a hoisted type) has the same name, and it
+    /// is reserved before `target` is called, so that recursive types
+    /// terminate.
+    pub fn define<T, F>(&mut self, name: &str, meta: Option<&str>, target: F) -> fio::Type
+    where
+        T: ?Sized + 'static,
+        F: FnOnce(&mut Self) -> fio::Type,
+    {
+        if let Some(name) = self.defined.get(&TypeId::of::<T>()) {
+            return reference(name);
+        }
+        let name = unique_name(&mut self.names, name);
+        self.defined.insert(TypeId::of::<T>(), name.clone());
+        self.add(name, meta, target)
+    }
+
+    /// Moves a type to a definition of its own, named after `hint`, where the
+    /// syntax does not allow it inline
+    pub fn hoist(&mut self, t: fio::Type, hint: &str) -> fio::Type {
+        self.define_new(hint, None, |_| t)
+    }
+
+    /// Adds a definition that is not the one of a Rust type, named after
+    /// `hint`
+    pub fn define_new<F>(&mut self, hint: &str, meta: Option<&str>, target: F) -> fio::Type
+    where
+        F: FnOnce(&mut Self) -> fio::Type,
+    {
+        let name = unique_name(&mut self.names, hint);
+        self.add(name, meta, target)
+    }
+
+    fn add<F>(&mut self, name: String, meta: Option<&str>, target: F) -> fio::Type
+    where
+        F: FnOnce(&mut Self) -> fio::Type,
+    {
+        let index = self.type_defs.len();
+        self.type_defs.push(fio::TypeDef {
+            meta: meta.map(String::from),
+            name: name.clone(),
+            target: any(),
+            position: nowhere(),
+        });
+        self.type_defs[index].target = target(self);
+        reference(&name)
+    }
+
+    pub fn into_schema(self) -> fio::Schema {
+        fio::Schema { imports: vec![], type_defs: self.type_defs }
+    }
+}
+
+/// The schema of a Rust type: the definitions of the types it is made of
+pub fn schema<T: Finitio + ?Sized>() -> fio::Schema {
+    let mut defs = TypeDefs::default();
+    T::finitio_type(&mut defs);
+    defs.into_schema()
+}
+
+/// The schema of a Rust type as `.fio` source
+pub fn fio_source<T: Finitio + ?Sized>() -> String {
+    format_schema(&schema::<T>())
+}
+
+/// Derived types are not found in any source file
+fn nowhere() -> FilePosition {
+    FilePosition { line: 0, column: 0 }
+}
+
+pub fn any() -> fio::Type {
+    fio::Type::AnyType(fio::AnyType { position: nowhere() })
+}
+
+pub fn nil() -> fio::Type {
+    fio::Type::NilType(fio::NilType { position: nowhere() })
+}
+
+pub fn builtin(name: &str) -> fio::Type {
+    fio::Type::BuiltinType(fio::BuiltinType { name: name.to_string(), position: nowhere() })
+}
+
+pub fn reference(name: &str) -> fio::Type {
+    fio::Type::RefType(fio::RefType { name: name.to_string(), position: nowhere() })
+}
+
+pub fn seq(elm_type: fio::Type) -> fio::Type {
+    fio::Type::SeqType(fio::SeqType { elm_type: Box::new(elm_type), position: nowhere() })
+}
+
+pub fn set(elm_type: fio::Type) -> fio::Type {
+    fio::Type::SetType(fio::SetType { elm_type: Box::new(elm_type), position: nowhere() })
+}
+
+pub fn r#struct(elements: Vec<fio::Type>) -> fio::Type {
+    fio::Type::StructType(fio::StructType { elements, position: nowhere() })
+}
+
+pub fn attribute(name: &str, att_type: fio::Type, optional: bool, meta: Option<&str>) -> fio::Attribute {
+    fio::Attribute {
+        meta: meta.map(String::from),
+        name: name.to_string(),
+        att_type,
+        optional,
+        position: nowhere(),
+    }
+}
+
+/// A tuple type, `extra` is the type of the extra attributes it allows
+pub fn tuple(attributes: Vec<fio::Attribute>, extra: Option<fio::Type>) -> fio::Type {
+    let heading = fio::Heading {
+        attributes,
+        allow_extra: extra.map(|extra_type| Box::new(fio::AllowExtra { extra_type, position: nowhere() })),
+        position: nowhere(),
+    };
+    fio::Type::TupleType(fio::TupleType { heading, foreign_keys: vec![], position: nowhere() })
+}
+
+/// A union, flattened, with the candidates the syntax does not allow inline
+/// hoisted to definitions named after `hint`
+pub fn union(defs: &mut TypeDefs, candidates: Vec<fio::Type>, hint: &str) -> fio::Type {
+    let mut flattened = vec![];
+    for candidate in candidates {
+        match candidate {
+            fio::Type::UnionType(u) => flattened.extend(u.candidates),
+            fio::Type::TupleType(_) | fio::Type::RelationType(_) => flattened.push(defs.hoist(candidate, hint)),
+            candidate => flattened.push(candidate),
+        }
+    }
+    if flattened.len() == 1 {
+        return flattened.remove(0);
+    }
+    fio::Type::UnionType(fio::UnionType { candidates: flattened, position: nowhere() })
+}
+
+/// The strings of an enumeration
+pub fn strings(values: &[&str]) -> fio::Type {
+    let alternatives: Vec<String> = values
+        .iter()
+        .map(|v| format!("s == {}", serde_json::Value::from(*v)))
+        .collect();
+    constrain(builtin("String"), "s", &alternatives.join(" || "))
+}
+
+fn constrain(base: fio::Type, param: &str, expr: &str) -> fio::Type {
+    fio::Type::SubType(fio::SubType {
+        base: Box::new(base),
+        constraints: vec![fio::Constraint {
+            param: param.to_string(),
+            expr: format!(" {}", expr.trim()),
+            position: nowhere(),
+        }],
+        position: nowhere(),
+    })
+}
+
+/// Restricts a type by a `param | expr` constraint. The constraint is merged
+/// with the one of a sub type with the same parameter, bases that cannot be
+/// sub typed inline are hoisted to definitions named after `hint`.
+pub fn subtype(defs: &mut TypeDefs, base: fio::Type, constraint: &str, hint: &str) -> fio::Type {
+    let (param, expr) = match constraint.split_once('|') {
+        Some((param, expr)) => (param.trim(), expr.trim()),
+        None => panic!("Invalid constraint `{}`, expected `param | expr`", constraint),
+    };
+    match base {
+        fio::Type::SubType(mut sub) if sub.constraints.len() == 1 && sub.constraints[0].param == param => {
+            let merged = format!(" ({}) && ({})", sub.constraints[0].expr.trim(), expr);
+            sub.constraints[0].expr = merged;
+            fio::Type::SubType(sub)
+        },
+        base @ (fio::Type::SubType(_) | fio::Type::UnionType(_) | fio::Type::NilType(_)) => {
+            let base = defs.hoist(base, hint);
+            constrain(base, param, expr)
+        },
+        base => constrain(base, param, expr),
+    }
+}
+
+impl Finitio for String {
+    fn finitio_type(_: &mut TypeDefs) -> fio::Type {
+        builtin("String")
+    }
+}
+
+impl Finitio for str {
+    fn finitio_type(_: &mut TypeDefs) -> fio::Type {
+        builtin("String")
+    }
+}
+
+impl Finitio for bool {
+    fn finitio_type(_: &mut TypeDefs) -> fio::Type {
+        builtin("Boolean")
+    }
+}
+
+impl Finitio for f32 {
+    fn finitio_type(_: &mut TypeDefs) -> fio::Type {
+        builtin("Number")
+    }
+}
+
+impl Finitio for f64 {
+    fn finitio_type(_: &mut TypeDefs) -> fio::Type {
+        builtin("Number")
+    }
+}
+
+macro_rules! integer {
+    ($expr:expr, $($t:ty),*) => {
+        $(
+            impl Finitio for $t {
+                fn finitio_type(_: &mut TypeDefs) -> fio::Type {
+                    constrain(builtin("Number"), "n", $expr)
+                }
+            }
+        )*
+    };
+}
+
+integer!("n % 1 == 0", i8, i16, i32, i64, i128, isize);
+integer!("n % 1 == 0 && n >= 0", u8, u16, u32, u64, u128, usize);
+
+impl Finitio for () {
+    fn finitio_type(_: &mut TypeDefs) -> fio::Type {
+        nil()
+    }
+}
+
+impl Finitio for serde_json::Value {
+    fn finitio_type(_: &mut TypeDefs) -> fio::Type {
+        any()
+    }
+}
+
+impl Finitio for chrono::NaiveDate {
+    fn finitio_type(_: &mut TypeDefs) -> fio::Type {
+        builtin("Date")
+    }
+}
+
+impl<Tz: chrono::TimeZone> Finitio for chrono::DateTime<Tz> {
+    fn finitio_type(_: &mut TypeDefs) -> fio::Type {
+        builtin("DateTime")
+    }
+}
+
+impl<T: Finitio + ?Sized> Finitio for Box<T> {
+    fn finitio_type(defs: &mut TypeDefs) -> fio::Type {
+        T::finitio_type(defs)
+    }
+}
+
+impl<T: Finitio> Finitio for Option<T> {
+    fn finitio_type(defs: &mut TypeDefs) -> fio::Type {
+        let t = T::finitio_type(defs);
+        union(defs, vec![t, nil()], "Tuple")
+    }
+}
+
+impl<T: Finitio> Finitio for Vec<T> {
+    fn finitio_type(defs: &mut TypeDefs) -> fio::Type {
+        seq(T::finitio_type(defs))
+    }
+}
+
+impl<T: Finitio> Finitio for HashSet<T> {
+    fn finitio_type(defs: &mut TypeDefs) -> fio::Type {
+        set(T::finitio_type(defs))
+    }
+}
+
+impl<T: Finitio> Finitio for BTreeSet<T> {
+    fn finitio_type(defs: &mut TypeDefs) -> fio::Type {
+        set(T::finitio_type(defs))
+    }
+}
+
+/// Maps are tuples made of extra attributes only
+impl<T: Finitio> Finitio for HashMap<String, T> {
+    fn finitio_type(defs: &mut TypeDefs) -> fio::Type {
+        tuple(vec![], Some(T::finitio_type(defs)))
+    }
+}
+
+impl<T: Finitio> Finitio for BTreeMap<String, T> {
+    fn finitio_type(defs: &mut TypeDefs) -> fio::Type {
+        tuple(vec![], Some(T::finitio_type(defs)))
+    }
+}
+
+macro_rules! structs {
+    ($(($($t:ident),+)),*) => {
+        $(
+            impl<$($t: Finitio),+> Finitio for ($($t,)+) {
+                fn finitio_type(defs: &mut TypeDefs) -> fio::Type {
+                    r#struct(vec![$($t::finitio_type(defs)),+])
+                }
+            }
+        )*
+    };
+}
+
+structs!((A), (A, B), (A, B, C), (A, B, C, D), (A, B, C, D, E));
+
+#[cfg(all(test, not(feature = "derive")))]
+use finitio_derive::Finitio;
+#[cfg(test)]
+use crate::schema::schema::test_schema;
+
+#[test]
+fn test_derive_structs() {
+    /// A person
+    #[allow(dead_code)]
+    #[derive(Finitio)]
+    struct Person {
+        /// The full name
+        name: String,
+        #[finitio(constraint = "n | n >= 18")]
+        age: Option<u32>,
+        #[finitio(set)]
+        tags: Vec<String>,
+        #[serde(rename = "bestFriend")]
+        best_friend: Option<Box<Person>>,
+        #[finitio(nullable)]
+        nickname: Option<String>,
+        #[finitio(skip)]
+        cache: Vec<u8>,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Finitio)]
+    #[finitio(name = "Meters", constraint = "m | m > 0")]
+    struct Distance(f64);
+
+    #[allow(dead_code)]
+    #[derive(Finitio)]
+    struct Trip(Person, Distance);
+
+    let source = fio_source::<Trip>();
+    assert_eq!(source, "Trip = <Person, Meters>
+
+/- A person -/
+Person = {
+  /- The full name -/
+  name: .String
+  age :? .Number(n | (n % 1 == 0 && n >= 0) && (n >= 18))
+  tags: {.String}
+  bestFriend :? Person
+  nickname: .String | Nil
+}
+
+Meters = .Number(m | m > 0)
+");
+    let schema = test_schema(&source);
+    use crate::schema::TypeInclude;
+    let trip = &schema.types["Trip"];
+    assert!(trip.include(&serde_json::json!([{ "name": "Al", "tags": [], "nickname": null }, 3.5])).is_ok());
+    assert!(trip.include(&serde_json::json!([{ "name": "Al", "tags": [], "nickname": null, "age": 12 }, 3.5])).is_err());
+}
+
+#[test]
+fn test_derive_enums() {
+    #[allow(dead_code)]
+    #[derive(Finitio)]
+    enum Color {
+        Red,
+        #[serde(rename = "green")]
+        Green,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Finitio)]
+    enum Shape {
+        Circle { radius: f64 },
+        Square(f64),
+        Empty,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Finitio)]
+    #[serde(untagged)]
+    enum Value {
+        Color(Color),
+        Point(f64, f64),
+        Named { name: String },
+        Missing,
+    }
+
+    assert_eq!(fio_source::<Color>(), "Color = .String(s | s == \"Red\" || s == \"green\")\n");
+    let source = fio_source::<(Shape, Value)>();
+    assert_eq!(source, "Shape = ShapeCircle | ShapeSquare | .String(s | s == \"Empty\")
+
+ShapeCircle = { Circle: { radius: .Number } }
+
+ShapeSquare = { Square: .Number }
+
+Value = Color | <.Number, .Number> | ValueNamed | Nil
+
+Color = .String(s | s == \"Red\" || s == \"green\")
+
+ValueNamed = { name: .String }
+");
+    let schema = test_schema(&source);
+    use crate::schema::TypeInclude;
+    assert!(schema.types["Shape"].include(&serde_json::json!({ "Circle": { "radius": 1 } })).is_ok());
+    assert!(schema.types["Shape"].include(&serde_json::json!("Empty")).is_ok());
+    assert!(schema.types["Value"].include(&serde_json::json!([1, 2])).is_ok());
+    assert!(schema.types["Value"].include(&serde_json::json!(null)).is_ok());
+}
+
+#[test]
+fn test_derive_serde_attributes() {
+    #[allow(dead_code)]
+    #[derive(Finitio)]
+    #[serde(rename_all = "camelCase", deny_unknown_fields)]
+    struct Account {
+        first_name: String,
+        #[serde(default)]
+        login_count: u32,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        tags: Vec<String>,
+        #[serde(rename = "mail")]
+        email_address: String,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Finitio)]
+    #[serde(rename_all = "snake_case")]
+    enum Status {
+        NotStarted,
+        #[serde(rename_all = "UPPERCASE")]
+        InProgress { done_count: u32 },
+    }
+
+    #[allow(dead_code)]
+    #[derive(Finitio)]
+    #[serde(default)]
+    struct Settings {
+        verbose: bool,
+    }
+
+    let source = fio_source::<(Account, Status, Settings)>();
+    assert_eq!(source, "Account = {
+  firstName: .String
+  loginCount :? .Number(n | n % 1 == 0 && n >= 0)
+  tags :? [.String]
+  mail: .String
+}
+
+Status = StatusInProgress | .String(s | s == \"not_started\")
+
+StatusInProgress = {
+  in_progress: { DONE_COUNT: .Number(n | n % 1 == 0 && n >= 0) }
+}
+
+Settings = { verbose :? .Boolean }
+");
+    let schema = test_schema(&source);
+    use crate::schema::TypeInclude;
+    let account = serde_json::json!({ "firstName": "Al", "mail": "al@example.com" });
+    assert!(schema.types["Account"].include(&account).is_ok());
+    assert!(schema.types["Account"].include(&serde_json::json!({ "first_name": "Al", "mail": "al@example.com" })).is_err());
+    assert!(schema.types["Status"].include(&serde_json::json!("not_started")).is_ok());
+    assert!(schema.types["Status"].include(&serde_json::json!({ "in_progress": { "DONE_COUNT": 2 } })).is_ok());
+}
+
+#[test]
+fn test_derive_name_collisions() {
+    mod a {
+        #[allow(dead_code)]
+        #[derive(super::Finitio)]
+        pub struct Item {
+            pub name: String,
+        }
+    }
+
+    mod b {
+        #[allow(dead_code)]
+        #[derive(super::Finitio)]
+        pub struct Item {
+            pub price: f64,
+        }
+    }
+
+    #[allow(dead_code)]
+    #[derive(Finitio)]
+    enum Shape {
+        Circle { radius: f64 },
+    }
+
+    #[allow(dead_code)]
+    #[derive(Finitio)]
+    struct ShapeCircle {
+        center: (f64, f64),
+    }
+
+    #[allow(dead_code)]
+    #[derive(Finitio)]
+    struct Order {
+        first: a::Item,
+        second: b::Item,
+        again: a::Item,
+        shape: Shape,
+        circle: ShapeCircle,
+    }
+
+    let source = fio_source::<Order>();
+    assert_eq!(source, "Order = {
+  first: Item
+  second: Item2
+  again: Item
+  shape: Shape
+  circle: ShapeCircle2
+}
+
+Item = { name: .String }
+
+Item2 = { price: .Number }
+
+Shape = ShapeCircle
+
+ShapeCircle = { Circle: { radius: .Number } }
+
+ShapeCircle2 = { center: <.Number, .Number> }
+");
+    test_schema(&source);
+}
@@ -0,0 +1,292 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use snafu::{Whatever, ResultExt};
+
+use crate::js::canonical_json;
+use crate::schema::{Attributes, FinitioValue, TypeDress};
+
+/// How two versions of some data are compared
+#[derive(Clone, Debug, Default)]
+pub struct DataDiffOptions {
+    /// Attributes identifying the tuples of relations. Tuples with the same
+    /// key are compared attribute by attribute, instead of being reported as
+    /// deleted and inserted. Relations where some tuples miss key attributes,
+    /// or share key values, are compared as a whole.
+    pub key: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DataChangeKind {
+    TupleInserted(serde_json::Value),
+    TupleDeleted(serde_json::Value),
+    ElementAdded(serde_json::Value),
+    ElementRemoved(serde_json::Value),
+    ItemAdded(serde_json::Value),
+    ItemRemoved(serde_json::Value),
+    AttributeAdded(serde_json::Value),
+    AttributeRemoved(serde_json::Value),
+    Changed {
+        old: serde_json::Value,
+        new: serde_json::Value,
+    },
+}
+
+impl fmt::Display for DataChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataChangeKind::TupleInserted(v) => write!(f, "tuple inserted {}", v),
+            DataChangeKind::TupleDeleted(v) => write!(f, "tuple deleted {}", v),
+            DataChangeKind::ElementAdded(v) => write!(f, "element added {}", v),
+            DataChangeKind::ElementRemoved(v) => write!(f, "element removed {}", v),
+            DataChangeKind::ItemAdded(v) => write!(f, "item added {}", v),
+            DataChangeKind::ItemRemoved(v) => write!(f, "item removed {}", v),
+            DataChangeKind::AttributeAdded(v) => write!(f, "attribute added {}", v),
+            DataChangeKind::AttributeRemoved(v) => write!(f, "attribute removed {}", v),
+            DataChangeKind::Changed { old, new } => write!(f, "changed from {} to {}", old, new),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DataChange {
+    /// Where the change is: attribute names, seq and struct indexes, and
+    /// `[attr=value,...]` for the tuples of relations matched by key
+    pub path: Vec<String>,
+    pub kind: DataChangeKind,
+}
+
+impl fmt::Display for DataChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "/{}: {}", self.path.join("/"), self.kind)
+    }
+}
+
+/// Dresses two versions of some data with a type and compares them
+pub fn diff_data<T>(t: &T, old: &serde_json::Value, new: &serde_json::Value, options: &DataDiffOptions) -> Result<Vec<DataChange>, Whatever>
+where
+    T: TypeDress<serde_json::Value>,
+{
+    let old = t.dress(old).whatever_context("Invalid old data")?;
+    let new = t.dress(new).whatever_context("Invalid new data")?;
+    Ok(diff_values(&old, &new, options))
+}
+
+/// Compares two dressed values: sets and relations are unordered, seqs and
+/// structs are compared position by position
+pub fn diff_values(old: &FinitioValue, new: &FinitioValue, options: &DataDiffOptions) -> Vec<DataChange> {
+    let mut changes = Vec::new();
+    diff_at(&mut Vec::new(), old, new, options, &mut changes);
+    changes
+}
+
+fn push(path: &[String], kind: DataChangeKind, changes: &mut Vec<DataChange>) {
+    changes.push(DataChange { path: path.to_vec(), kind });
+}
+
+fn diff_at(path: &mut Vec<String>, old: &FinitioValue, new: &FinitioValue, options: &DataDiffOptions, changes: &mut Vec<DataChange>) {
+    match (old.untagged(), new.untagged()) {
+        (FinitioValue::Seq(olds), FinitioValue::Seq(news)) => {
+            for index in 0..olds.len().max(news.len()) {
+                path.push(index.to_string());
+                match (olds.get(index), news.get(index)) {
+                    (Some(o), Some(n)) => diff_at(path, o, n, options, changes),
+                    (Some(o), None) => push(path, DataChangeKind::ItemRemoved(o.to_json()), changes),
+                    (None, Some(n)) => push(path, DataChangeKind::ItemAdded(n.to_json()), changes),
+                    (None, None) => {},
+                }
+                path.pop();
+            }
+        },
+        (FinitioValue::Struct(olds), FinitioValue::Struct(news)) if olds.len() == news.len() => {
+            for (index, (o, n)) in olds.iter().zip(news.iter()).enumerate() {
+                path.push(index.to_string());
+                diff_at(path, o, n, options, changes);
+                path.pop();
+            }
+        },
+        (FinitioValue::Set(olds), FinitioValue::Set(news)) => {
+            let olds = by_encoding(olds.iter().cloned());
+            let news = by_encoding(news.iter().cloned());
+            for (encoding, o) in olds.iter() {
+                if !news.contains_key(encoding) {
+                    push(path, DataChangeKind::ElementRemoved(o.clone()), changes);
+                }
+            }
+            for (encoding, n) in news.iter() {
+                if !olds.contains_key(encoding) {
+                    push(path, DataChangeKind::ElementAdded(n.clone()), changes);
+                }
+            }
+        },
+        (FinitioValue::Relation(olds), FinitioValue::Relation(news)) => {
+            diff_relations(path, olds, news, options, changes)
+        },
+        (FinitioValue::Tuple(olds), FinitioValue::Tuple(news)) => {
+            diff_attributes(path, olds, news, options, changes)
+        },
+        (o, n) => {
+            if canonical_json(o) != canonical_json(n) {
+                push(path, DataChangeKind::Changed { old: o.to_json(), new: n.to_json() }, changes);
+            }
+        },
+    }
+}
+
+/// JSON values by their canonical encoding, so that sets and relations
+/// nested in different orders are equal
+fn by_encoding<I: Iterator<Item = FinitioValue>>(values: I) -> BTreeMap<String, serde_json::Value> {
+    values.map(|v| (canonical_json(&v).to_string(), v.to_json())).collect()
+}
+
+/// The key of a tuple, None if it misses some of the key attributes
+fn tuple_key(attributes: &Attributes, key: &[String]) -> Option<String> {
+    let values: Option<Vec<String>> = key
+        .iter()
+        .map(|name| attributes.get(name).map(|v| format!("{}={}", name, canonical_json(v))))
+        .collect();
+    values.map(|values| format!("[{}]", values.join(",")))
+}
+
+fn diff_relations(path: &mut Vec<String>, olds: &[Attributes], news: &[Attributes], options: &DataDiffOptions, changes: &mut Vec<DataChange>) {
+    let keyed = |tuples: &[Attributes]| -> Option<BTreeMap<String, Attributes>> {
+        let mut keyed = BTreeMap::new();
+        for t in tuples {
+            if keyed.insert(tuple_key(t, &options.key)?, t.clone()).is_some() {
+                return None;
+            }
+        }
+        Some(keyed)
+    };
+    let keys = match (options.key.is_empty(), keyed(olds), keyed(news)) {
+        (false, Some(olds), Some(news)) => Some((olds, news)),
+        _ => None,
+    };
+
+    match keys {
+        Some((olds, news)) => {
+            for (key, o) in olds.iter() {
+                match news.get(key) {
+                    Some(n) => {
+                        path.push(key.clone());
+                        diff_attributes(path, o, n, options, changes);
+                        path.pop();
+                    },
+                    None => push(path, DataChangeKind::TupleDeleted(tuple_json(o)), changes),
+                }
+            }
+            for (key, n) in news.iter() {
+                if !olds.contains_key(key) {
+                    push(path, DataChangeKind::TupleInserted(tuple_json(n)), changes);
+                }
+            }
+        },
+        None => {
+            let olds = by_encoding(olds.iter().cloned().map(FinitioValue::Tuple));
+            let news = by_encoding(news.iter().cloned().map(FinitioValue::Tuple));
+            for (encoding, o) in olds.iter() {
+                if !news.contains_key(encoding) {
+                    push(path, DataChangeKind::TupleDeleted(o.clone()), changes);
+                }
+            }
+            for (encoding, n) in news.iter() {
+                if !olds.contains_key(encoding) {
+                    push(path, DataChangeKind::TupleInserted(n.clone()), changes);
+                }
+            }
+        },
+    }
+}
+
+fn tuple_json(attributes: &Attributes) -> serde_json::Value {
+    FinitioValue::Tuple(attributes.clone()).to_json()
+}
+
+fn diff_attributes(path: &mut Vec<String>, olds: &Attributes, news: &Attributes, options: &DataDiffOptions, changes: &mut Vec<DataChange>) {
+    let names: BTreeSet<&String> = olds.keys().chain(news.keys()).collect();
+    for name in names {
+        path.push(name.clone());
+        match (olds.get(name), news.get(name)) {
+            (Some(o), Some(n)) => diff_at(path, o, n, options, changes),
+            (Some(o), None) => push(path, DataChangeKind::AttributeRemoved(o.to_json()), changes),
+            (None, Some(n)) => push(path, DataChangeKind::AttributeAdded(n.to_json()), changes),
+            (None, None) => {},
+        }
+        path.pop();
+    }
+}
+
+#[cfg(test)]
+fn diff_source(source: &str, name: &str, old: serde_json::Value, new: serde_json::Value, key: &[&str]) -> Vec<String> {
+    let schema = crate::schema::schema::test_schema(source);
+    let options = DataDiffOptions { key: key.iter().map(|k| k.to_string()).collect() };
+    diff_data(&schema.types[name], &old, &new, &options)
+        .unwrap()
+        .iter()
+        .map(DataChange::to_string)
+        .collect()
+}
+
+#[test]
+fn test_diff_relations() {
+    let source = "People = {{ id: .Number, name: .String, tags: {.String} }}";
+    let old = serde_json::json!([
+        { "id": 1, "name": "Al", "tags": ["a", "b"] },
+        { "id": 2, "name": "Bo", "tags": [] },
+    ]);
+    let new = serde_json::json!([
+        { "id": 3, "name": "Cy", "tags": [] },
+        { "id": 1, "name": "Alan", "tags": ["c", "a"] },
+    ]);
+
+    assert_eq!(diff_source(source, "People", old.clone(), new.clone(), &[]), vec![
+        r#"/: tuple deleted {"id":1,"name":"Al","tags":["a","b"]}"#,
+        r#"/: tuple deleted {"id":2,"name":"Bo","tags":[]}"#,
+        r#"/: tuple inserted {"id":1,"name":"Alan","tags":["c","a"]}"#,
+        r#"/: tuple inserted {"id":3,"name":"Cy","tags":[]}"#,
+    ]);
+    assert_eq!(diff_source(source, "People", old.clone(), new, &["id"]), vec![
+        r#"/[id=1]/name: changed from "Al" to "Alan""#,
+        r#"/[id=1]/tags: element removed "b""#,
+        r#"/[id=1]/tags: element added "c""#,
+        r#"/: tuple deleted {"id":2,"name":"Bo","tags":[]}"#,
+        r#"/: tuple inserted {"id":3,"name":"Cy","tags":[]}"#,
+    ]);
+
+    // order does not matter
+    let reversed = serde_json::json!([old[1], old[0]]);
+    assert!(diff_source(source, "People", old, reversed, &[]).is_empty());
+}
+
+#[test]
+fn test_diff_tuples_and_seqs() {
+    let source = "Person = { name: .String, nick :? .String, scores: [.Number] }";
+    let old = serde_json::json!({ "name": "Al", "nick": "A", "scores": [1, 2, 3] });
+    let new = serde_json::json!({ "name": "Al", "scores": [1, 5] });
+    assert_eq!(diff_source(source, "Person", old, new, &[]), vec![
+        r#"/nick: attribute removed "A""#,
+        "/scores/1: changed from 2 to 5",
+        "/scores/2: item removed 3",
+    ]);
+}
+
+#[test]
+fn test_diff_canonical_encoding() {
+    let source = "People = {{ id: .Number, tags: {.String}, scores: {{ n: .Number }} }}";
+    let old = serde_json::json!([{ "id": 1, "tags": ["a", "b"], "scores": [{ "n": 1 }, { "n": 2.0 }] }]);
+    let new = serde_json::json!([{ "id": 1.0, "tags": ["b", "a"], "scores": [{ "n": 2 }, { "n": 1 }] }]);
+    assert!(diff_source(source, "People", old.clone(), new.clone(), &[]).is_empty());
+    assert!(diff_source(source, "People", old, new, &["id"]).is_empty());
+}
+
+#[test]
+fn test_diff_duplicate_keys() {
+    let source = "People = {{ id: .Number, name: .String }}";
+    let old = serde_json::json!([{ "id": 1, "name": "a" }, { "id": 1, "name": "b" }]);
+    let new = serde_json::json!([{ "id": 1, "name": "a" }]);
+
+    // `id` does not identify tuples, relations are compared as a whole
+    assert_eq!(diff_source(source, "People", old, new, &["id"]), vec![
+        r#"/: tuple deleted {"id":1,"name":"b"}"#,
+    ]);
+}
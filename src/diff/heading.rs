@@ -0,0 +1,124 @@
+use crate::fio;
+
+use super::r#type::{within, Differ};
+use super::{ChangeKind, Compatibility, Side};
+
+impl<'a> Differ<'a> {
+    pub(crate) fn diff_headings(&mut self, path: &[String], oheading: &fio::Heading, nheading: &fio::Heading) {
+        let oextra = oheading.allow_extra.as_ref().map(|e| &e.extra_type);
+        let nextra = nheading.allow_extra.as_ref().map(|e| &e.extra_type);
+
+        for natt in nheading.attributes.iter() {
+            let att_path = within(path, &natt.name);
+            match oheading.attributes.iter().find(|a| a.name == natt.name) {
+                Some(oatt) => {
+                    match (oatt.optional, natt.optional) {
+                        (false, true) => {
+                            let compatibility = Compatibility::Backward;
+                            self.push(&att_path, ChangeKind::AttributeMadeOptional, compatibility, Side::New, &natt.position)
+                        },
+                        (true, false) => {
+                            let compatibility = Compatibility::Forward;
+                            self.push(&att_path, ChangeKind::AttributeMadeRequired, compatibility, Side::New, &natt.position)
+                        },
+                        _ => {},
+                    }
+                    self.diff_types(&att_path, &oatt.att_type, &natt.att_type);
+                },
+                None => {
+                    // Old values may already hold the attribute as an extra one,
+                    // and new values are fine for the old version if it is one
+                    let backward = natt.optional && match oextra {
+                        Some(oextra) => self.old_in_new(oextra, &natt.att_type),
+                        None => true,
+                    };
+                    let forward = oextra.is_some_and(|oextra| self.new_in_old(&natt.att_type, oextra));
+                    let kind = ChangeKind::AttributeAdded { optional: natt.optional };
+                    self.push(&att_path, kind, Compatibility::from_directions(backward, forward), Side::New, &natt.position);
+                },
+            }
+        }
+
+        for oatt in oheading.attributes.iter() {
+            if nheading.attributes.iter().any(|a| a.name == oatt.name) {
+                continue;
+            }
+            let backward = nextra.is_some_and(|nextra| self.old_in_new(&oatt.att_type, nextra));
+            let forward = oatt.optional && match nextra {
+                Some(nextra) => self.new_in_old(nextra, &oatt.att_type),
+                None => true,
+            };
+            let compatibility = Compatibility::from_directions(backward, forward);
+            self.push(&within(path, &oatt.name), ChangeKind::AttributeRemoved, compatibility, Side::Old, &oatt.position);
+        }
+
+        match (&oheading.allow_extra, &nheading.allow_extra) {
+            (None, Some(nextra)) => {
+                self.push(path, ChangeKind::ExtraAttributesAllowed, Compatibility::Backward, Side::New, &nextra.position)
+            },
+            (Some(oextra), None) => {
+                self.push(path, ChangeKind::ExtraAttributesDisallowed, Compatibility::Forward, Side::Old, &oextra.position)
+            },
+            (Some(oextra), Some(nextra)) => {
+                self.diff_types(&within(path, "..."), &oextra.extra_type, &nextra.extra_type)
+            },
+            (None, None) => {},
+        }
+    }
+}
+
+#[cfg(test)]
+use super::{diff_sources, summary};
+
+#[test]
+fn test_diff_headings() {
+    let old = "
+String = .String
+Number = .Number
+Person = {
+  name: String
+  nick: String
+  age :? Number
+  email :? String
+  phone: String
+}
+";
+    let new = "
+String = .String
+Number = .Number
+Person = {
+  name: String
+  nick :? String
+  age: Number
+  address :? String
+  country: String
+}
+";
+    assert_eq!(summary(&diff_sources(old, new)), vec![
+        "backward: Person/nick: attribute made optional",
+        "forward: Person/age: attribute made required",
+        "backward: Person/address: attribute added as optional",
+        "breaking: Person/country: attribute added as required",
+        "forward: Person/email: attribute removed",
+        "breaking: Person/phone: attribute removed",
+    ]);
+}
+
+#[test]
+fn test_diff_headings_extras() {
+    let old = "
+String = .String
+Person = { name: String, ...: String }
+People = {{ name: String }}
+";
+    let new = "
+String = .String
+Person = { name: String, nick: String }
+People = {{ name: String, ... }}
+";
+    assert_eq!(summary(&diff_sources(old, new)), vec![
+        "backward: People: extra attributes allowed",
+        "forward: Person/nick: attribute added as required",
+        "forward: Person: extra attributes disallowed",
+    ]);
+}
@@ -0,0 +1,273 @@
+mod data;
+mod heading;
+mod r#type;
+
+pub use data::{diff_data, diff_values, DataChange, DataChangeKind, DataDiffOptions};
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::common::FilePosition;
+use crate::{fio, schema};
+
+/// How data written against one version of a schema fares with the other one
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compatibility {
+    /// Data is accepted by both versions
+    Full,
+    /// Data valid for the old version is valid for the new one
+    Backward,
+    /// Data valid for the new version is valid for the old one
+    Forward,
+    /// Neither
+    Breaking,
+}
+
+impl Compatibility {
+    pub const ALL: [Compatibility; 4] = [
+        Compatibility::Full,
+        Compatibility::Backward,
+        Compatibility::Forward,
+        Compatibility::Breaking,
+    ];
+
+    pub(crate) fn from_directions(backward: bool, forward: bool) -> Self {
+        match (backward, forward) {
+            (true, true) => Compatibility::Full,
+            (true, false) => Compatibility::Backward,
+            (false, true) => Compatibility::Forward,
+            (false, false) => Compatibility::Breaking,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Compatibility::Full => "full",
+            Compatibility::Backward => "backward",
+            Compatibility::Forward => "forward",
+            Compatibility::Breaking => "breaking",
+        }
+    }
+
+    /// Whether a change with this compatibility meets the `required` one
+    pub fn satisfies(&self, required: Compatibility) -> bool {
+        match required {
+            Compatibility::Full => *self == Compatibility::Full,
+            Compatibility::Backward => matches!(self, Compatibility::Full | Compatibility::Backward),
+            Compatibility::Forward => matches!(self, Compatibility::Full | Compatibility::Forward),
+            Compatibility::Breaking => true,
+        }
+    }
+}
+
+impl fmt::Display for Compatibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Compatibility {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Compatibility::ALL
+            .iter()
+            .find(|c| c.name() == name)
+            .copied()
+            .ok_or_else(|| format!("Unknown compatibility: {}", name))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ChangeKind {
+    TypeAdded,
+    TypeRemoved,
+    AttributeAdded { optional: bool },
+    AttributeRemoved,
+    AttributeMadeOptional,
+    AttributeMadeRequired,
+    ExtraAttributesAllowed,
+    ExtraAttributesDisallowed,
+    KeyAdded { key: String },
+    KeyRemoved { key: String },
+    ForeignKeyAdded { foreign_key: String },
+    ForeignKeyRemoved { foreign_key: String },
+    CandidateAdded { candidate: String },
+    CandidateRemoved { candidate: String },
+    ConstraintChanged,
+    TypeWidened,
+    TypeNarrowed,
+    TypeChanged,
+}
+
+impl fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChangeKind::TypeAdded => write!(f, "type added"),
+            ChangeKind::TypeRemoved => write!(f, "type removed"),
+            ChangeKind::AttributeAdded { optional: true } => write!(f, "attribute added as optional"),
+            ChangeKind::AttributeAdded { optional: false } => write!(f, "attribute added as required"),
+            ChangeKind::AttributeRemoved => write!(f, "attribute removed"),
+            ChangeKind::AttributeMadeOptional => write!(f, "attribute made optional"),
+            ChangeKind::AttributeMadeRequired => write!(f, "attribute made required"),
+            ChangeKind::ExtraAttributesAllowed => write!(f, "extra attributes allowed"),
+            ChangeKind::ExtraAttributesDisallowed => write!(f, "extra attributes disallowed"),
+            ChangeKind::KeyAdded { key } => write!(f, "key `{}` added", key),
+            ChangeKind::KeyRemoved { key } => write!(f, "key `{}` removed", key),
+            ChangeKind::ForeignKeyAdded { foreign_key } => write!(f, "foreign key `{}` added", foreign_key),
+            ChangeKind::ForeignKeyRemoved { foreign_key } => write!(f, "foreign key `{}` removed", foreign_key),
+            ChangeKind::CandidateAdded { candidate } => write!(f, "union candidate `{}` added", candidate),
+            ChangeKind::CandidateRemoved { candidate } => write!(f, "union candidate `{}` removed", candidate),
+            ChangeKind::ConstraintChanged => write!(f, "constraint changed"),
+            ChangeKind::TypeWidened => write!(f, "type widened"),
+            ChangeKind::TypeNarrowed => write!(f, "type narrowed"),
+            ChangeKind::TypeChanged => write!(f, "type changed"),
+        }
+    }
+}
+
+/// The version of the schema a change position refers to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side {
+    Old,
+    New,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Change {
+    /// The name of the changed type definition
+    pub type_name: String,
+    /// Where the change is within the type definition: attribute names, `*`
+    /// for the elements of sequences and sets, indexes for struct components
+    pub path: Vec<String>,
+    pub kind: ChangeKind,
+    pub compatibility: Compatibility,
+    pub side: Side,
+    pub position: FilePosition,
+}
+
+impl Change {
+    pub fn location(&self) -> String {
+        let mut location = vec![self.type_name.clone()];
+        location.extend(self.path.iter().cloned());
+        location.join("/")
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {}: {}: {}",
+            self.position.line,
+            self.position.column,
+            self.compatibility,
+            self.location(),
+            self.kind
+        )
+    }
+}
+
+/// A validated schema together with the parsed definitions of its types
+pub struct Version {
+    pub(crate) typedefs: HashMap<String, fio::TypeDef>,
+    pub(crate) schema: schema::Schema,
+}
+
+impl Version {
+    /// `fios` are the parsed schemas `schema` was resolved from
+    pub fn new(fios: &HashMap<PathBuf, fio::Schema>, schema: schema::Schema) -> Self {
+        let typedefs = fios
+            .values()
+            .flat_map(|fschema| fschema.type_defs.iter())
+            .filter(|typedef| schema.types.contains_key(&typedef.name))
+            .map(|typedef| (typedef.name.clone(), typedef.clone()))
+            .collect();
+        Self { typedefs, schema }
+    }
+
+    pub(crate) fn resolve(&self, ftype: &fio::Type) -> Option<schema::Type> {
+        self.schema.resolve_type(ftype).ok()
+    }
+}
+
+/// Compares two versions of a schema, type definitions are matched by name
+pub fn diff(old: &Version, new: &Version) -> Vec<Change> {
+    let names: BTreeSet<&String> = old.typedefs.keys().chain(new.typedefs.keys()).collect();
+    let mut changes = Vec::new();
+    for name in names {
+        match (old.typedefs.get(name), new.typedefs.get(name)) {
+            (Some(otypedef), Some(ntypedef)) => {
+                let mut differ = r#type::Differ { old, new, type_name: name, changes: &mut changes };
+                differ.diff_types(&[], &otypedef.target, &ntypedef.target);
+            },
+            (Some(otypedef), None) => changes.push(Change {
+                type_name: name.clone(),
+                path: vec![],
+                kind: ChangeKind::TypeRemoved,
+                compatibility: Compatibility::Breaking,
+                side: Side::Old,
+                position: otypedef.position.clone(),
+            }),
+            (None, Some(ntypedef)) => changes.push(Change {
+                type_name: name.clone(),
+                path: vec![],
+                kind: ChangeKind::TypeAdded,
+                compatibility: Compatibility::Full,
+                side: Side::New,
+                position: ntypedef.position.clone(),
+            }),
+            (None, None) => {},
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+pub(crate) fn diff_sources(old: &str, new: &str) -> Vec<Change> {
+    let version = |source: &str| {
+        let path = PathBuf::from("test.fio");
+        let fios = HashMap::from([(path.clone(), fio::parse_schema(source).unwrap())]);
+        let mut schemas = schema::Schema::from_fios(fios.clone()).unwrap();
+        Version::new(&fios, schemas.remove(&path).unwrap())
+    };
+    diff(&version(old), &version(new))
+}
+
+#[cfg(test)]
+pub(crate) fn summary(changes: &[Change]) -> Vec<String> {
+    changes
+        .iter()
+        .map(|c| format!("{}: {}: {}", c.compatibility, c.location(), c.kind))
+        .collect()
+}
+
+#[test]
+fn test_diff_typedefs() {
+    let old = "
+String = .String
+Name = String
+Legacy = .Number
+";
+    let new = "
+String = .String
+Name = String
+Email = String
+";
+    assert_eq!(summary(&diff_sources(old, new)), vec![
+        "full: Email: type added",
+        "breaking: Legacy: type removed",
+    ]);
+}
+
+#[test]
+fn test_compatibility_satisfies() {
+    assert!(Compatibility::Full.satisfies(Compatibility::Backward));
+    assert!(Compatibility::Backward.satisfies(Compatibility::Backward));
+    assert!(!Compatibility::Forward.satisfies(Compatibility::Backward));
+    assert!(!Compatibility::Backward.satisfies(Compatibility::Full));
+    assert!(Compatibility::Breaking.satisfies(Compatibility::Breaking));
+    assert_eq!("forward".parse(), Ok(Compatibility::Forward));
+}
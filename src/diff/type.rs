@@ -0,0 +1,291 @@
+use crate::common::FilePosition;
+use crate::fio;
+use crate::fmt::FioFormat;
+use crate::schema::{is_subtype, relation::implies_key, tuple::{ForeignKey, Tuple}};
+
+use super::{Change, ChangeKind, Compatibility, Side, Version};
+
+pub(crate) struct Differ<'a> {
+    pub old: &'a Version,
+    pub new: &'a Version,
+    pub type_name: &'a str,
+    pub changes: &'a mut Vec<Change>,
+}
+
+impl<'a> Differ<'a> {
+    pub(crate) fn push(&mut self, path: &[String], kind: ChangeKind, compatibility: Compatibility, side: Side, position: &FilePosition) {
+        self.changes.push(Change {
+            type_name: self.type_name.to_string(),
+            path: path.to_vec(),
+            kind,
+            compatibility,
+            side,
+            position: position.clone(),
+        });
+    }
+
+    /// Whether every value of `otype` (in the old version) is accepted by
+    /// `ntype` (in the new version)
+    pub(crate) fn old_in_new(&self, otype: &fio::Type, ntype: &fio::Type) -> bool {
+        match (self.old.resolve(otype), self.new.resolve(ntype)) {
+            (Some(otype), Some(ntype)) => is_subtype(&otype, &ntype).is_ok(),
+            _ => false,
+        }
+    }
+
+    /// Whether every value of `ntype` (in the new version) is accepted by
+    /// `otype` (in the old version)
+    pub(crate) fn new_in_old(&self, ntype: &fio::Type, otype: &fio::Type) -> bool {
+        match (self.new.resolve(ntype), self.old.resolve(otype)) {
+            (Some(ntype), Some(otype)) => is_subtype(&ntype, &otype).is_ok(),
+            _ => false,
+        }
+    }
+
+    pub(crate) fn diff_types(&mut self, path: &[String], otype: &fio::Type, ntype: &fio::Type) {
+        use fio::Type::*;
+
+        match (otype, ntype) {
+            // A change of the referenced type is reported on its own definition
+            (RefType(oref), RefType(nref)) if oref.name == nref.name => {},
+            (SeqType(oseq), SeqType(nseq)) => {
+                self.diff_types(&within(path, "*"), &oseq.elm_type, &nseq.elm_type)
+            },
+            (SetType(oset), SetType(nset)) => {
+                self.diff_types(&within(path, "*"), &oset.elm_type, &nset.elm_type)
+            },
+            (StructType(ostruct), StructType(nstruct)) if ostruct.elements.len() == nstruct.elements.len() => {
+                let elements = ostruct.elements.iter().zip(nstruct.elements.iter());
+                for (index, (oelm, nelm)) in elements.enumerate() {
+                    self.diff_types(&within(path, &index.to_string()), oelm, nelm);
+                }
+            },
+            (TupleType(otuple), TupleType(ntuple)) => {
+                self.diff_headings(path, &otuple.heading, &ntuple.heading);
+                self.diff_foreign_keys(path, otuple, ntuple);
+            },
+            (RelationType(orel), RelationType(nrel)) => {
+                self.diff_headings(path, &orel.heading, &nrel.heading);
+                self.diff_keys(path, orel, nrel);
+            },
+            (UnionType(ounion), UnionType(nunion)) => self.diff_unions(path, ounion, nunion),
+            (SubType(osub), SubType(nsub)) => {
+                if !same_constraints(osub, nsub) {
+                    let compatibility = self.compatibility(otype, ntype);
+                    self.push(path, ChangeKind::ConstraintChanged, compatibility, Side::New, &nsub.position);
+                }
+                self.diff_types(path, &osub.base, &nsub.base);
+            },
+            (otype, ntype) => {
+                let kind = match (self.old_in_new(otype, ntype), self.new_in_old(ntype, otype)) {
+                    (true, true) => return,
+                    (true, false) => ChangeKind::TypeWidened,
+                    (false, true) => ChangeKind::TypeNarrowed,
+                    (false, false) => ChangeKind::TypeChanged,
+                };
+                let compatibility = self.compatibility(otype, ntype);
+                self.push(path, kind, compatibility, Side::New, ntype.position());
+            },
+        }
+    }
+
+    fn compatibility(&self, otype: &fio::Type, ntype: &fio::Type) -> Compatibility {
+        Compatibility::from_directions(self.old_in_new(otype, ntype), self.new_in_old(ntype, otype))
+    }
+
+    /// Keys are matched regardless of the order of their attributes. A key
+    /// narrows the relation unless the keys of the other version imply it.
+    fn diff_keys(&mut self, path: &[String], orel: &fio::RelationType, nrel: &fio::RelationType) {
+        let attributes = |rel: &fio::RelationType| -> Vec<Vec<String>> {
+            rel.keys.iter().map(|key| key.attributes.clone()).collect()
+        };
+        let (okeys, nkeys) = (attributes(orel), attributes(nrel));
+        let same = |a: &[String], b: &[String]| a.len() == b.len() && a.iter().all(|name| b.contains(name));
+
+        for nkey in nrel.keys.iter() {
+            if !okeys.iter().any(|okey| same(okey, &nkey.attributes)) {
+                let backward = implies_key(&okeys, &nkey.attributes);
+                let kind = ChangeKind::KeyAdded { key: nkey.attributes.join(", ") };
+                self.push(path, kind, Compatibility::from_directions(backward, true), Side::New, &nkey.position);
+            }
+        }
+        for okey in orel.keys.iter() {
+            if !nkeys.iter().any(|nkey| same(nkey, &okey.attributes)) {
+                let forward = implies_key(&nkeys, &okey.attributes);
+                let kind = ChangeKind::KeyRemoved { key: okey.attributes.join(", ") };
+                self.push(path, kind, Compatibility::from_directions(true, forward), Side::Old, &okey.position);
+            }
+        }
+    }
+
+    /// A foreign key added narrows the tuple type, a removed one widens it
+    fn diff_foreign_keys(&mut self, path: &[String], otuple: &fio::TupleType, ntuple: &fio::TupleType) {
+        let resolved = |tuple: &fio::TupleType| -> Vec<ForeignKey> {
+            match Tuple::from_fio(tuple) {
+                Ok(tuple) => tuple.foreign_keys,
+                Err(_) => vec![],
+            }
+        };
+        let (ofks, nfks) = (resolved(otuple), resolved(ntuple));
+
+        for nfk in nfks.iter() {
+            if !ofks.iter().any(|ofk| ofk.same(nfk)) {
+                let kind = ChangeKind::ForeignKeyAdded { foreign_key: nfk.to_string() };
+                self.push(path, kind, Compatibility::Forward, Side::New, &nfk.position);
+            }
+        }
+        for ofk in ofks.iter() {
+            if !nfks.iter().any(|nfk| nfk.same(ofk)) {
+                let kind = ChangeKind::ForeignKeyRemoved { foreign_key: ofk.to_string() };
+                self.push(path, kind, Compatibility::Backward, Side::Old, &ofk.position);
+            }
+        }
+    }
+
+    fn diff_unions(&mut self, path: &[String], ounion: &fio::UnionType, nunion: &fio::UnionType) {
+        let otype = fio::Type::UnionType(ounion.clone());
+        let ntype = fio::Type::UnionType(nunion.clone());
+
+        // Candidates are matched by equivalence, a modified candidate shows
+        // up as removed and added
+        let equivalent = |ocand: &fio::Type, ncand: &fio::Type| {
+            self.old_in_new(ocand, ncand) && self.new_in_old(ncand, ocand)
+        };
+        let added: Vec<&fio::Type> = nunion.candidates
+            .iter()
+            .filter(|ncand| !ounion.candidates.iter().any(|ocand| equivalent(ocand, ncand)))
+            .collect();
+        let removed: Vec<&fio::Type> = ounion.candidates
+            .iter()
+            .filter(|ocand| !nunion.candidates.iter().any(|ncand| equivalent(ocand, ncand)))
+            .collect();
+
+        for ncand in added {
+            // New values are only a problem if the old union rejects them
+            let forward = self.new_in_old(ncand, &otype);
+            let kind = ChangeKind::CandidateAdded { candidate: ncand.format(0) };
+            self.push(path, kind, Compatibility::from_directions(true, forward), Side::New, ncand.position());
+        }
+        for ocand in removed {
+            let backward = self.old_in_new(ocand, &ntype);
+            let kind = ChangeKind::CandidateRemoved { candidate: ocand.format(0) };
+            self.push(path, kind, Compatibility::from_directions(backward, true), Side::Old, ocand.position());
+        }
+    }
+}
+
+pub(crate) fn within(path: &[String], segment: &str) -> Vec<String> {
+    let mut path = path.to_vec();
+    path.push(segment.to_string());
+    path
+}
+
+fn same_constraints(osub: &fio::SubType, nsub: &fio::SubType) -> bool {
+    osub.constraints.len() == nsub.constraints.len()
+        && osub.constraints.iter().zip(nsub.constraints.iter()).all(|(o, n)| {
+            o.param == n.param && o.expr.trim() == n.expr.trim()
+        })
+}
+
+#[cfg(test)]
+use super::{diff_sources, summary};
+
+#[test]
+fn test_diff_types() {
+    let old = "
+Number = .Number
+String = .String
+PosNumber = Number(n | n > 0)
+Age = Number
+Score = PosNumber
+Limit = Number(n | n < 10)
+Floor = Number(n | n > 0)
+Tags = [String]
+";
+    let new = "
+Number = .Number
+String = .String
+PosNumber = Number(n | n > 0)
+Age = PosNumber
+Score = Number
+Limit = Number(n | n < 100)
+Floor = Number(n | n > 5)
+Tags = [Number]
+";
+    assert_eq!(summary(&diff_sources(old, new)), vec![
+        "forward: Age: type narrowed",
+        "forward: Floor: constraint changed",
+        "backward: Limit: constraint changed",
+        "backward: Score: type widened",
+        "breaking: Tags/*: type changed",
+    ]);
+}
+
+#[test]
+fn test_diff_unions() {
+    let old = "
+Number = .Number
+String = .String
+Id = Number | String
+Scalar = Number | String
+";
+    let new = "
+Number = .Number
+String = .String
+Id = Number | String | Nil
+Scalar = Number | Nil
+";
+    assert_eq!(summary(&diff_sources(old, new)), vec![
+        "backward: Id: union candidate `Nil` added",
+        "backward: Scalar: union candidate `Nil` added",
+        "forward: Scalar: union candidate `String` removed",
+    ]);
+}
+
+#[test]
+fn test_diff_keys() {
+    let old = "
+Number = .Number
+String = .String
+People = {{ id: Number, name: String }}
+Users = {{ id: Number, name: String }} key(id, name)
+Items = {{ id: Number, name: String }} key(id)
+Codes = {{ id: Number, name: String }} key(id, name)
+";
+    let new = "
+Number = .Number
+String = .String
+People = {{ id: Number, name: String }} key(id)
+Users = {{ id: Number, name: String }} key(id)
+Items = {{ id: Number, name: String }}
+Codes = {{ id: Number, name: String }} key(name, id)
+";
+    assert_eq!(summary(&diff_sources(old, new)), vec![
+        "backward: Items: key `id` removed",
+        "forward: People: key `id` added",
+        "forward: Users: key `id` added",
+        "full: Users: key `id, name` removed",
+    ]);
+}
+
+#[test]
+fn test_diff_foreign_keys() {
+    let old = "
+Number = .Number
+Ids = {{ id: Number, other: Number }}
+Added = { a: Ids, b: Ids }
+Removed = { a: Ids, b: Ids } ref(a(id) -> b(id))
+Same = { a: Ids, b: Ids } ref(a(id, other) -> b(id, other))
+";
+    let new = "
+Number = .Number
+Ids = {{ id: Number, other: Number }}
+Added = { a: Ids, b: Ids } ref(a(id) -> b(id))
+Removed = { a: Ids, b: Ids }
+Same = { a: Ids, b: Ids } ref(a(other, id) -> b(other, id))
+";
+    assert_eq!(summary(&diff_sources(old, new)), vec![
+        "forward: Added: foreign key `a(id) -> b(id)` added",
+        "backward: Removed: foreign key `a(id) -> b(id)` removed",
+    ]);
+}
@@ -1,5 +1,6 @@
 use std::fmt;
 
+use crate::common::FilePosition;
 use crate::fio::common::Span;
 
 #[derive(Debug, PartialEq)]
@@ -8,6 +9,17 @@ pub enum ParseError<'a> {
     TrailingGarbage(Span<'a>),
 }
 
+impl ParseError<'_> {
+    /// Where the parsing failed
+    pub fn position(&self) -> FilePosition {
+        match self {
+            ParseError::Nom(nom::Err::Error(e)) | ParseError::Nom(nom::Err::Failure(e)) => e.input.into(),
+            ParseError::Nom(nom::Err::Incomplete(_)) => FilePosition { line: 1, column: 1 },
+            ParseError::TrailingGarbage(span) => (*span).into(),
+        }
+    }
+}
+
 impl<'a> fmt::Display for ParseError<'a> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -42,6 +42,18 @@ pub struct Attribute {
     pub position: FilePosition,
 }
 
+impl Heading {
+    /// Visits the types of all attributes (and extra attributes) of the heading
+    pub fn walk<F: FnMut(&Type)>(&self, visit: &mut F) {
+        for attribute in self.attributes.iter() {
+            attribute.att_type.walk(visit);
+        }
+        if let Some(extra) = &self.allow_extra {
+            extra.extra_type.walk(visit);
+        }
+    }
+}
+
 fn parse_separator(input: Span) -> IResult<Span, char> {
     alt((
         preceded(ws_no_nl, preceded(opt(char(',')), newline)),
@@ -24,11 +24,14 @@ pub use nil::NilType;
 pub use r#ref::RefType;
 pub use r#struct::StructType;
 pub use r#type::Type;
-pub use relation::RelationType;
-pub use schema::{parse_schema, parse_file, Schema};
+pub use relation::{RelationType, RelationKey};
+pub use schema::{parse_schema, parse_schema_parts, parse_file, Schema, SchemaPart};
 pub use seq::SeqType;
 pub use set::SetType;
-pub use sub::SubType;
-pub use tuple::TupleType;
-pub use heading::Heading;
+pub use sub::{SubType, Constraint};
+pub use tuple::{TupleType, ForeignKey};
+pub use heading::{Heading, Attribute, AllowExtra};
+pub use import::Import;
+pub use typedef::TypeDef;
+pub use errors::ParseError;
 pub use union::UnionType;
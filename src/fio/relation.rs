@@ -1,22 +1,56 @@
 use super::heading::{parse_heading, Heading};
 use crate::common::FilePosition;
-use crate::fio::common::Span;
-use nom::{bytes::complete::tag, combinator::map, sequence::delimited, IResult};
+use crate::fio::common::{parse_identifier, ws, ws_no_nl, Span};
+use nom::{
+    bytes::complete::tag,
+    combinator::map,
+    multi::{many0, separated_list1},
+    sequence::{delimited, pair, preceded, terminated},
+    IResult,
+};
 use serde::{Serialize, Deserialize};
 
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
 pub struct RelationType {
     pub heading: Heading,
+    /// Candidate keys, e.g. `{{ id: Number, email: String }} key(id) key(email)`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keys: Vec<RelationKey>,
     pub position: FilePosition,
 }
 
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+pub struct RelationKey {
+    pub attributes: Vec<String>,
+    pub position: FilePosition,
+}
+
+pub fn parse_relation_key(input: Span) -> IResult<Span, RelationKey> {
+    let attributes = separated_list1(delimited(ws, tag(","), ws), parse_identifier);
+    map(
+        preceded(
+            terminated(tag("key"), ws),
+            delimited(terminated(tag("("), ws), attributes, preceded(ws, tag(")"))),
+        ),
+        |attributes| RelationKey {
+            attributes,
+            position: input.into(),
+        },
+    )(input)
+}
+
 pub fn parse_relation(input: Span) -> IResult<Span, RelationType> {
-    map(delimited(tag("{"), parse_heading, tag("}")), |heading| {
-        RelationType {
+    map(
+        pair(
+            delimited(tag("{"), parse_heading, tag("}")),
+            many0(preceded(ws_no_nl, parse_relation_key)),
+        ),
+        |(heading, keys)| RelationType {
             heading,
+            keys,
             position: input.into(),
-        }
-    })(input)
+        },
+    )(input)
 }
 
 #[cfg(test)]
@@ -64,7 +98,24 @@ fn test_parse_relation_simple() {
                 ],
                 position: FilePosition { line: 1, column: 2 },
             },
+            keys: vec![],
             position: FilePosition { line: 1, column: 1 },
         },
     );
 }
+
+#[test]
+fn test_parse_relation_keys() {
+    let (rest, relation) = parse_relation(Span::new("{{ id: Number, first: String, last: String }} key(id) key( first , last )\nNext = .")).unwrap();
+    assert_eq!(rest.fragment(), &"\nNext = .");
+    assert_eq!(relation.keys, vec![
+        RelationKey {
+            attributes: vec!["id".to_string()],
+            position: FilePosition { line: 1, column: 47 },
+        },
+        RelationKey {
+            attributes: vec!["first".to_string(), "last".to_string()],
+            position: FilePosition { line: 1, column: 55 },
+        },
+    ]);
+}
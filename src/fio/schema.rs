@@ -1,9 +1,9 @@
-use std::{path::{PathBuf}, collections::{HashSet, HashMap}};
+use std::{path::{PathBuf}, collections::HashMap};
 use std::fs;
 
 use nom::{
     branch::alt,
-    combinator::map,
+    combinator::{map, recognize},
     multi::separated_list0,
     sequence::{preceded, terminated},
     IResult,
@@ -25,9 +25,11 @@ pub struct Schema {
     pub type_defs: Vec<TypeDef>,
 }
 
+#[derive(Debug, PartialEq, Clone)]
 pub enum SchemaPart {
     Import(Import),
     TypeDef(TypeDef),
+    /// A top-level comment, kept verbatim (delimiters included)
     Comment(String)
 }
 
@@ -72,23 +74,26 @@ pub fn parse_file(filename: &PathBuf) -> Result<HashMap<PathBuf, Schema>, Valida
     Ok(fios)
 }
 
-pub fn parse_schema(input: &str) -> Result<Schema, ParseError> {
-    let span = Span::new(input);
-    let result = parse_schema_content(span);
-    match result {
-        Ok((span, parts)) if span.fragment() == &"" => {
-            let mut imports: Vec<Import> = Vec::new();
-            let mut type_defs: Vec<TypeDef> = Vec::new();
-
-            for part in parts {
-                match part {
-                    SchemaPart::Import(part) => imports.push(part),
-                    SchemaPart::TypeDef(part) => type_defs.push(part),
-                    SchemaPart::Comment(_) => {},
-                }
-            }
-            Ok(Schema { imports, type_defs })
+pub fn parse_schema(input: &str) -> Result<Schema, ParseError<'_>> {
+    let parts = parse_schema_parts(input)?;
+    let mut imports: Vec<Import> = Vec::new();
+    let mut type_defs: Vec<TypeDef> = Vec::new();
+
+    for part in parts {
+        match part {
+            SchemaPart::Import(part) => imports.push(part),
+            SchemaPart::TypeDef(part) => type_defs.push(part),
+            SchemaPart::Comment(_) => {},
         }
+    }
+    Ok(Schema { imports, type_defs })
+}
+
+/// Parses a schema, keeping the parts (comments included) in source order
+pub fn parse_schema_parts(input: &str) -> Result<Vec<SchemaPart>, ParseError<'_>> {
+    let span = Span::new(input);
+    match parse_schema_content(span) {
+        Ok((span, parts)) if span.fragment() == &"" => Ok(parts),
         Ok((garbage, _)) => Err(ParseError::TrailingGarbage(garbage)),
         Err(error) => Err(ParseError::Nom(error)),
     }
@@ -98,7 +103,7 @@ fn parse_schema_part(input: Span) -> IResult<Span, SchemaPart> {
     alt((
         map(parse_import, SchemaPart::Import),
         map(parse_typedef, SchemaPart::TypeDef),
-        map(parse_comment, SchemaPart::Comment)
+        map(recognize(parse_comment), |c: Span| SchemaPart::Comment(c.to_string()))
     ))(input)
 }
 
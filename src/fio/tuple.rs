@@ -1,21 +1,75 @@
 
 use super::heading::{parse_heading, Heading};
 use crate::common::FilePosition;
-use crate::fio::common::Span;
-use nom::{combinator::map, IResult};
+use crate::fio::common::{parse_identifier, ws, ws_no_nl, Span};
+use nom::{
+    bytes::complete::tag,
+    combinator::map,
+    multi::{many0, separated_list1},
+    sequence::{delimited, pair, preceded, separated_pair, terminated},
+    IResult,
+};
 use serde::{Serialize, Deserialize};
 
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
 pub struct TupleType {
     pub heading: Heading,
+    /// Foreign keys between relation-valued attributes, e.g.
+    /// `{ orders: Orders, customers: Customers } ref(orders(customer_id) -> customers(id))`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub foreign_keys: Vec<ForeignKey>,
     pub position: FilePosition,
 }
 
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+pub struct ForeignKey {
+    /// The referencing relation-valued attribute
+    pub source: String,
+    pub source_attributes: Vec<String>,
+    /// The referenced relation-valued attribute
+    pub target: String,
+    pub target_attributes: Vec<String>,
+    pub position: FilePosition,
+}
+
+/// Parses `relation(a, b)`
+fn parse_key_attributes(input: Span) -> IResult<Span, (String, Vec<String>)> {
+    let attributes = separated_list1(delimited(ws, tag(","), ws), parse_identifier);
+    pair(
+        terminated(parse_identifier, ws),
+        delimited(terminated(tag("("), ws), attributes, preceded(ws, tag(")"))),
+    )(input)
+}
+
+pub fn parse_foreign_key(input: Span) -> IResult<Span, ForeignKey> {
+    map(
+        preceded(
+            terminated(tag("ref"), ws),
+            delimited(
+                terminated(tag("("), ws),
+                separated_pair(parse_key_attributes, delimited(ws, tag("->"), ws), parse_key_attributes),
+                preceded(ws, tag(")")),
+            ),
+        ),
+        |((source, source_attributes), (target, target_attributes))| ForeignKey {
+            source,
+            source_attributes,
+            target,
+            target_attributes,
+            position: input.into(),
+        },
+    )(input)
+}
+
 pub fn parse_tuple(input: Span) -> IResult<Span, TupleType> {
-    map(parse_heading, |heading| TupleType {
-        heading,
-        position: input.into(),
-    })(input)
+    map(
+        pair(parse_heading, many0(preceded(ws_no_nl, parse_foreign_key))),
+        |(heading, foreign_keys)| TupleType {
+            heading,
+            foreign_keys,
+            position: input.into(),
+        },
+    )(input)
 }
 
 #[cfg(test)]
@@ -60,7 +114,31 @@ fn test_parse_tuple_simple() {
                 ],
                 position: FilePosition { line: 1, column: 1 },
             },
+            foreign_keys: vec![],
             position: FilePosition { line: 1, column: 1 },
         },
     );
 }
+
+#[test]
+fn test_parse_tuple_foreign_keys() {
+    let source = "{ orders: Orders, customers: Customers } ref(orders(customer_id) -> customers(id)) ref( orders(a, b)->others(c,d) )\nNext = .";
+    let (rest, tuple) = parse_tuple(Span::new(source)).unwrap();
+    assert_eq!(rest.fragment(), &"\nNext = .");
+    assert_eq!(tuple.foreign_keys, vec![
+        ForeignKey {
+            source: "orders".to_string(),
+            source_attributes: vec!["customer_id".to_string()],
+            target: "customers".to_string(),
+            target_attributes: vec!["id".to_string()],
+            position: FilePosition { line: 1, column: 42 },
+        },
+        ForeignKey {
+            source: "orders".to_string(),
+            source_attributes: vec!["a".to_string(), "b".to_string()],
+            target: "others".to_string(),
+            target_attributes: vec!["c".to_string(), "d".to_string()],
+            position: FilePosition { line: 1, column: 84 },
+        },
+    ]);
+}
@@ -1,5 +1,6 @@
 #[cfg(test)]
-use crate::{common::FilePosition, fio::common::assert_parse};
+use crate::fio::common::assert_parse;
+use crate::common::FilePosition;
 
 use crate::fio::seq::{parse_seq, SeqType};
 use crate::fio::set::{parse_set, SetType};
@@ -40,6 +41,39 @@ pub enum Type {
     RelationType(RelationType),
 }
 
+impl Type {
+    pub fn position(&self) -> &FilePosition {
+        match self {
+            Type::AnyType(t) => &t.position,
+            Type::NilType(t) => &t.position,
+            Type::BuiltinType(t) => &t.position,
+            Type::RefType(t) => &t.position,
+            Type::SeqType(t) => &t.position,
+            Type::SetType(t) => &t.position,
+            Type::UnionType(t) => &t.position,
+            Type::StructType(t) => &t.position,
+            Type::SubType(t) => &t.position,
+            Type::TupleType(t) => &t.position,
+            Type::RelationType(t) => &t.position,
+        }
+    }
+
+    /// Visits this type and all the types nested in it, depth first
+    pub fn walk<F: FnMut(&Type)>(&self, visit: &mut F) {
+        visit(self);
+        match self {
+            Type::AnyType(_) | Type::NilType(_) | Type::BuiltinType(_) | Type::RefType(_) => {},
+            Type::SeqType(t) => t.elm_type.walk(visit),
+            Type::SetType(t) => t.elm_type.walk(visit),
+            Type::UnionType(t) => t.candidates.iter().for_each(|c| c.walk(visit)),
+            Type::StructType(t) => t.elements.iter().for_each(|e| e.walk(visit)),
+            Type::SubType(t) => t.base.walk(visit),
+            Type::TupleType(t) => t.heading.walk(visit),
+            Type::RelationType(t) => t.heading.walk(visit),
+        }
+    }
+}
+
 pub fn parse_type(input: Span) -> IResult<Span, Type> {
     alt((
         map(preceded(ws, parse_union), Type::UnionType),
@@ -0,0 +1,108 @@
+use crate::fio::{AllowExtra, Attribute, Heading, Type};
+
+use super::{indentation, FioFormat, MAX_INLINE_WIDTH};
+
+impl FioFormat for Attribute {
+    fn format(&self, indent: usize) -> String {
+        let separator = if self.optional { " :?" } else { ":" };
+        let meta = match &self.meta {
+            Some(meta) => format!("/-{}-/\n{}", meta, indentation(indent)),
+            None => String::new(),
+        };
+        format!("{}{}{} {}", meta, self.name, separator, self.att_type.format(indent))
+    }
+}
+
+impl FioFormat for AllowExtra {
+    fn format(&self, indent: usize) -> String {
+        match &self.extra_type {
+            Type::AnyType(_) => String::from("..."),
+            extra_type => format!("...: {}", extra_type.format(indent)),
+        }
+    }
+}
+
+impl FioFormat for Heading {
+    fn format(&self, indent: usize) -> String {
+        if self.attributes.is_empty() && self.allow_extra.is_none() {
+            return String::from("{}");
+        }
+
+        let inline = self.format_inline(indent);
+        let has_meta = self.attributes.iter().any(|a| a.meta.is_some());
+        if !has_meta && !inline.contains('\n') && inline.len() <= MAX_INLINE_WIDTH {
+            return inline;
+        }
+
+        let inner = indentation(indent + 1);
+        let mut lines: Vec<String> = self.attributes
+            .iter()
+            .map(|a| format!("{}{}", inner, a.format(indent + 1)))
+            .collect();
+        if let Some(extra) = &self.allow_extra {
+            lines.push(format!("{}{}", inner, extra.format(indent + 1)));
+        }
+        format!("{{\n{}\n{}}}", lines.join("\n"), indentation(indent))
+    }
+}
+
+impl Heading {
+    fn format_inline(&self, indent: usize) -> String {
+        let mut parts: Vec<String> = self.attributes
+            .iter()
+            .map(|a| a.format(indent))
+            .collect();
+        if let Some(extra) = &self.allow_extra {
+            parts.push(extra.format(indent));
+        }
+        format!("{{ {} }}", parts.join(", "))
+    }
+}
+
+#[cfg(test)]
+use crate::fio::parse_schema;
+
+#[test]
+fn test_format_heading_inline() {
+    let fio = parse_schema("T = {name:String,age :? Number}\nR = {{ name: String, ... }}").unwrap();
+    assert_eq!(fio.type_defs[0].target.format(0), "{ name: String, age :? Number }");
+    assert_eq!(fio.type_defs[1].target.format(0), "{{ name: String, ... }}");
+}
+
+#[test]
+fn test_format_relation_keys() {
+    let fio = parse_schema("R = {{ id: Number, first: String, last: String }} key( id ) key(first,last)").unwrap();
+    assert_eq!(fio.type_defs[0].target.format(0), "{{ id: Number, first: String, last: String }} key(id) key(first, last)");
+}
+
+#[test]
+fn test_format_tuple_foreign_keys() {
+    let fio = parse_schema("T = { a: A, b: B } ref( a(x,y)->b(z, w) )").unwrap();
+    assert_eq!(fio.type_defs[0].target.format(0), "{ a: A, b: B } ref(a(x, y) -> b(z, w))");
+}
+
+#[test]
+fn test_format_heading_empty() {
+    let fio = parse_schema("T = { }\nU = { ...: String }").unwrap();
+    assert_eq!(fio.type_defs[0].target.format(0), "{}");
+    assert_eq!(fio.type_defs[1].target.format(0), "{ ...: String }");
+}
+
+#[test]
+fn test_format_heading_multiline() {
+    let fio = parse_schema("T = {
+        /- The name -/
+        name: String
+        address: { street: String, number: Integer, zip: String, city: String }
+    }").unwrap();
+    assert_eq!(fio.type_defs[0].target.format(0), "{
+  /- The name -/
+  name: String
+  address: {
+    street: String
+    number: Integer
+    zip: String
+    city: String
+  }
+}");
+}
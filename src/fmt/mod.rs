@@ -0,0 +1,25 @@
+mod heading;
+mod schema;
+mod r#type;
+
+pub use schema::{format_schema, format_source};
+
+/// Number of spaces used for each level of indentation
+pub const INDENT: usize = 2;
+
+/// Headings longer than this (once printed on a single line) are split
+/// over several lines, one attribute per line
+pub const MAX_INLINE_WIDTH: usize = 60;
+
+/// Prints a node of the `fio` AST back to finitio source.
+///
+/// `indent` is the indentation level of the line the node starts on, and
+/// is used by nodes spanning several lines (headings) to indent their
+/// content and closing delimiter.
+pub trait FioFormat {
+    fn format(&self, indent: usize) -> String;
+}
+
+pub(crate) fn indentation(indent: usize) -> String {
+    " ".repeat(indent * INDENT)
+}
@@ -0,0 +1,172 @@
+use crate::fio::{parse_schema_parts, Import, ParseError, Schema, SchemaPart, TypeDef};
+
+use super::FioFormat;
+
+impl FioFormat for Import {
+    fn format(&self, _indent: usize) -> String {
+        format!("@import {}", self.filename)
+    }
+}
+
+impl FioFormat for TypeDef {
+    fn format(&self, indent: usize) -> String {
+        let meta = match &self.meta {
+            Some(meta) => format!("/-{}-/\n", meta),
+            None => String::new(),
+        };
+        format!("{}{} = {}", meta, self.name, self.target.format(indent))
+    }
+}
+
+impl FioFormat for SchemaPart {
+    fn format(&self, indent: usize) -> String {
+        match self {
+            SchemaPart::Import(i) => i.format(indent),
+            SchemaPart::TypeDef(t) => t.format(indent),
+            SchemaPart::Comment(c) => c.trim_end().to_string(),
+        }
+    }
+}
+
+/// Prints a schema in canonical form: imports first, then type definitions
+/// separated by blank lines.
+pub fn format_schema(schema: &Schema) -> String {
+    let parts: Vec<SchemaPart> = schema.imports
+        .iter()
+        .cloned()
+        .map(SchemaPart::Import)
+        .chain(schema.type_defs.iter().cloned().map(SchemaPart::TypeDef))
+        .collect();
+    format_parts(&parts)
+}
+
+/// Formats finitio source, keeping top-level comments and the original order
+/// of its parts.
+pub fn format_source(source: &str) -> Result<String, ParseError<'_>> {
+    let parts = parse_schema_parts(source)?;
+    Ok(format_parts(&parts))
+}
+
+fn format_parts(parts: &[SchemaPart]) -> String {
+    let mut output = String::new();
+    for (pos, part) in parts.iter().enumerate() {
+        if pos > 0 {
+            // Consecutive imports are grouped, everything else is spaced out
+            match (&parts[pos - 1], part) {
+                (SchemaPart::Import(_), SchemaPart::Import(_)) => output.push('\n'),
+                _ => output.push_str("\n\n"),
+            }
+        }
+        output.push_str(&part.format(0));
+    }
+    if !output.is_empty() {
+        output.push('\n');
+    }
+    output
+}
+
+#[cfg(test)]
+use crate::fio::parse_schema;
+
+#[cfg(test)]
+fn without_positions(schema: &Schema) -> serde_json::Value {
+    fn strip(value: &mut serde_json::Value) {
+        match value {
+            serde_json::Value::Object(obj) => {
+                obj.remove("position");
+                obj.values_mut().for_each(strip);
+            },
+            serde_json::Value::Array(arr) => arr.iter_mut().for_each(strip),
+            _ => {},
+        }
+    }
+    let mut value = serde_json::to_value(schema).unwrap();
+    strip(&mut value);
+    value
+}
+
+#[cfg(test)]
+const MESSY: &str = "
+@import scalars.fio
+// A comment about Seq
+Seq=[ Integer ]
+/- A positive integer -/
+PosInteger =Integer(i | i >= 0)
+/* Multiline
+   comment */
+MaybeNumber = Number|Nil
+Person = {
+    /- Attributes can have meta -/
+    name          :   String,
+    emails        :?  {String}
+    password      :?  String(s | len(s) > 10)
+}(p | p.password != p.name)
+People = {{ name: String, age :? Integer, ...: String }}
+Pair = < Number, {{ ... }} >
+";
+
+#[test]
+fn test_format_source() {
+    assert_eq!(format_source(MESSY).unwrap(), "@import scalars.fio
+
+// A comment about Seq
+
+Seq = [Integer]
+
+/- A positive integer -/
+PosInteger = Integer(i | i >= 0)
+
+/* Multiline
+   comment */
+
+MaybeNumber = Number | Nil
+
+Person = {
+  /- Attributes can have meta -/
+  name: String
+  emails :? {String}
+  password :? String(s | len(s) > 10)
+}(p | p.password != p.name)
+
+People = {{ name: String, age :? Integer, ...: String }}
+
+Pair = <Number, {{ ... }}>
+");
+}
+
+#[test]
+fn test_format_is_idempotent() {
+    let once = format_source(MESSY).unwrap();
+    let twice = format_source(&once).unwrap();
+    assert_eq!(once, twice);
+}
+
+#[test]
+fn test_format_preserves_ast() {
+    let original = parse_schema(MESSY).unwrap();
+    let formatted = format_schema(&original);
+    let reparsed = parse_schema(&formatted).unwrap();
+    assert_eq!(without_positions(&original), without_positions(&reparsed));
+}
+
+#[test]
+fn test_format_nested_braces() {
+    let source = "T = { { a: String } }\nS = { {} }\nR = {{ a: String }}\nU = { {a: String}(t | t.a != \"\") }\n";
+    let original = parse_schema(source).unwrap();
+    let formatted = format_schema(&original);
+    assert_eq!(formatted, "T = { { a: String } }\n\nS = { {} }\n\nR = {{ a: String }}\n\nU = { { a: String }(t | t.a != \"\") }\n");
+    let reparsed = parse_schema(&formatted).unwrap();
+    assert_eq!(without_positions(&original), without_positions(&reparsed));
+}
+
+#[test]
+fn test_format_examples() {
+    for file in ["examples/main.fio", "examples/scalars.fio"] {
+        let source = std::fs::read_to_string(file).unwrap();
+        let original = parse_schema(&source).unwrap();
+        let formatted = format_source(&source).unwrap();
+        assert_eq!(format_source(&formatted).unwrap(), formatted);
+        let reparsed = parse_schema(&formatted).unwrap();
+        assert_eq!(without_positions(&original), without_positions(&reparsed));
+    }
+}
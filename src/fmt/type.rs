@@ -0,0 +1,147 @@
+use crate::fio::{
+    AnyType, BuiltinType, NilType, RefType, RelationType, SeqType, SetType, StructType, SubType,
+    TupleType, Type, UnionType,
+};
+
+use super::FioFormat;
+
+impl FioFormat for Type {
+    fn format(&self, indent: usize) -> String {
+        match self {
+            Type::AnyType(t) => t.format(indent),
+            Type::NilType(t) => t.format(indent),
+            Type::BuiltinType(t) => t.format(indent),
+            Type::RefType(t) => t.format(indent),
+            Type::SeqType(t) => t.format(indent),
+            Type::SetType(t) => t.format(indent),
+            Type::UnionType(t) => t.format(indent),
+            Type::StructType(t) => t.format(indent),
+            Type::SubType(t) => t.format(indent),
+            Type::TupleType(t) => t.format(indent),
+            Type::RelationType(t) => t.format(indent),
+        }
+    }
+}
+
+impl FioFormat for AnyType {
+    fn format(&self, _indent: usize) -> String {
+        String::from(".")
+    }
+}
+
+impl FioFormat for NilType {
+    fn format(&self, _indent: usize) -> String {
+        String::from("Nil")
+    }
+}
+
+impl FioFormat for BuiltinType {
+    fn format(&self, _indent: usize) -> String {
+        format!(".{}", self.name)
+    }
+}
+
+impl FioFormat for RefType {
+    fn format(&self, _indent: usize) -> String {
+        self.name.clone()
+    }
+}
+
+impl FioFormat for SeqType {
+    fn format(&self, indent: usize) -> String {
+        format!("[{}]", self.elm_type.format(indent))
+    }
+}
+
+impl FioFormat for SetType {
+    fn format(&self, indent: usize) -> String {
+        let elm_type = self.elm_type.format(indent);
+        // `{{` would open a relation, so a set of tuples keeps its braces apart
+        if elm_type.starts_with('{') {
+            format!("{{ {} }}", elm_type)
+        } else {
+            format!("{{{}}}", elm_type)
+        }
+    }
+}
+
+impl FioFormat for UnionType {
+    fn format(&self, indent: usize) -> String {
+        self.candidates
+            .iter()
+            .map(|c| c.format(indent))
+            .collect::<Vec<_>>()
+            .join(" | ")
+    }
+}
+
+impl FioFormat for StructType {
+    fn format(&self, indent: usize) -> String {
+        let elements = self.elements
+            .iter()
+            .map(|e| e.format(indent))
+            .collect::<Vec<_>>();
+        format!("<{}>", elements.join(", "))
+    }
+}
+
+impl FioFormat for SubType {
+    fn format(&self, indent: usize) -> String {
+        // The expression is kept verbatim (including its leading spaces) so that
+        // re-parsing yields the very same constraint
+        let constraints = self.constraints
+            .iter()
+            .map(|c| format!("({} |{})", c.param, c.expr))
+            .collect::<String>();
+        format!("{}{}", self.base.format(indent), constraints)
+    }
+}
+
+impl FioFormat for TupleType {
+    fn format(&self, indent: usize) -> String {
+        let foreign_keys: Vec<String> = self.foreign_keys
+            .iter()
+            .map(|fk| format!(
+                " ref({}({}) -> {}({}))",
+                fk.source,
+                fk.source_attributes.join(", "),
+                fk.target,
+                fk.target_attributes.join(", ")
+            ))
+            .collect();
+        format!("{}{}", self.heading.format(indent), foreign_keys.concat())
+    }
+}
+
+impl FioFormat for RelationType {
+    fn format(&self, indent: usize) -> String {
+        let keys: Vec<String> = self.keys
+            .iter()
+            .map(|key| format!(" key({})", key.attributes.join(", ")))
+            .collect();
+        format!("{{{}}}{}", self.heading.format(indent), keys.concat())
+    }
+}
+
+#[cfg(test)]
+use crate::fio::parse_schema;
+
+#[test]
+fn test_format_scalars() {
+    let fio = parse_schema("A = .\nB = Nil\nC = .Number\nD = Integer").unwrap();
+    let formatted: Vec<String> = fio.type_defs.iter().map(|t| t.target.format(0)).collect();
+    assert_eq!(formatted, vec![".", "Nil", ".Number", "Integer"]);
+}
+
+#[test]
+fn test_format_collections() {
+    let fio = parse_schema("A = [ Integer ]\nB = {Integer}\nC = < Number|Integer , Nil >").unwrap();
+    let formatted: Vec<String> = fio.type_defs.iter().map(|t| t.target.format(0)).collect();
+    assert_eq!(formatted, vec!["[Integer]", "{Integer}", "<Number | Integer, Nil>"]);
+}
+
+#[test]
+fn test_format_sub() {
+    let fio = parse_schema("PosInt = Integer( i | i >= 0)").unwrap();
+    assert_eq!(fio.type_defs[0].target.format(0), "Integer(i | i >= 0)");
+}
@@ -0,0 +1,638 @@
+//! Export of finitio schemas as GraphQL schema definitions (SDL), for the
+//! JSON data the finitio types include.
+//!
+//! GraphQL has no type aliases: type definitions that are neither tuples,
+//! relations, unions of tuples nor constrained types are inlined where they
+//! are referenced.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+
+use crate::codegen::pascal_case;
+use crate::schema::{heading::Heading, union::Union, Schema, Type};
+
+/// Maximum number of references followed to find what a type is made of
+const MAX_DEPTH: usize = 64;
+
+/// The scalars of GraphQL, and those declared by the export
+const RESERVED: [&str; 8] = ["Boolean", "Date", "DateTime", "Float", "ID", "Int", "JSON", "String"];
+
+/// A GraphQL schema, and what it could not express
+#[derive(Clone, Debug)]
+pub struct Export {
+    pub sdl: String,
+    pub untranslated: Vec<Untranslated>,
+}
+
+/// A part of a finitio type that GraphQL cannot express, the exported types
+/// accept more values than the finitio type
+#[derive(Clone, Debug, PartialEq)]
+pub struct Untranslated {
+    /// The name of the GraphQL type
+    pub type_name: String,
+    /// The field of the GraphQL type, None for the type itself
+    pub field: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for Untranslated {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.field {
+            Some(field) => write!(f, "{}/{}: {}", self.type_name, field, self.message),
+            None => write!(f, "{}: {}", self.type_name, self.message),
+        }
+    }
+}
+
+/// What a type definition is exported as
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Def {
+    /// An object type and an input type
+    Object,
+    /// The object and input types of its rows
+    Rows,
+    Union,
+    /// A custom scalar
+    Scalar,
+    /// Nothing, references are replaced by the defined type
+    Alias,
+}
+
+/// The type of a GraphQL field, without its nullability
+#[derive(Clone, Debug, PartialEq)]
+enum GqlType {
+    Scalar(String),
+    /// An object type, with an input type named `<name>Input`
+    Object(String),
+    Union(String),
+    /// A list, and whether its elements are nullable
+    List(Box<GqlType>, bool),
+}
+
+/// A field of an object (and input) type
+struct Field {
+    name: String,
+    meta: Option<String>,
+    gql_type: GqlType,
+    nullable: bool,
+}
+
+/// Exports all the type definitions of a schema as GraphQL types.
+///
+/// Tuples are object types along with input types named `<Name>Input`,
+/// relations are lists of row types named `<Name>Row`, and unions of tuples
+/// are unions. Seqs and sets are lists, and fields are non-null unless their
+/// attribute is optional or their type includes Nil. Numbers are floats,
+/// dates, date times, unknown builtins and sub types with constraints are
+/// custom scalars, other values are `JSON`. Inline tuples, relations and sub
+/// types are named after where they are used. Metas are descriptions.
+pub fn export(schema: &Schema) -> Export {
+    let mut exporter = Exporter {
+        schema,
+        gql_names: HashMap::new(),
+        names: RESERVED.iter().map(|name| name.to_string()).collect(),
+        builtins: HashMap::new(),
+        scalars: BTreeMap::new(),
+        aliases: HashMap::new(),
+        items: vec![],
+        untranslated: vec![],
+        type_name: String::new(),
+        field: None,
+    };
+    for name in schema.types.keys() {
+        let gql_name = match exporter.def(name) {
+            Def::Object => exporter.object_name(&pascal_case(name)),
+            Def::Rows => exporter.object_name(&format!("{}Row", pascal_case(name))),
+            Def::Union | Def::Scalar => exporter.unique_name(&pascal_case(name)),
+            Def::Alias => continue,
+        };
+        exporter.gql_names.insert(name.clone(), gql_name);
+    }
+
+    for (name, typedef) in schema.types.iter() {
+        let target = typedef.target_type();
+        let meta = typedef.meta();
+        let def = exporter.def(name);
+        if def == Def::Alias {
+            exporter.alias(name);
+            continue;
+        }
+        let gql_name = exporter.gql_names[name].clone();
+        exporter.type_name = gql_name.clone();
+        exporter.field = None;
+        match (def, &target) {
+            (Def::Object, Type::Tuple(tuple)) => exporter.object(&gql_name, &gql_name, &tuple.heading, meta),
+            (Def::Rows, Type::Relation(relation)) => {
+                exporter.object(&gql_name, &pascal_case(name), &relation.heading, meta)
+            },
+            (Def::Union, Type::Union(union)) => exporter.union(&gql_name, union, meta),
+            _ => exporter.items.push(format!("{}scalar {}\n", description(meta, ""), gql_name)),
+        }
+    }
+
+    let mut sdl = String::from("# Generated by finitio, do not edit\n");
+    for (name, meta) in exporter.scalars.iter() {
+        sdl.push('\n');
+        sdl.push_str(&format!("{}scalar {}\n", description(*meta, ""), name));
+    }
+    for item in exporter.items.iter() {
+        sdl.push('\n');
+        sdl.push_str(item);
+    }
+    Export { sdl, untranslated: exporter.untranslated }
+}
+
+/// A GraphQL string for a `/- meta -/`, block strings span several lines
+fn description(meta: Option<&str>, indent: &str) -> String {
+    let meta = match meta.map(str::trim) {
+        Some(meta) if !meta.is_empty() => meta,
+        _ => return String::new(),
+    };
+    if !meta.contains('\n') {
+        return format!("{}{}\n", indent, serde_json::Value::String(meta.to_string()));
+    }
+    let mut description = format!("{}\"\"\"\n", indent);
+    for line in meta.lines() {
+        let line = format!("{}{}", indent, line.trim().replace("\"\"\"", "\\\"\"\""));
+        description.push_str(line.trim_end());
+        description.push('\n');
+    }
+    description.push_str(&format!("{}\"\"\"\n", indent));
+    description
+}
+
+/// The name of the field of an attribute, GraphQL names are made of
+/// alphanumerics and underscores
+fn field_name(name: &str) -> String {
+    let mut field: String = name.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }).collect();
+    if field.starts_with(|c: char| c.is_ascii_digit()) {
+        field.insert(0, '_');
+    }
+    field
+}
+
+fn is_nil(t: &Type) -> bool {
+    matches!(t, Type::Nil(_))
+}
+
+/// The candidates of a union but Nil
+fn candidates(union: &Union) -> Vec<&Type> {
+    union.candidates.iter().filter(|c| !is_nil(c)).collect()
+}
+
+struct Exporter<'a> {
+    schema: &'a Schema,
+    /// The GraphQL names of the type definitions that are not aliases, the
+    /// names of the row types for relations
+    gql_names: HashMap<String, String>,
+    /// All the GraphQL names in use
+    names: HashSet<String>,
+    /// The names of the scalars of unknown builtins
+    builtins: HashMap<String, String>,
+    /// The scalars used by the fields, and their descriptions
+    scalars: BTreeMap<String, Option<&'static str>>,
+    /// The types aliases stand for, None while being exported
+    aliases: HashMap<String, Option<(GqlType, bool)>>,
+    items: Vec<String>,
+    untranslated: Vec<Untranslated>,
+    /// The GraphQL type being exported
+    type_name: String,
+    /// The field being exported
+    field: Option<String>,
+}
+
+impl<'a> Exporter<'a> {
+    fn report(&mut self, message: String) {
+        self.untranslated.push(Untranslated { type_name: self.type_name.clone(), field: self.field.clone(), message });
+    }
+
+    fn target(&self, name: &str) -> Option<Type> {
+        self.schema.types.get(name).map(|typedef| typedef.target_type())
+    }
+
+    /// The first of `name`, `name2`, `name3`... that is not taken yet
+    fn unique_name(&mut self, name: &str) -> String {
+        crate::codegen::unique_name(&mut self.names, name)
+    }
+
+    /// A unique name for an object type, whose input type is also unique
+    fn object_name(&mut self, name: &str) -> String {
+        let mut unique = name.to_string();
+        let mut index = 1;
+        while self.names.contains(&unique) || self.names.contains(&format!("{}Input", unique)) {
+            index += 1;
+            unique = format!("{}{}", name, index);
+        }
+        self.names.insert(format!("{}Input", unique));
+        self.names.insert(unique.clone());
+        unique
+    }
+
+    /// Declares a scalar that is not a type definition
+    fn scalar(&mut self, name: &str) -> GqlType {
+        let meta = match name {
+            "Date" => Some("An ISO 8601 calendar date, like `2023-01-31`"),
+            "DateTime" => Some("An RFC 3339 date time, like `2023-01-31T10:00:00Z`"),
+            "JSON" => Some("Any JSON value"),
+            _ => None,
+        };
+        if !["Boolean", "Float", "String"].contains(&name) {
+            self.scalars.insert(name.to_string(), meta);
+        }
+        GqlType::Scalar(name.to_string())
+    }
+
+    /// A value GraphQL cannot describe
+    fn json(&mut self, what: &str) -> GqlType {
+        self.report(format!("{} cannot be expressed in GraphQL, exported as JSON", what));
+        self.scalar("JSON")
+    }
+
+    fn builtin(&mut self, target: &str) -> GqlType {
+        match target {
+            "Number" => self.scalar("Float"),
+            "String" => self.scalar("String"),
+            "Boolean" => self.scalar("Boolean"),
+            "Date" | "DateTime" => self.scalar(target),
+            target => {
+                let name = match self.builtins.get(target) {
+                    Some(name) => name.clone(),
+                    None => {
+                        let name = self.unique_name(&pascal_case(target));
+                        self.builtins.insert(target.to_string(), name.clone());
+                        name
+                    },
+                };
+                self.scalar(&name)
+            },
+        }
+    }
+
+    fn def(&self, name: &str) -> Def {
+        match self.target(name) {
+            Some(target) => self.classify(&target),
+            None => Def::Alias,
+        }
+    }
+
+    fn classify(&self, t: &Type) -> Def {
+        match t {
+            Type::Tuple(t) if !t.heading.attributes.is_empty() => Def::Object,
+            Type::Relation(r) if !r.heading.attributes.is_empty() => Def::Rows,
+            Type::Tuple(_) | Type::Relation(_) | Type::Struct(_) => Def::Scalar,
+            Type::Union(u) => {
+                let candidates = candidates(u);
+                if candidates.len() == 1 {
+                    Def::Alias
+                } else if candidates.iter().all(|c| self.is_object(c, 0)) {
+                    Def::Union
+                } else {
+                    Def::Scalar
+                }
+            },
+            // The constraints of objects are not expressed
+            Type::Sub(s) if self.is_object(&s.base_type, 0) => Def::Alias,
+            Type::Sub(_) => Def::Scalar,
+            Type::Any(_) | Type::Nil(_) | Type::Builtin(_) | Type::Ref(_) | Type::Seq(_) | Type::Set(_) => Def::Alias,
+        }
+    }
+
+    /// Whether a type is exported as an object type
+    fn is_object(&self, t: &Type, depth: usize) -> bool {
+        if depth > MAX_DEPTH {
+            return false;
+        }
+        match t {
+            Type::Tuple(t) => !t.heading.attributes.is_empty(),
+            Type::Sub(s) => self.is_object(&s.base_type, depth + 1),
+            Type::Union(u) => match candidates(u).as_slice() {
+                [candidate] => self.is_object(candidate, depth + 1),
+                _ => false,
+            },
+            Type::Ref(r) => self.target(r.name()).is_some_and(|target| self.is_object(&target, depth + 1)),
+            _ => false,
+        }
+    }
+
+    /// The type an alias stands for, exported once
+    fn alias(&mut self, name: &str) -> (GqlType, bool) {
+        match self.aliases.get(name) {
+            Some(Some(alias)) => return alias.clone(),
+            Some(None) => return (self.json(&format!("the recursive type `{}`", name)), true),
+            None => {},
+        }
+        let target = match self.target(name) {
+            Some(target) => target,
+            None => return (self.scalar("JSON"), true),
+        };
+        self.aliases.insert(name.to_string(), None);
+        let location = (std::mem::replace(&mut self.type_name, pascal_case(name)), self.field.take());
+        let alias = self.gql_type(&target, &pascal_case(name));
+        (self.type_name, self.field) = location;
+        self.aliases.insert(name.to_string(), Some(alias.clone()));
+        alias
+    }
+
+    /// The type of a field, and whether it is nullable. Inline types are
+    /// named after `hint`.
+    fn gql_type(&mut self, t: &Type, hint: &str) -> (GqlType, bool) {
+        match t {
+            Type::Any(_) | Type::Nil(_) => (self.scalar("JSON"), true),
+            Type::Builtin(b) => (self.builtin(&b.target), false),
+            Type::Ref(r) => self.reference(r.name()),
+            Type::Seq(s) => self.list(&s.elm_type, hint),
+            Type::Set(s) => self.list(&s.elm_type, hint),
+            Type::Union(u) => {
+                let nullable = u.candidates.iter().any(is_nil);
+                let candidates = candidates(u);
+                if let [candidate] = candidates.as_slice() {
+                    let (gql_type, candidate_nullable) = self.gql_type(candidate, hint);
+                    return (gql_type, nullable || candidate_nullable);
+                }
+                if candidates.iter().all(|c| self.is_object(c, 0)) {
+                    let name = self.unique_name(hint);
+                    self.union(&name, u, None);
+                    (GqlType::Union(name), nullable)
+                } else {
+                    (self.json("a union of other types than tuples"), true)
+                }
+            },
+            Type::Struct(_) => (self.json("a struct"), false),
+            Type::Sub(s) if self.is_object(&s.base_type, 0) => {
+                for constraint in s.constraints.iter() {
+                    let message = format!("`{} | {}` cannot be expressed in GraphQL", constraint.param, constraint.expr.trim());
+                    self.report(message);
+                }
+                self.gql_type(&s.base_type, hint)
+            },
+            Type::Sub(_) => {
+                let name = self.unique_name(hint);
+                self.items.push(format!("scalar {}\n", name));
+                (GqlType::Scalar(name), false)
+            },
+            Type::Tuple(t) if !t.heading.attributes.is_empty() => {
+                let name = self.object_name(hint);
+                self.object(&name, &name, &t.heading, None);
+                (GqlType::Object(name), false)
+            },
+            Type::Relation(r) if !r.heading.attributes.is_empty() => {
+                let name = self.object_name(&format!("{}Row", hint));
+                self.object(&name, hint, &r.heading, None);
+                (GqlType::List(Box::new(GqlType::Object(name)), false), false)
+            },
+            Type::Tuple(_) => (self.json("an empty tuple"), false),
+            Type::Relation(_) => (self.json("an empty relation"), false),
+        }
+    }
+
+    fn reference(&mut self, name: &str) -> (GqlType, bool) {
+        let gql_name = self.gql_names.get(name).cloned().unwrap_or_default();
+        let nullable = matches!(self.target(name), Some(Type::Union(u)) if u.candidates.iter().any(is_nil));
+        match self.def(name) {
+            Def::Object => (GqlType::Object(gql_name), false),
+            Def::Rows => (GqlType::List(Box::new(GqlType::Object(gql_name)), false), false),
+            Def::Union => (GqlType::Union(gql_name), nullable),
+            Def::Scalar => (GqlType::Scalar(gql_name), nullable),
+            Def::Alias => self.alias(name),
+        }
+    }
+
+    fn list(&mut self, elm_type: &Type, hint: &str) -> (GqlType, bool) {
+        let (elm, nullable) = self.gql_type(elm_type, hint);
+        (GqlType::List(Box::new(elm), nullable), false)
+    }
+
+    /// Exports the object and input types of a heading, the inline types of
+    /// its attributes are named after `hint`
+    fn object(&mut self, name: &str, hint: &str, heading: &Heading, meta: Option<&str>) {
+        let at = self.items.len();
+        let location = (std::mem::replace(&mut self.type_name, name.to_string()), self.field.take());
+
+        let mut attribute_names: Vec<&String> = heading.attributes.keys().collect();
+        attribute_names.sort();
+        let mut field_names = HashSet::new();
+        let mut fields = vec![];
+        for attribute_name in attribute_names {
+            let attribute = &heading.attributes[attribute_name];
+            let field = crate::codegen::unique_name(&mut field_names, &field_name(attribute_name));
+            self.field = Some(field.clone());
+            if field != *attribute_name {
+                self.report(format!("the attribute `{}` is exported as `{}`", attribute_name, field));
+            }
+            let hint = format!("{}{}", hint, pascal_case(attribute_name));
+            let (gql_type, nullable) = self.gql_type(&attribute.att_type, &hint);
+            fields.push(Field {
+                name: field,
+                meta: attribute.meta.clone(),
+                gql_type,
+                nullable: nullable || attribute.optional,
+            });
+        }
+        self.field = None;
+        if heading.allow_extra.is_some() {
+            self.report(String::from("extra attributes cannot be expressed in GraphQL"));
+        }
+
+        let mut object = format!("{}type {} {{\n", description(meta, ""), name);
+        let mut input = format!("{}input {}Input {{\n", description(meta, ""), name);
+        for field in fields.iter() {
+            self.field = Some(field.name.clone());
+            let docs = description(field.meta.as_deref(), "  ");
+            let bang = if field.nullable { "" } else { "!" };
+            object.push_str(&format!("{}  {}: {}{}\n", docs, field.name, self.render(&field.gql_type, false), bang));
+            input.push_str(&format!("{}  {}: {}{}\n", docs, field.name, self.render(&field.gql_type, true), bang));
+        }
+        object.push_str("}\n");
+        input.push_str("}\n");
+        self.items.insert(at, object);
+        self.items.insert(at + 1, input);
+        (self.type_name, self.field) = location;
+    }
+
+    /// Exports a union of object types
+    fn union(&mut self, name: &str, union: &Union, meta: Option<&str>) {
+        let at = self.items.len();
+        let mut members: Vec<String> = vec![];
+        for (index, candidate) in candidates(union).into_iter().enumerate() {
+            if let (GqlType::Object(member), _) = self.gql_type(candidate, &format!("{}{}", name, index + 1)) {
+                if !members.contains(&member) {
+                    members.push(member);
+                }
+            }
+        }
+        self.items.insert(at, format!("{}union {} = {}\n", description(meta, ""), name, members.join(" | ")));
+    }
+
+    /// The name of a type in object or input types
+    fn render(&mut self, gql_type: &GqlType, input: bool) -> String {
+        match gql_type {
+            GqlType::Scalar(name) => name.clone(),
+            GqlType::Object(name) if input => format!("{}Input", name),
+            GqlType::Object(name) => name.clone(),
+            GqlType::Union(name) if input => {
+                self.report(format!("GraphQL has no input unions, `{}` is exported as JSON in inputs", name));
+                self.scalar("JSON");
+                String::from("JSON")
+            },
+            GqlType::Union(name) => name.clone(),
+            GqlType::List(elm, nullable) => {
+                let bang = if *nullable { "" } else { "!" };
+                format!("[{}{}]", self.render(elm, input), bang)
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+fn export_source(source: &str) -> Export {
+    let schema = crate::schema::schema::test_schema(source);
+    export(&schema)
+}
+
+#[test]
+fn test_export_types() {
+    let export = export_source(r#"
+/- A person -/
+Person = {
+  /- The full name -/
+  name: Name
+  born: .Date | Nil
+  nickname :? .String
+  tags: {.String}
+  friends: [Person]
+  address :? { city: .String }
+}
+/- A non empty name -/
+Name = .String(s | len(s) > 0)
+Shape = Circle | Square | Nil
+Circle = { radius: .Number }
+Square = { side: .Number }
+Catalog = {{ id: .String, price: .Number }} key(id)
+Shop = { catalog: Catalog, owner: MaybePerson, size: .Size }
+MaybePerson = Person | Nil
+"#);
+    assert_eq!(export.sdl, r#"# Generated by finitio, do not edit
+
+"An ISO 8601 calendar date, like `2023-01-31`"
+scalar Date
+
+scalar Size
+
+type CatalogRow {
+  id: String!
+  price: Float!
+}
+
+input CatalogRowInput {
+  id: String!
+  price: Float!
+}
+
+type Circle {
+  radius: Float!
+}
+
+input CircleInput {
+  radius: Float!
+}
+
+"A non empty name"
+scalar Name
+
+"A person"
+type Person {
+  address: PersonAddress
+  born: Date
+  friends: [Person!]!
+  "The full name"
+  name: Name!
+  nickname: String
+  tags: [String!]!
+}
+
+"A person"
+input PersonInput {
+  address: PersonAddressInput
+  born: Date
+  friends: [PersonInput!]!
+  "The full name"
+  name: Name!
+  nickname: String
+  tags: [String!]!
+}
+
+type PersonAddress {
+  city: String!
+}
+
+input PersonAddressInput {
+  city: String!
+}
+
+union Shape = Circle | Square
+
+type Shop {
+  catalog: [CatalogRow!]!
+  owner: Person
+  size: Size!
+}
+
+input ShopInput {
+  catalog: [CatalogRowInput!]!
+  owner: PersonInput
+  size: Size!
+}
+
+type Square {
+  side: Float!
+}
+
+input SquareInput {
+  side: Float!
+}
+"#);
+    assert!(export.untranslated.is_empty());
+}
+
+#[test]
+fn test_export_untranslated() {
+    let export = export_source(r#"
+Date = { day: .Number, ... }
+Circle = { radius: .Number }
+Square = { side: .Number }
+Drawing = { shapes: [Circle | Square], nick.name: .String, nick_name: .Number, position: <.Number, .Number>, tree: Tree }
+Tree = [Tree]
+Big = Circle(c | c.radius > 10)
+"#);
+    assert!(export.sdl.contains("type Date2 {\n  day: Float!\n}\n"));
+    assert!(export.sdl.contains(r#"type Drawing {
+  nick_name: String!
+  nick_name2: Float!
+  position: JSON!
+  shapes: [DrawingShapes!]!
+  tree: [JSON]!
+}
+
+input DrawingInput {
+  nick_name: String!
+  nick_name2: Float!
+  position: JSON!
+  shapes: [JSON!]!
+  tree: [JSON]!
+}
+
+union DrawingShapes = Circle | Square
+"#));
+    assert_eq!(
+        export.untranslated.iter().map(Untranslated::to_string).collect::<Vec<_>>(),
+        vec![
+            "Big: `c | c.radius > 10` cannot be expressed in GraphQL",
+            "Date2: extra attributes cannot be expressed in GraphQL",
+            "Drawing/nick_name: the attribute `nick.name` is exported as `nick_name`",
+            "Drawing/nick_name2: the attribute `nick_name` is exported as `nick_name2`",
+            "Drawing/position: a struct cannot be expressed in GraphQL, exported as JSON",
+            "Tree: the recursive type `Tree` cannot be expressed in GraphQL, exported as JSON",
+            "Drawing/shapes: GraphQL has no input unions, `DrawingShapes` is exported as JSON in inputs",
+        ]
+    );
+}
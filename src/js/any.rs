@@ -1,6 +1,6 @@
 use snafu::Whatever;
 
-use crate::schema::{TypeInclude, any::Any};
+use crate::schema::{TypeInclude, TypeDress, TypeUndress, FinitioValue, DressOptions, any::Any};
 
 impl<T> TypeInclude<T> for Any {
     fn include(&self, _: &T) -> Result<(), Whatever> {
@@ -8,6 +8,18 @@ impl<T> TypeInclude<T> for Any {
     }
 }
 
+impl TypeDress<serde_json::Value> for Any {
+    fn dress_with(&self, v: &serde_json::Value, _options: &DressOptions) -> Result<FinitioValue, Whatever> {
+        Ok(FinitioValue::Any(v.clone()))
+    }
+}
+
+impl TypeUndress<serde_json::Value> for Any {
+    fn undress(&self, value: &FinitioValue) -> Result<serde_json::Value, Whatever> {
+        Ok(value.to_json())
+    }
+}
+
 #[cfg(test)]
 #[test]
 fn test_include_any() {
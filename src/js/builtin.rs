@@ -1,5 +1,26 @@
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime};
 use snafu::{Whatever, whatever};
-use crate::schema::{TypeInclude, builtin::Builtin};
+use crate::schema::{TypeInclude, TypeDress, TypeUndress, FinitioValue, DressOptions, builtin::Builtin};
+
+/// Dates are encoded as ISO 8601 calendar dates (`2023-01-31`)
+fn parse_date(s: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").ok()
+}
+
+/// Date times are encoded as RFC 3339 strings (`2023-01-31T10:00:00Z`).
+/// When `lenient`, date times without offset and dates are accepted as UTC.
+fn parse_datetime(s: &str, lenient: bool) -> Option<DateTime<FixedOffset>> {
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(s) {
+        return Some(datetime);
+    }
+    if !lenient {
+        return None;
+    }
+    NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f")
+        .ok()
+        .or_else(|| parse_date(s).and_then(|date| date.and_hms_opt(0, 0, 0)))
+        .map(|datetime| datetime.and_utc().fixed_offset())
+}
 
 impl TypeInclude<serde_json::Value> for Builtin {
     fn include(&self, v: &serde_json::Value) -> Result<(), Whatever> {
@@ -22,12 +43,67 @@ impl TypeInclude<serde_json::Value> for Builtin {
                     v => whatever!("Not a valid builtin Boolean: {}", v)
                 }
             },
+            "Date" => {
+                match v {
+                    serde_json::Value::String(s) if parse_date(s).is_some() => Ok(()),
+                    v => whatever!("Not a valid builtin Date: {}", v)
+                }
+            },
+            "DateTime" => {
+                match v {
+                    serde_json::Value::String(s) if parse_datetime(s, false).is_some() => Ok(()),
+                    v => whatever!("Not a valid builtin DateTime: {}", v)
+                }
+            },
             &_ => whatever!("Unsupported builtin type name: {}", self.target)
         }
     }
 }
 
 
+impl TypeDress<serde_json::Value> for Builtin {
+    fn dress_with(&self, v: &serde_json::Value, options: &DressOptions) -> Result<FinitioValue, Whatever> {
+        use serde_json::Value;
+
+        let coerce = options.coerce;
+        let dressed = match (self.target.as_str(), v) {
+            ("Number", Value::Number(n)) => Some(FinitioValue::Number(n.clone())),
+            ("Number", Value::String(s)) if coerce => s.parse().ok().map(FinitioValue::Number),
+            ("String", Value::String(s)) => Some(FinitioValue::String(s.clone())),
+            ("Boolean", Value::Bool(b)) => Some(FinitioValue::Boolean(*b)),
+            ("Boolean", Value::String(s)) if coerce => match s.as_str() {
+                "true" => Some(FinitioValue::Boolean(true)),
+                "false" => Some(FinitioValue::Boolean(false)),
+                _ => None,
+            },
+            ("Date", Value::String(s)) => parse_date(s).map(FinitioValue::Date),
+            ("DateTime", Value::String(s)) => parse_datetime(s, coerce).map(FinitioValue::DateTime),
+            _ => None,
+        };
+        match dressed {
+            Some(value) => Ok(value),
+            None => {
+                // Reports unsupported builtins and invalid values alike
+                self.include(v)?;
+                whatever!("Not a valid builtin {}: {}", self.target, v)
+            }
+        }
+    }
+}
+
+impl TypeUndress<serde_json::Value> for Builtin {
+    fn undress(&self, value: &FinitioValue) -> Result<serde_json::Value, Whatever> {
+        match (self.target.as_str(), value.unnamed()) {
+            ("Number", FinitioValue::Number(_))
+            | ("String", FinitioValue::String(_))
+            | ("Boolean", FinitioValue::Boolean(_))
+            | ("Date", FinitioValue::Date(_))
+            | ("DateTime", FinitioValue::DateTime(_)) => Ok(value.to_json()),
+            (_, value) => whatever!("Not a valid builtin {}: {:?}", self.target, value)
+        }
+    }
+}
+
 #[cfg(test)]
 #[test]
 fn test_include_builtin() {
@@ -55,3 +131,37 @@ fn test_include_builtin() {
     let arr = serde_json::json!([]);
     assert_eq!(builtin_str.include(&arr).is_ok(), false);
 }
+
+#[test]
+fn test_dress_builtin() {
+    use crate::common::FilePosition;
+
+    let builtin = |target: &str| Builtin {
+        position: FilePosition { line: 1, column: 1 },
+        target: String::from(target),
+    };
+    let strict = DressOptions::default();
+    let lenient = DressOptions::lenient();
+
+    // strict mode is the default
+    assert!(builtin("Number").dress(&serde_json::json!("42")).is_err());
+    assert!(builtin("Boolean").dress(&serde_json::json!("true")).is_err());
+
+    assert_eq!(builtin("Number").dress_with(&serde_json::json!("42"), &lenient).unwrap(), FinitioValue::Number(42.into()));
+    assert_eq!(builtin("Number").dress_with(&serde_json::json!("-1.5"), &lenient).unwrap(), FinitioValue::Number(serde_json::Number::from_f64(-1.5).unwrap()));
+    assert!(builtin("Number").dress_with(&serde_json::json!(" 42"), &lenient).is_err());
+    assert!(builtin("Number").dress_with(&serde_json::json!(""), &lenient).is_err());
+    assert_eq!(builtin("Boolean").dress_with(&serde_json::json!("false"), &lenient).unwrap(), FinitioValue::Boolean(false));
+    assert!(builtin("Boolean").dress_with(&serde_json::json!("yes"), &lenient).is_err());
+    // strings are never coerced from other values
+    assert!(builtin("String").dress_with(&serde_json::json!(42), &lenient).is_err());
+
+    let date = NaiveDate::from_ymd_opt(2023, 1, 31).unwrap();
+    assert_eq!(builtin("Date").dress_with(&serde_json::json!("2023-01-31"), &strict).unwrap(), FinitioValue::Date(date));
+    assert!(builtin("Date").include(&serde_json::json!("2023-02-31")).is_err());
+
+    let datetime = date.and_hms_opt(10, 0, 0).unwrap().and_utc().fixed_offset();
+    assert_eq!(builtin("DateTime").dress(&serde_json::json!("2023-01-31T10:00:00Z")).unwrap(), FinitioValue::DateTime(datetime));
+    assert!(builtin("DateTime").dress(&serde_json::json!("2023-01-31T10:00:00")).is_err());
+    assert_eq!(builtin("DateTime").dress_with(&serde_json::json!("2023-01-31T10:00:00"), &lenient).unwrap(), FinitioValue::DateTime(datetime));
+}
@@ -11,7 +11,9 @@ impl ConstraintExecute<serde_json::Value> for &Constraint {
         Some(node) => node
       };
 
+      // Functions are not kept when cloning compiled expressions
       let res = node.clone()
+        .function("defined", defined)
         .value(self.param.clone(), v)
         .exec()
         .with_whatever_context(|_| format!("Unable to evaluate constraint: {}", self))?;
@@ -25,6 +27,15 @@ impl ConstraintExecute<serde_json::Value> for &Constraint {
   }
 }
 
+/// `defined(p.x)`: whether an (optional) attribute is present, absent ones
+/// evaluate to null
+fn defined(args: Vec<Value>) -> Result<Value, resolver::Error> {
+  match args.as_slice() {
+    [value] => Ok(Value::Bool(!value.is_null())),
+    _ => Err(resolver::Error::Custom(format!("defined() expects 1 argument, got {}", args.len()))),
+  }
+}
+
 impl fmt::Display for Constraint {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     match self.expr_node {
@@ -1,39 +1,25 @@
-use snafu::{Whatever, whatever};
-use crate::schema::{TypeInclude, heading::{Heading}};
+use snafu::{Whatever, whatever, ResultExt};
+use crate::schema::{TypeInclude, TypeDress, TypeUndress, DressOptions, Attributes, heading::{Heading}};
 
 impl TypeInclude<serde_json::Value> for Heading {
     fn include(&self, v: &serde_json::Value) -> Result<(), Whatever> {
         match v {
             serde_json::Value::Object(obj) => {
-                // check for extra props
-                let extras: Vec<String> = obj
-                    .keys()
-                    .filter(|prop| !self.attributes.contains_key(*prop))
-                    .map(|s| String::from(s))
-                    .collect();
-
-                if extras.len() > 0 {
-                    whatever!("The objet has extra properties: {}", extras.join(","))
-                }
+                self.include_extras(obj)?;
 
                 // check for missing props
-                let missing: Vec<String> = self.attributes
-                    .values()
-                    .filter(|prop| {
-                        !prop.optional && !obj.contains_key(&prop.name)
-                    })
-                    .map(|a| {
-                        a.name.clone()
-                    })
-                    .collect();
-
+                let missing = self.missing_attributes(obj);
                 if missing.len() > 0 {
                     whatever!("The objet is missing properties: {}", missing.join(","))
                 }
 
                 // validate all properties
                 let mut errors = obj.iter().fold(Vec::new(), |mut errors, (name, value)| {
-                    let att = self.attributes.get(name).unwrap();
+                    let att = match self.attributes.get(name) {
+                        Some(att) => att,
+                        // extras have been validated above
+                        None => return errors,
+                    };
                     let is_valid = att.att_type.include(value);
                     match is_valid {
                         Ok(_) => (),
@@ -55,3 +41,123 @@ impl TypeInclude<serde_json::Value> for Heading {
         }
     }
 }
+
+impl Heading {
+    /// Checks the properties of an object that are not attributes of the
+    /// heading: they are rejected, unless the heading allows extra attributes
+    /// (`...`), whose values must then be of the extra type (`...: T`)
+    fn include_extras(&self, obj: &serde_json::Map<String, serde_json::Value>) -> Result<(), Whatever> {
+        let extras: Vec<&String> = obj.keys().filter(|prop| !self.attributes.contains_key(*prop)).collect();
+        match &self.allow_extra {
+            None if !extras.is_empty() => {
+                let names: Vec<&str> = extras.iter().map(|name| name.as_str()).collect();
+                whatever!("The objet has extra properties: {}", names.join(","))
+            },
+            None => Ok(()),
+            Some(extra_type) => {
+                for name in extras {
+                    let err = extra_type.include(&obj[name]);
+                    whatever!(err, "Invalid value for extra attribute: {}", name);
+                }
+                Ok(())
+            },
+        }
+    }
+
+    fn missing_attributes(&self, obj: &serde_json::Map<String, serde_json::Value>) -> Vec<String> {
+        self.attributes
+            .values()
+            .filter(|prop| !prop.optional && !obj.contains_key(&prop.name))
+            .map(|a| a.name.clone())
+            .collect()
+    }
+
+    /// Dresses the attributes (and the extra ones) of an object accepted by
+    /// the heading
+    pub(crate) fn dress_attributes(&self, v: &serde_json::Value, options: &DressOptions) -> Result<Attributes, Whatever> {
+        let obj = match v {
+            serde_json::Value::Object(obj) => obj,
+            v => whatever!("Value not compatible with heading: {}", v)
+        };
+        self.include_extras(obj)?;
+        let missing = self.missing_attributes(obj);
+        if !missing.is_empty() {
+            whatever!("The objet is missing properties: {}", missing.join(","))
+        }
+        let mut attributes = Attributes::new();
+        for (name, value) in obj.iter() {
+            let att_type = match (self.attributes.get(name), &self.allow_extra) {
+                (Some(att), _) => &att.att_type,
+                (None, Some(extra_type)) => extra_type.as_ref(),
+                (None, None) => whatever!("The objet has extra properties: {}", name),
+            };
+            let value = att_type.dress_with(value, options)
+                .with_whatever_context(|_| format!("Invalid value for attribute: {}", name))?;
+            attributes.insert(name.clone(), value);
+        }
+        Ok(attributes)
+    }
+
+    /// Encodes the attributes of a tuple as an object, absent optional
+    /// attributes are omitted
+    pub(crate) fn undress_attributes(&self, attributes: &Attributes) -> Result<serde_json::Map<String, serde_json::Value>, Whatever> {
+        let mut missing: Vec<&str> = self.attributes
+            .values()
+            .filter(|att| !att.optional && !attributes.contains_key(&att.name))
+            .map(|att| att.name.as_str())
+            .collect();
+        missing.sort_unstable();
+        if !missing.is_empty() {
+            whatever!("The tuple is missing attributes: {}", missing.join(","))
+        }
+        let mut obj = serde_json::Map::new();
+        for (name, value) in attributes.iter() {
+            let att_type = match (self.attributes.get(name), &self.allow_extra) {
+                (Some(att), _) => &att.att_type,
+                (None, Some(extra_type)) => extra_type.as_ref(),
+                (None, None) => whatever!("The tuple has extra attributes: {}", name),
+            };
+            let v = att_type.undress(value)
+                .with_whatever_context(|_| format!("Invalid value for attribute: {}", name))?;
+            obj.insert(name.clone(), v);
+        }
+        Ok(obj)
+    }
+}
+
+#[cfg(test)]
+fn include_source(source: &str, name: &str, v: serde_json::Value) -> Result<(), Whatever> {
+    let schema = crate::schema::schema::test_schema(source);
+    schema.types[name].target_type().include(&v)
+}
+
+#[cfg(test)]
+fn root_cause(err: Whatever) -> String {
+    let mut cause: &dyn std::error::Error = &err;
+    while let Some(source) = cause.source() {
+        cause = source;
+    }
+    cause.to_string()
+}
+
+#[test]
+fn test_include_extras() {
+    let source = "Closed = { name: .String }\nOpen = { name: .String, ... }\nTyped = { name: .String, ...: .Number }";
+
+    // Without `...`, extra properties are rejected
+    assert!(include_source(source, "Closed", serde_json::json!({ "name": "a" })).is_ok());
+    let err = include_source(source, "Closed", serde_json::json!({ "name": "a", "age": 1 })).unwrap_err();
+    assert_eq!(root_cause(err), "The objet has extra properties: age");
+
+    // With `...`, any extra property is accepted
+    assert!(include_source(source, "Open", serde_json::json!({ "name": "a", "age": 1, "tags": [] })).is_ok());
+
+    // With `...: T`, extra properties must be of type T
+    assert!(include_source(source, "Typed", serde_json::json!({ "name": "a", "age": 1 })).is_ok());
+    let err = include_source(source, "Typed", serde_json::json!({ "name": "a", "nick": "b" })).unwrap_err();
+    assert!(format!("{:?}", err).contains("Invalid value for extra attribute: nick"));
+
+    // Declared attributes are still required, and checked against their own type
+    assert!(include_source(source, "Open", serde_json::json!({ "age": 1 })).is_err());
+    assert!(include_source(source, "Typed", serde_json::json!({ "name": 1 })).is_err());
+}
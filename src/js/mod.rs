@@ -13,5 +13,8 @@ mod tuple;
 mod r#struct;
 mod heading;
 mod constraint;
+mod normalize;
 
 pub use schema::generate_json;
+pub use normalize::normalize;
+pub(crate) use normalize::{canonical_json, normalize_json};
@@ -1,6 +1,6 @@
 use snafu::{Whatever, whatever};
 
-use crate::schema::{TypeInclude, nil::Nil};
+use crate::schema::{TypeInclude, TypeDress, TypeUndress, FinitioValue, DressOptions, nil::Nil};
 
 impl TypeInclude<serde_json::Value> for Nil {
     fn include(&self, v: &serde_json::Value) -> Result<(), Whatever> {
@@ -11,6 +11,27 @@ impl TypeInclude<serde_json::Value> for Nil {
     }
 }
 
+impl TypeDress<serde_json::Value> for Nil {
+    fn dress_with(&self, v: &serde_json::Value, options: &DressOptions) -> Result<FinitioValue, Whatever> {
+        match v {
+            serde_json::Value::String(s) if options.coerce && options.nil_strings.contains(s) => Ok(FinitioValue::Nil),
+            v => {
+                self.include(v)?;
+                Ok(FinitioValue::Nil)
+            }
+        }
+    }
+}
+
+impl TypeUndress<serde_json::Value> for Nil {
+    fn undress(&self, value: &FinitioValue) -> Result<serde_json::Value, Whatever> {
+        match value.unnamed() {
+            FinitioValue::Nil => Ok(serde_json::Value::Null),
+            value => whatever!("not a nil value: {:?}", value)
+        }
+    }
+}
+
 #[cfg(test)]
 #[test]
 fn test_include_nil() {
@@ -0,0 +1,120 @@
+use snafu::{Whatever, ResultExt};
+
+use crate::schema::{TypeDress, TypeUndress, FinitioValue, Attributes};
+
+/// Integral floats are written as integers, so that `1.0` and `1` agree
+fn normalize_number(n: &serde_json::Number) -> serde_json::Number {
+    /// Floats are exact integers up to 2^53
+    const MAX_EXACT: f64 = 9007199254740992.0;
+
+    match n.as_f64() {
+        Some(f) if n.is_f64() && f.fract() == 0.0 && f.abs() <= MAX_EXACT => (f as i64).into(),
+        _ => n.clone(),
+    }
+}
+
+pub(crate) fn normalize_json(v: &serde_json::Value) -> serde_json::Value {
+    match v {
+        serde_json::Value::Number(n) => serde_json::Value::Number(normalize_number(n)),
+        serde_json::Value::Array(a) => serde_json::Value::Array(a.iter().map(normalize_json).collect()),
+        serde_json::Value::Object(o) => {
+            serde_json::Value::Object(o.iter().map(|(k, v)| (k.clone(), normalize_json(v))).collect())
+        },
+        v => v.clone(),
+    }
+}
+
+fn normalize_attributes(attributes: &Attributes) -> Attributes {
+    attributes.iter().map(|(name, value)| (name.clone(), normalize_value(value))).collect()
+}
+
+fn normalize_value(value: &FinitioValue) -> FinitioValue {
+    let all = |values: &[FinitioValue]| values.iter().map(normalize_value).collect();
+    match value {
+        FinitioValue::Any(v) => FinitioValue::Any(normalize_json(v)),
+        FinitioValue::Number(n) => FinitioValue::Number(normalize_number(n)),
+        FinitioValue::Seq(values) => FinitioValue::Seq(all(values)),
+        FinitioValue::Set(values) => FinitioValue::Set(all(values)),
+        FinitioValue::Struct(values) => FinitioValue::Struct(all(values)),
+        FinitioValue::Tuple(attributes) => FinitioValue::Tuple(normalize_attributes(attributes)),
+        FinitioValue::Relation(tuples) => FinitioValue::Relation(tuples.iter().map(normalize_attributes).collect()),
+        FinitioValue::Union { candidate, value } => FinitioValue::Union {
+            candidate: *candidate,
+            value: Box::new(normalize_value(value)),
+        },
+        FinitioValue::Named { name, value } => FinitioValue::Named {
+            name: name.clone(),
+            value: Box::new(normalize_value(value)),
+        },
+        value => value.clone(),
+    }
+}
+
+/// The canonical JSON encoding of a dressed value: numbers are normalized and
+/// the elements of sets and relations sorted, so that equal values, nested
+/// ones included, have equal encodings
+pub(crate) fn canonical_json(value: &FinitioValue) -> serde_json::Value {
+    let sorted = |mut values: Vec<serde_json::Value>| {
+        values.sort_by_cached_key(|v| v.to_string());
+        serde_json::Value::Array(values)
+    };
+    let object = |attributes: &Attributes| {
+        serde_json::Value::Object(attributes.iter().map(|(name, value)| (name.clone(), canonical_json(value))).collect())
+    };
+    match value.untagged() {
+        FinitioValue::Seq(values) | FinitioValue::Struct(values) => {
+            serde_json::Value::Array(values.iter().map(canonical_json).collect())
+        },
+        FinitioValue::Set(values) => sorted(values.iter().map(canonical_json).collect()),
+        FinitioValue::Tuple(attributes) => object(attributes),
+        FinitioValue::Relation(tuples) => sorted(tuples.iter().map(object).collect()),
+        value => normalize_json(&value.to_json()),
+    }
+}
+
+/// Returns the canonical JSON encoding of data accepted by a type: sets and
+/// relations are sorted, numbers normalized and duplicates rejected. Object
+/// keys are kept sorted by `serde_json::Map`.
+pub fn normalize<T>(t: &T, v: &serde_json::Value) -> Result<serde_json::Value, Whatever>
+where
+    T: TypeDress<serde_json::Value> + TypeUndress<serde_json::Value>,
+{
+    let value = t.dress(v)?;
+    t.undress(&normalize_value(&value))
+        .with_whatever_context(|_| format!("Unable to normalize: {}", v))
+}
+
+#[test]
+fn test_normalize() {
+    let source = "
+Tags = {.String}
+Point = <.Number, .Number>
+People = {{ name: .String, age: .Number, meta :? . }}
+";
+    let schema = crate::schema::schema::test_schema(source);
+
+    let people = serde_json::json!([
+        { "name": "Zoe", "age": 33.0 },
+        { "age": 1e1, "name": "Al", "meta": { "b": 2.0, "a": [1.5] } },
+    ]);
+    let normalized = normalize(&schema.types["People"], &people).unwrap();
+    assert_eq!(
+        serde_json::to_string(&normalized).unwrap(),
+        r#"[{"age":10,"meta":{"a":[1.5],"b":2},"name":"Al"},{"age":33,"name":"Zoe"}]"#
+    );
+
+    // semantically equal documents normalize identically
+    let reordered = serde_json::json!([
+        { "name": "Al", "age": 10, "meta": { "a": [1.5], "b": 2 } },
+        { "name": "Zoe", "age": 33 },
+    ]);
+    assert_eq!(normalize(&schema.types["People"], &reordered).unwrap(), normalized);
+
+    let point = normalize(&schema.types["Point"], &serde_json::json!([-0.0, 2.50])).unwrap();
+    assert_eq!(serde_json::to_string(&point).unwrap(), "[0,2.5]");
+
+    // duplicates are rejected, including the ones revealed by normalization
+    assert!(normalize(&schema.types["Tags"], &serde_json::json!(["a", "a"])).is_err());
+    let people = serde_json::json!([{ "name": "Al", "age": 10 }, { "name": "Al", "age": 10.0 }]);
+    assert!(normalize(&schema.types["People"], &people).is_err());
+}
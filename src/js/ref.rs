@@ -1,6 +1,6 @@
-use snafu::Whatever;
+use snafu::{Whatever, whatever};
 
-use crate::schema::{TypeInclude, r#ref::Ref, TypeRef};
+use crate::schema::{TypeInclude, TypeDress, TypeUndress, FinitioValue, DressOptions, r#ref::Ref, TypeRef};
 
 impl TypeInclude<serde_json::Value> for Ref {
     fn include(&self, v: &serde_json::Value) -> Result<(), Whatever> {
@@ -50,6 +50,57 @@ impl TypeInclude<serde_json::Value> for TypeRef {
 }
 
 
+impl TypeDress<serde_json::Value> for Ref {
+    fn dress_with(&self, v: &serde_json::Value, options: &DressOptions) -> Result<FinitioValue, Whatever> {
+        self.target.dress_with(v, options)
+    }
+}
+
+impl TypeDress<serde_json::Value> for TypeRef {
+    fn dress_with(&self, v: &serde_json::Value, options: &DressOptions) -> Result<FinitioValue, Whatever> {
+        let value = match self {
+            TypeRef::Any(t) => t.any_.upgrade().unwrap().borrow().dress_with(v, options),
+            TypeRef::Nil(t) => t.nil_.upgrade().unwrap().borrow().dress_with(v, options),
+            TypeRef::Builtin(t) => t.builtin_.upgrade().unwrap().borrow().dress_with(v, options),
+            TypeRef::Ref(t) => t.ref_.upgrade().unwrap().borrow().dress_with(v, options),
+            TypeRef::Seq(t) => t.seq_.upgrade().unwrap().borrow().dress_with(v, options),
+            TypeRef::Set(t) => t.set_.upgrade().unwrap().borrow().dress_with(v, options),
+            TypeRef::Union(t) => t.union_.upgrade().unwrap().borrow().dress_with(v, options),
+            TypeRef::Struct(t) => t.struct_.upgrade().unwrap().borrow().dress_with(v, options),
+            TypeRef::Sub(t) => t.sub_.upgrade().unwrap().borrow().dress_with(v, options),
+            TypeRef::Tuple(t) => t.tuple_.upgrade().unwrap().borrow().dress_with(v, options),
+            TypeRef::Relation(t) => t.relation_.upgrade().unwrap().borrow().dress_with(v, options),
+            TypeRef::Unresolved { name, position: _ } => whatever!("Unresolved type reference: {}", name),
+        }?;
+        Ok(FinitioValue::Named { name: self.name().to_string(), value: Box::new(value) })
+    }
+}
+
+impl TypeUndress<serde_json::Value> for Ref {
+    fn undress(&self, value: &FinitioValue) -> Result<serde_json::Value, Whatever> {
+        self.target.undress(value)
+    }
+}
+
+impl TypeUndress<serde_json::Value> for TypeRef {
+    fn undress(&self, value: &FinitioValue) -> Result<serde_json::Value, Whatever> {
+        match self {
+            TypeRef::Any(t) => t.any_.upgrade().unwrap().borrow().undress(value),
+            TypeRef::Nil(t) => t.nil_.upgrade().unwrap().borrow().undress(value),
+            TypeRef::Builtin(t) => t.builtin_.upgrade().unwrap().borrow().undress(value),
+            TypeRef::Ref(t) => t.ref_.upgrade().unwrap().borrow().undress(value),
+            TypeRef::Seq(t) => t.seq_.upgrade().unwrap().borrow().undress(value),
+            TypeRef::Set(t) => t.set_.upgrade().unwrap().borrow().undress(value),
+            TypeRef::Union(t) => t.union_.upgrade().unwrap().borrow().undress(value),
+            TypeRef::Struct(t) => t.struct_.upgrade().unwrap().borrow().undress(value),
+            TypeRef::Sub(t) => t.sub_.upgrade().unwrap().borrow().undress(value),
+            TypeRef::Tuple(t) => t.tuple_.upgrade().unwrap().borrow().undress(value),
+            TypeRef::Relation(t) => t.relation_.upgrade().unwrap().borrow().undress(value),
+            TypeRef::Unresolved { name, position: _ } => whatever!("Unresolved type reference: {}", name),
+        }
+    }
+}
+
 #[cfg(test)]
 use crate::schema::{builtin::Builtin, r#type::Type};
 #[test]
@@ -1,8 +1,9 @@
 use std::collections::{HashMap};
-use serde_hashkey::{to_key};
+use serde_hashkey::{to_key_with_ordered_float as to_key};
 use snafu::{Whatever, whatever, ResultExt};
 
-use crate::schema::{TypeInclude, relation::{Relation}};
+use crate::schema::{TypeInclude, FinitioValue, DressOptions, TypeDress, TypeUndress, relation::{Relation}};
+use super::{canonical_json, normalize_json};
 
 impl TypeInclude<serde_json::Value> for Relation {
     fn include(&self, v: &serde_json::Value) -> Result<(), Whatever> {
@@ -23,13 +24,97 @@ impl TypeInclude<serde_json::Value> for Relation {
                         }
                     }
                 }
-                Ok(())
+                self.check_keys(arr)
             },
             v => whatever!("Invalid value for Relation: {}", v)
         }
     }
 }
 
+impl TypeDress<serde_json::Value> for Relation {
+    fn dress_with(&self, v: &serde_json::Value, options: &DressOptions) -> Result<FinitioValue, Whatever> {
+        match v {
+            serde_json::Value::Array(arr) => {
+                let mut keys = HashMap::new();
+                let mut tuples = Vec::with_capacity(arr.len());
+
+                for (pos, row) in arr.iter().enumerate() {
+                    let attributes = self.heading.dress_attributes(row, options)
+                        .with_whatever_context(|_| format!("Relation contains invalid tuple at index {}", pos))?;
+                    // Coercion may dress distinct tuples alike, e.g. { id: "1" } and { id: 1 }
+                    let key = to_key(&canonical_json(&FinitioValue::Tuple(attributes.clone()))).unwrap();
+                    if keys.insert(key, row).is_some() {
+                        whatever!("Relation contains duplicated tuple: {}", row)
+                    }
+                    tuples.push(attributes);
+                }
+                if !self.keys.is_empty() {
+                    // Keys apply to the (possibly coerced) dressed values
+                    let rows: Vec<serde_json::Value> = tuples
+                        .iter()
+                        .map(|attributes| FinitioValue::Tuple(attributes.clone()).to_json())
+                        .collect();
+                    self.check_keys(&rows)?;
+                }
+                Ok(FinitioValue::Relation(tuples))
+            },
+            v => whatever!("Invalid value for Relation: {}", v)
+        }
+    }
+}
+
+impl TypeUndress<serde_json::Value> for Relation {
+    fn undress(&self, value: &FinitioValue) -> Result<serde_json::Value, Whatever> {
+        match value.unnamed() {
+            FinitioValue::Relation(tuples) => {
+                let mut rows = Vec::with_capacity(tuples.len());
+                for (pos, attributes) in tuples.iter().enumerate() {
+                    let obj = self.heading.undress_attributes(attributes)
+                        .with_whatever_context(|_| format!("Relation contains invalid tuple at index {}", pos))?;
+                    rows.push(serde_json::Value::Object(obj));
+                }
+                self.check_keys(&rows)?;
+                // Relations are emitted in a deterministic order
+                rows.sort_by_cached_key(|row| row.to_string());
+                if let Some(pair) = rows.windows(2).find(|pair| pair[0] == pair[1]) {
+                    whatever!("Relation contains duplicated tuple: {}", pair[0])
+                }
+                Ok(serde_json::Value::Array(rows))
+            },
+            value => whatever!("Not a relation: {:?}", value)
+        }
+    }
+}
+
+impl Relation {
+    /// Rejects tuples sharing the values of a key, compared once normalized so
+    /// that `1` and `1.0` agree. Tuples missing some of the (optional)
+    /// attributes of a key are not constrained by it.
+    fn check_keys(&self, rows: &[serde_json::Value]) -> Result<(), Whatever> {
+        for key in self.keys.iter() {
+            let mut seen = HashMap::new();
+            for (pos, row) in rows.iter().enumerate() {
+                let values: Option<Vec<&serde_json::Value>> = key.iter().map(|name| row.get(name)).collect();
+                let values = match values {
+                    Some(values) => values,
+                    None => continue,
+                };
+                let values = normalize_json(&serde_json::json!(values));
+                if let Some(first) = seen.insert(to_key(&values).unwrap(), pos) {
+                    whatever!(
+                        "Relation violates key ({}): tuples at index {} and {} share {}",
+                        key.join(", "),
+                        first,
+                        pos,
+                        values
+                    )
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 use crate::schema::{any::Any, heading::{Heading, Attribute}, builtin::Builtin, r#type::Type};
 
@@ -56,24 +141,29 @@ fn test_include_tuple() {
         ("name".to_string(), Attribute {
             name: String::from("name"),
             att_type: builtin_str,
-            optional: false
+            optional: false,
+            meta: None
         }),
         ("age".to_string(), Attribute {
             name: String::from("age"),
             att_type: builtin_num,
-            optional: false
+            optional: false,
+            meta: None
         }),
         ("extra".to_string(), Attribute {
             name: String::from("extra"),
             att_type: any_t,
-            optional: true
+            optional: true,
+            meta: None
         }),
     ]);
     let relation = Type::Relation(Relation {
         heading: Heading {
             attributes,
+            allow_extra: None,
             position: position.clone()
         },
+        keys: vec![],
         position: position.clone()
     });
 
@@ -108,3 +198,54 @@ fn test_include_tuple() {
     assert_eq!(relation.include(&valid).is_ok(), true, "obj with missing optional props is valid");
 
 }
+
+#[test]
+fn test_include_relation_keys() {
+    let source = "People = {{ id: .Number, first: .String, last: .String, email :? .String }} key(id) key(first, last) key(email)";
+    let schema = crate::schema::schema::test_schema(source);
+    let people = &schema.types["People"];
+
+    let valid = serde_json::json!([
+        { "id": 1, "first": "Al", "last": "Ba" },
+        { "id": 2, "first": "Al", "last": "Co" },
+        { "id": 3, "first": "Bo", "last": "Ba" },
+    ]);
+    assert!(people.include(&valid).is_ok());
+    assert!(people.dress(&valid).is_ok());
+
+    let same_id = serde_json::json!([
+        { "id": 1, "first": "Al", "last": "Ba" },
+        { "id": 2, "first": "Bo", "last": "Ba" },
+        { "id": 1, "first": "Cy", "last": "Ba" },
+    ]);
+    let err = people.include(&same_id).unwrap_err();
+    assert_eq!(err.to_string(), "Relation violates key (id): tuples at index 0 and 2 share [1]");
+    assert!(people.dress(&same_id).is_err());
+
+    // key values are compared once normalized
+    let same_number = serde_json::json!([
+        { "id": 1, "first": "Al", "last": "Ba" },
+        { "id": 1.0, "first": "Bo", "last": "Ba" },
+    ]);
+    let err = people.include(&same_number).unwrap_err();
+    assert_eq!(err.to_string(), "Relation violates key (id): tuples at index 0 and 1 share [1]");
+    assert!(people.dress(&same_number).is_err());
+
+    let same_name = serde_json::json!([
+        { "id": 1, "first": "Al", "last": "Ba", "email": "a@b.c" },
+        { "id": 2, "first": "Al", "last": "Ba" },
+    ]);
+    let err = people.include(&same_name).unwrap_err();
+    assert_eq!(err.to_string(), r#"Relation violates key (first, last): tuples at index 0 and 1 share ["Al","Ba"]"#);
+
+    // keys are only checked on the tuples having all their attributes
+    let same_email = serde_json::json!([
+        { "id": 1, "first": "Al", "last": "Ba", "email": "a@b.c" },
+        { "id": 2, "first": "Bo", "last": "Ba" },
+        { "id": 3, "first": "Cy", "last": "Ba" },
+        { "id": 4, "first": "Di", "last": "Ba", "email": "a@b.c" },
+    ]);
+    let err = people.include(&same_email).unwrap_err();
+    assert_eq!(err.to_string(), r#"Relation violates key (email): tuples at index 0 and 3 share ["a@b.c"]"#);
+}
+
@@ -1,6 +1,6 @@
 use snafu::{Whatever, whatever, ResultExt};
 
-use crate::schema::{TypeInclude, seq::Seq};
+use crate::schema::{TypeInclude, TypeDress, TypeUndress, FinitioValue, DressOptions, seq::Seq};
 
 impl TypeInclude<serde_json::Value> for Seq {
     fn include(&self, v: &serde_json::Value) -> Result<(), Whatever> {
@@ -26,6 +26,40 @@ impl TypeInclude<serde_json::Value> for Seq {
     }
 }
 
+impl TypeDress<serde_json::Value> for Seq {
+    fn dress_with(&self, v: &serde_json::Value, options: &DressOptions) -> Result<FinitioValue, Whatever> {
+        match v {
+            serde_json::Value::Array(a) => {
+                let mut values = Vec::with_capacity(a.len());
+                for (index, value) in a.iter().enumerate() {
+                    let value = self.elm_type.dress_with(value, options)
+                        .with_whatever_context(|_| format!("Seq contains invalid value at index {}", index))?;
+                    values.push(value);
+                }
+                Ok(FinitioValue::Seq(values))
+            },
+            v => whatever!("Not an array: {}", v),
+        }
+    }
+}
+
+impl TypeUndress<serde_json::Value> for Seq {
+    fn undress(&self, value: &FinitioValue) -> Result<serde_json::Value, Whatever> {
+        match value.unnamed() {
+            FinitioValue::Seq(values) => {
+                let mut a = Vec::with_capacity(values.len());
+                for (index, value) in values.iter().enumerate() {
+                    let v = self.elm_type.undress(value)
+                        .with_whatever_context(|_| format!("Seq contains invalid value at index {}", index))?;
+                    a.push(v);
+                }
+                Ok(serde_json::Value::Array(a))
+            },
+            value => whatever!("Not a seq: {:?}", value),
+        }
+    }
+}
+
 #[cfg(test)]
 use crate::schema::{any::Any, nil::Nil, r#type::Type};
 #[test]
@@ -1,7 +1,8 @@
 use std::collections::{HashMap};
-use serde_hashkey::{to_key};
+use serde_hashkey::{to_key_with_ordered_float as to_key};
 use snafu::{Whatever, whatever, ResultExt};
-use crate::schema::{TypeInclude, set::Set};
+use crate::schema::{TypeInclude, TypeDress, TypeUndress, FinitioValue, DressOptions, set::Set};
+use super::canonical_json;
 
 impl TypeInclude<serde_json::Value> for Set {
     fn include(&self, v: &serde_json::Value) -> Result<(), Whatever> {
@@ -29,6 +30,52 @@ impl TypeInclude<serde_json::Value> for Set {
     }
 }
 
+/// Duplicated elements are rejected, so that an array is dressed if and only
+/// if it is included in the set type
+impl TypeDress<serde_json::Value> for Set {
+    fn dress_with(&self, v: &serde_json::Value, options: &DressOptions) -> Result<FinitioValue, Whatever> {
+        match v {
+            serde_json::Value::Array(a) => {
+                let mut keys = HashMap::new();
+                let mut values = Vec::with_capacity(a.len());
+                for (pos, value) in a.iter().enumerate() {
+                    let dressed = self.elm_type.dress_with(value, options)
+                        .with_whatever_context(|_| format!("Set contains invalid value at index {}", pos))?;
+                    // Coercion may dress distinct values alike, e.g. "1" and 1
+                    if keys.insert(to_key(&canonical_json(&dressed)).unwrap(), value).is_some() {
+                        whatever!("Set contains duplicated value: {}", value)
+                    }
+                    values.push(dressed);
+                }
+                Ok(FinitioValue::Set(values))
+            },
+            v => whatever!("Not an array: {}", v),
+        }
+    }
+}
+
+impl TypeUndress<serde_json::Value> for Set {
+    fn undress(&self, value: &FinitioValue) -> Result<serde_json::Value, Whatever> {
+        match value.unnamed() {
+            FinitioValue::Set(values) => {
+                let mut a = Vec::with_capacity(values.len());
+                for (pos, value) in values.iter().enumerate() {
+                    let v = self.elm_type.undress(value)
+                        .with_whatever_context(|_| format!("Set contains invalid value at index {}", pos))?;
+                    a.push(v);
+                }
+                // Sets are emitted in a deterministic order
+                a.sort_by_cached_key(|v| v.to_string());
+                if let Some(pair) = a.windows(2).find(|pair| pair[0] == pair[1]) {
+                    whatever!("Set contains duplicated value: {}", pair[0])
+                }
+                Ok(serde_json::Value::Array(a))
+            },
+            value => whatever!("Not a set: {:?}", value),
+        }
+    }
+}
+
 #[cfg(test)]
 use crate::schema::{r#type::Type, builtin::Builtin};
 #[test]
@@ -1,6 +1,6 @@
 use snafu::{Whatever, whatever, ResultExt};
 
-use crate::schema::{TypeInclude, r#struct::Struct};
+use crate::schema::{TypeInclude, TypeDress, TypeUndress, FinitioValue, DressOptions, r#struct::Struct};
 
 impl TypeInclude<serde_json::Value> for Struct {
     fn include(&self, v: &serde_json::Value) -> Result<(), Whatever> {
@@ -30,6 +30,44 @@ impl TypeInclude<serde_json::Value> for Struct {
     }
 }
 
+impl TypeDress<serde_json::Value> for Struct {
+    fn dress_with(&self, v: &serde_json::Value, options: &DressOptions) -> Result<FinitioValue, Whatever> {
+        match v {
+            serde_json::Value::Array(arr) => {
+                if arr.len() != self.elements.len() {
+                    whatever!("Invalid struct length, expected {} found: {}", self.elements.len(), arr.len())
+                }
+                let values = arr.iter()
+                    .zip(self.elements.iter())
+                    .map(|(val, val_type)| val_type.dress_with(val, options))
+                    .collect::<Result<Vec<FinitioValue>, Whatever>>()
+                    .with_whatever_context(|_| format!("Invalid struct value: {}", v))?;
+                Ok(FinitioValue::Struct(values))
+            },
+            _ => whatever!("Invalid value for type Struct: {}", v)
+        }
+    }
+}
+
+impl TypeUndress<serde_json::Value> for Struct {
+    fn undress(&self, value: &FinitioValue) -> Result<serde_json::Value, Whatever> {
+        match value.unnamed() {
+            FinitioValue::Struct(values) => {
+                if values.len() != self.elements.len() {
+                    whatever!("Invalid struct length, expected {} found: {}", self.elements.len(), values.len())
+                }
+                let a = values.iter()
+                    .zip(self.elements.iter())
+                    .map(|(val, val_type)| val_type.undress(val))
+                    .collect::<Result<Vec<serde_json::Value>, Whatever>>()
+                    .with_whatever_context(|_| format!("Invalid struct value: {:?}", value))?;
+                Ok(serde_json::Value::Array(a))
+            },
+            value => whatever!("Invalid value for type Struct: {:?}", value)
+        }
+    }
+}
+
 #[cfg(test)]
 use crate::schema::{any::Any, builtin::Builtin, r#type::Type};
 
@@ -1,27 +1,73 @@
 use snafu::{Whatever, ResultExt, whatever};
-use crate::{schema::{TypeInclude, sub::{Sub}, constraint::ConstraintExecute}};
+use crate::{schema::{TypeInclude, TypeDress, TypeUndress, FinitioValue, DressOptions, Constraint, sub::{Sub}, constraint::ConstraintExecute}};
 
 impl TypeInclude<serde_json::Value> for Sub {
     fn include(&self, v: &serde_json::Value) -> Result<(), Whatever> {
         self.base_type.include(v)
             .with_whatever_context(|_| format!("Value rejected by base type: {}", v))?;
 
+        self.check_constraints(v)
+    }
+}
+
+impl TypeDress<serde_json::Value> for Sub {
+    fn dress_with(&self, v: &serde_json::Value, options: &DressOptions) -> Result<FinitioValue, Whatever> {
+        let value = self.base_type.dress_with(v, options)
+            .with_whatever_context(|_| format!("Value rejected by base type: {}", v))?;
+
+        // Constraints apply to the (possibly coerced) dressed value
+        self.check_constraints(&value.to_json())?;
+        Ok(value)
+    }
+}
+
+impl TypeUndress<serde_json::Value> for Sub {
+    fn undress(&self, value: &FinitioValue) -> Result<serde_json::Value, Whatever> {
+        let v = self.base_type.undress(value)
+            .with_whatever_context(|_| format!("Value rejected by base type: {:?}", value))?;
+
+        self.check_constraints(&v)?;
+        Ok(v)
+    }
+}
+
+impl Sub {
+    fn check_constraints(&self, v: &serde_json::Value) -> Result<(), Whatever> {
         for constraint in self.constraints.iter() {
             let valid = constraint
                 .execute(v)
-                .with_whatever_context(|_| format!("Unable to execute constraint: {}", constraint))?;
-
+                .with_whatever_context(|_| format!("Unable to execute constraint: {}{}", constraint, involved(constraint, v)))?;
             if !valid {
-                whatever!("Value rejected by constraint {}: {}", constraint, v);
+                whatever!("Value rejected by constraint {}{}: {}", constraint, involved(constraint, v), v);
             }
         }
-
         Ok(())
     }
 }
 
+/// Describes the attributes of a tuple a constraint accesses, absent optional
+/// ones evaluate to null
+fn involved(constraint: &Constraint, v: &serde_json::Value) -> String {
+    let obj = match v {
+        serde_json::Value::Object(obj) => obj,
+        _ => return String::new(),
+    };
+    let attributes: Vec<String> = constraint.attributes()
+        .iter()
+        .map(|name| match obj.get(name) {
+            Some(value) => format!("{}.{} = {}", constraint.param, name, value),
+            None => format!("{}.{} is absent", constraint.param, name),
+        })
+        .collect();
+    if attributes.is_empty() {
+        String::new()
+    } else {
+        format!(" ({})", attributes.join(", "))
+    }
+}
+
 #[cfg(test)]
-use crate::schema::{builtin::Builtin, r#type::Type, Constraint};
+use crate::schema::{builtin::Builtin, r#type::Type};
 
 #[test]
 fn test_include_sub() {
@@ -56,3 +102,40 @@ fn test_include_sub() {
     let valid_pos_int = serde_json::json!(-10);
     assert_eq!(sub.include(&valid_pos_int).is_ok(), false, "invalid negative int");
 }
+
+#[test]
+fn test_include_tuple_constraints() {
+    let source = "
+Person = {
+  name: .String
+  password :? .String
+  confirm :? .String
+}(p | p.password == p.confirm)
+Secure = Person(p | (!defined(p.password)) || len(p.password) > 3)
+Positive = { a: .Number, b :? .Number }(t | (!defined(t.b)) || t.b > 0)
+";
+    let schema = crate::schema::schema::test_schema(source);
+    let person = &schema.types["Person"];
+
+    assert!(person.include(&serde_json::json!({ "name": "Al" })).is_ok());
+    assert!(person.include(&serde_json::json!({ "name": "Al", "password": "abcd", "confirm": "abcd" })).is_ok());
+    assert!(person.dress(&serde_json::json!({ "name": "Al", "password": "abcd", "confirm": "abcd" })).is_ok());
+
+    let err = person.include(&serde_json::json!({ "name": "Al", "password": "abcd" })).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        r#"Value rejected by constraint Compiled(p,  p.password == p.confirm) (p.password = "abcd", p.confirm is absent): {"name":"Al","password":"abcd"}"#
+    );
+
+    let secure = &schema.types["Secure"];
+    assert!(secure.include(&serde_json::json!({ "name": "Al" })).is_ok());
+    assert!(secure.include(&serde_json::json!({ "name": "Al", "password": "abcd", "confirm": "abcd" })).is_ok());
+    let err = secure.include(&serde_json::json!({ "name": "Al", "password": "ab", "confirm": "ab" })).unwrap_err();
+    assert!(err.to_string().contains(r#"(p.password = "ab")"#), "{}", err);
+
+    // Guards are parenthesized, `!x || y` would read `!(x || y)`
+    let positive = &schema.types["Positive"];
+    assert!(positive.include(&serde_json::json!({ "a": 1 })).is_ok());
+    assert!(positive.include(&serde_json::json!({ "a": 1, "b": 2 })).is_ok());
+    assert!(positive.include(&serde_json::json!({ "a": 1, "b": -2 })).is_err());
+}
@@ -1,19 +1,101 @@
+use std::collections::HashSet;
+use serde_hashkey::{to_key_with_ordered_float as to_key};
 use snafu::{Whatever, whatever, ResultExt};
 
-use crate::schema::{TypeInclude, tuple::{Tuple}};
+use crate::schema::{TypeInclude, TypeDress, TypeUndress, FinitioValue, DressOptions, tuple::{Tuple, ForeignKey}};
+use super::normalize_json;
 
 impl TypeInclude<serde_json::Value> for Tuple {
     fn include(&self, v: &serde_json::Value) -> Result<(), Whatever> {
         match v {
-            serde_json::Value::Object(_obj) => {
+            serde_json::Value::Object(obj) => {
                 self.heading.include(v)
-                    .with_whatever_context(|_| format!("Invalid tuple: {}", v))
+                    .with_whatever_context(|_| format!("Invalid tuple: {}", v))?;
+                self.check_foreign_keys(obj)
+            },
+            v => whatever!("Invalid source type for Tuple: {}", v)
+        }
+    }
+}
+
+impl TypeDress<serde_json::Value> for Tuple {
+    fn dress_with(&self, v: &serde_json::Value, options: &DressOptions) -> Result<FinitioValue, Whatever> {
+        match v {
+            serde_json::Value::Object(_obj) => {
+                let attributes = self.heading.dress_attributes(v, options)
+                    .with_whatever_context(|_| format!("Invalid tuple: {}", v))?;
+                let value = FinitioValue::Tuple(attributes);
+                if !self.foreign_keys.is_empty() {
+                    // Foreign keys apply to the (possibly coerced) dressed values
+                    if let serde_json::Value::Object(obj) = value.to_json() {
+                        self.check_foreign_keys(&obj)?;
+                    }
+                }
+                Ok(value)
             },
             v => whatever!("Invalid source type for Tuple: {}", v)
         }
     }
 }
 
+impl TypeUndress<serde_json::Value> for Tuple {
+    fn undress(&self, value: &FinitioValue) -> Result<serde_json::Value, Whatever> {
+        match value.unnamed() {
+            FinitioValue::Tuple(attributes) => {
+                let obj = self.heading.undress_attributes(attributes)
+                    .with_whatever_context(|_| format!("Invalid tuple: {:?}", value))?;
+                self.check_foreign_keys(&obj)?;
+                Ok(serde_json::Value::Object(obj))
+            },
+            value => whatever!("Not a tuple: {:?}", value)
+        }
+    }
+}
+
+impl Tuple {
+    /// Rejects tuples of relation-valued attributes referencing no tuple of
+    /// the target relation. As for keys, source tuples missing some of the
+    /// (optional) attributes are not constrained, and an absent (optional)
+    /// relation-valued attribute holds no tuple.
+    fn check_foreign_keys(&self, obj: &serde_json::Map<String, serde_json::Value>) -> Result<(), Whatever> {
+        let rows = |fk: &ForeignKey, name: &str| -> Result<&[serde_json::Value], Whatever> {
+            match obj.get(name) {
+                Some(serde_json::Value::Array(rows)) => Ok(rows.as_slice()),
+                None if self.heading.attributes.contains_key(name) => Ok(&[]),
+                None => whatever!("No such attribute `{}` for {}", name, fk),
+                Some(v) => whatever!("Not a relation for {}: {}", fk, v),
+            }
+        };
+        for fk in self.foreign_keys.iter() {
+            let targets: HashSet<_> = rows(fk, &fk.target)?
+                .iter()
+                .filter_map(|row| project(row, &fk.target_attributes))
+                .map(|values| to_key(&values).unwrap())
+                .collect();
+            let dangling: Vec<String> = rows(fk, &fk.source)?
+                .iter()
+                .enumerate()
+                .filter(|(_, row)| match project(row, &fk.source_attributes) {
+                    Some(values) => !targets.contains(&to_key(&values).unwrap()),
+                    None => false,
+                })
+                .map(|(pos, row)| format!("{} at index {}", row, pos))
+                .collect();
+            if !dangling.is_empty() {
+                whatever!("Dangling tuples for {}: {}", fk, dangling.join(", "))
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The normalized values of some attributes of a tuple, so that `1` and `1.0`
+/// agree, None if it misses some of them
+fn project(row: &serde_json::Value, attributes: &[String]) -> Option<serde_json::Value> {
+    let values: Option<Vec<&serde_json::Value>> = attributes.iter().map(|name| row.get(name)).collect();
+    values.map(|values| normalize_json(&serde_json::json!(values)))
+}
+
 #[cfg(test)]
 use std::collections::HashMap;
 #[cfg(test)]
@@ -42,24 +124,29 @@ fn test_include_tuple() {
         ("name".to_string(), Attribute {
             name: String::from("name"),
             att_type: builtin_str,
-            optional: false
+            optional: false,
+            meta: None
         }),
         ("age".to_string(), Attribute {
             name: String::from("age"),
             att_type: builtin_num,
-            optional: false
+            optional: false,
+            meta: None
         }),
         ("extra".to_string(), Attribute {
             name: String::from("extra"),
             att_type: any_t,
-            optional: true
+            optional: true,
+            meta: None
         }),
     ]);
     let tuple = Type::Tuple(Tuple {
         heading: Heading {
             attributes,
+            allow_extra: None,
             position: position.clone()
         },
+        foreign_keys: vec![],
         position: position.clone()
     });
 
@@ -84,3 +171,78 @@ fn test_include_tuple() {
     assert_eq!(tuple.include(&valid).is_ok(), true, "obj with missing optional props is valid");
 
 }
+
+#[test]
+fn test_include_tuple_extras() {
+    use crate::common::FilePosition;
+
+    let position = FilePosition { line: 1, column: 1 };
+    let builtin_str = Type::Builtin(Builtin {
+        position: position.clone(),
+        target: String::from("String")
+    });
+
+    // Tuple { name: .String, ...: .String }
+    let attributes = HashMap::from([
+        ("name".to_string(), Attribute {
+            name: String::from("name"),
+            att_type: builtin_str.clone(),
+            optional: false,
+            meta: None
+        }),
+    ]);
+    let tuple = Type::Tuple(Tuple {
+        heading: Heading {
+            attributes,
+            allow_extra: Some(Box::new(builtin_str)),
+            position: position.clone()
+        },
+        foreign_keys: vec![],
+        position: position.clone()
+    });
+
+    let valid = serde_json::json!({ "name": "Foo", "nick": "Bar" });
+    assert!(tuple.include(&valid).is_ok(), "obj with valid extra props is valid");
+
+    let invalid = serde_json::json!({ "name": "Foo", "age": 22 });
+    assert!(tuple.include(&invalid).is_err(), "obj with invalid extra props is not valid");
+}
+
+#[test]
+fn test_include_tuple_foreign_keys() {
+    let source = "
+Customers = {{ id: .Number, name: .String }}
+Shop = {
+  customers: Customers
+  orders: {{ id: .Number, customer_id :? .Number }}
+} ref(orders(customer_id) -> customers(id))
+";
+    let schema = crate::schema::schema::test_schema(source);
+    let shop = &schema.types["Shop"];
+
+    let valid = serde_json::json!({
+        "customers": [{ "id": 1, "name": "Al" }, { "id": 2, "name": "Bo" }],
+        "orders": [{ "id": 10, "customer_id": 1 }, { "id": 11, "customer_id": 2 }, { "id": 12 }],
+    });
+    assert!(shop.include(&valid).is_ok());
+    assert!(shop.dress(&valid).is_ok());
+
+    let dangling = serde_json::json!({
+        "customers": [{ "id": 1, "name": "Al" }],
+        "orders": [{ "id": 10, "customer_id": 3 }, { "id": 11, "customer_id": 1 }, { "id": 12, "customer_id": 4 }],
+    });
+    let err = shop.include(&dangling).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        r#"Dangling tuples for orders(customer_id) -> customers(id): {"customer_id":3,"id":10} at index 0, {"customer_id":4,"id":12} at index 2"#
+    );
+    assert!(shop.dress(&dangling).is_err());
+
+    // references are compared once normalized
+    let normalized = serde_json::json!({
+        "customers": [{ "id": 1.0, "name": "Al" }],
+        "orders": [{ "id": 10, "customer_id": 1 }],
+    });
+    assert!(shop.include(&normalized).is_ok());
+    assert!(shop.dress(&normalized).is_ok());
+}
@@ -1,6 +1,6 @@
 use snafu::Whatever;
 
-use crate::schema::{TypeInclude, Type, TypeDef};
+use crate::schema::{TypeInclude, TypeDress, TypeUndress, FinitioValue, DressOptions, Type, TypeDef};
 
 impl TypeInclude<serde_json::Value> for Type {
   fn include(&self, v: &serde_json::Value) -> Result<(), Whatever> {
@@ -37,3 +37,227 @@ impl TypeInclude<serde_json::Value> for TypeDef {
     }
   }
 }
+
+impl TypeDress<serde_json::Value> for Type {
+  fn dress_with(&self, v: &serde_json::Value, options: &DressOptions) -> Result<FinitioValue, Whatever> {
+      match self {
+          Type::Any(t) => t.dress_with(v, options),
+          Type::Nil(t) => t.dress_with(v, options),
+          Type::Builtin(t) => t.dress_with(v, options),
+          Type::Ref(t) => t.dress_with(v, options),
+          Type::Seq(t) => t.dress_with(v, options),
+          Type::Set(t) => t.dress_with(v, options),
+          Type::Union(t) => t.dress_with(v, options),
+          Type::Struct(t) => t.dress_with(v, options),
+          Type::Sub(t) => t.dress_with(v, options),
+          Type::Tuple(t) => t.dress_with(v, options),
+          Type::Relation(t) => t.dress_with(v, options),
+      }
+  }
+}
+
+impl TypeDress<serde_json::Value> for TypeDef {
+  fn dress_with(&self, v: &serde_json::Value, options: &DressOptions) -> Result<FinitioValue, Whatever> {
+      let value = match self {
+        TypeDef::AnyType(t) => t.target.borrow().dress_with(v, options),
+        TypeDef::NilType(t) => t.target.borrow().dress_with(v, options),
+        TypeDef::BuiltinType(t) => t.target.borrow().dress_with(v, options),
+        TypeDef::RefType(t) => t.target.borrow().dress_with(v, options),
+        TypeDef::SeqType(t) => t.target.borrow().dress_with(v, options),
+        TypeDef::SetType(t) => t.target.borrow().dress_with(v, options),
+        TypeDef::UnionType(t) => t.target.borrow().dress_with(v, options),
+        TypeDef::StructType(t) => t.target.borrow().dress_with(v, options),
+        TypeDef::SubType(t) => t.target.borrow().dress_with(v, options),
+        TypeDef::TupleType(t) => t.target.borrow().dress_with(v, options),
+        TypeDef::RelationType(t) => t.target.borrow().dress_with(v, options),
+      }?;
+      Ok(FinitioValue::Named { name: self.name(), value: Box::new(value) })
+  }
+}
+
+impl TypeUndress<serde_json::Value> for Type {
+  fn undress(&self, value: &FinitioValue) -> Result<serde_json::Value, Whatever> {
+      match self {
+          Type::Any(t) => t.undress(value),
+          Type::Nil(t) => t.undress(value),
+          Type::Builtin(t) => t.undress(value),
+          Type::Ref(t) => t.undress(value),
+          Type::Seq(t) => t.undress(value),
+          Type::Set(t) => t.undress(value),
+          Type::Union(t) => t.undress(value),
+          Type::Struct(t) => t.undress(value),
+          Type::Sub(t) => t.undress(value),
+          Type::Tuple(t) => t.undress(value),
+          Type::Relation(t) => t.undress(value),
+      }
+  }
+}
+
+impl TypeUndress<serde_json::Value> for TypeDef {
+  fn undress(&self, value: &FinitioValue) -> Result<serde_json::Value, Whatever> {
+      match self {
+        TypeDef::AnyType(t) => t.target.borrow().undress(value),
+        TypeDef::NilType(t) => t.target.borrow().undress(value),
+        TypeDef::BuiltinType(t) => t.target.borrow().undress(value),
+        TypeDef::RefType(t) => t.target.borrow().undress(value),
+        TypeDef::SeqType(t) => t.target.borrow().undress(value),
+        TypeDef::SetType(t) => t.target.borrow().undress(value),
+        TypeDef::UnionType(t) => t.target.borrow().undress(value),
+        TypeDef::StructType(t) => t.target.borrow().undress(value),
+        TypeDef::SubType(t) => t.target.borrow().undress(value),
+        TypeDef::TupleType(t) => t.target.borrow().undress(value),
+        TypeDef::RelationType(t) => t.target.borrow().undress(value),
+    }
+  }
+}
+
+#[cfg(test)]
+use crate::schema::Attributes;
+
+#[test]
+fn test_dress_typedefs() {
+  let source = "
+Number = .Number
+String = .String
+PosNumber = Number(n | n > 0)
+Id = PosNumber | String
+Point = <Number, Number>
+Tags = {String}
+Person = { id: Id, name: String, tags :? Tags, ...: Number }
+People = {{ name: String }}
+Path = [Point]
+";
+  let schema = crate::schema::schema::test_schema(source);
+  let dress = |name: &str, v: serde_json::Value| schema.types[name].dress(&v);
+  let named = |name: &str, value: FinitioValue| FinitioValue::Named { name: name.to_string(), value: Box::new(value) };
+  let string = |s: &str| named("String", FinitioValue::String(s.to_string()));
+  let number = |n: i64| named("Number", FinitioValue::Number(n.into()));
+
+  // the union candidate that matched is kept, along with the type names
+  let id = dress("Id", serde_json::json!(12)).unwrap();
+  assert_eq!(id, named("Id", FinitioValue::Union {
+    candidate: 0,
+    value: Box::new(named("PosNumber", number(12))),
+  }));
+  assert_eq!(id.type_name(), Some("Id"));
+  assert_eq!(id.untagged(), &FinitioValue::Number(12.into()));
+
+  let person = dress("Person", serde_json::json!({ "id": "foo", "name": "Foo", "age": 42 })).unwrap();
+  assert_eq!(person, named("Person", FinitioValue::Tuple(Attributes::from([
+    ("id".to_string(), named("Id", FinitioValue::Union { candidate: 1, value: Box::new(string("foo")) })),
+    ("name".to_string(), string("Foo")),
+    ("age".to_string(), number(42)),
+  ]))));
+
+  let path = dress("Path", serde_json::json!([[1, 2]])).unwrap();
+  assert_eq!(path, named("Path", FinitioValue::Seq(vec![
+    named("Point", FinitioValue::Struct(vec![number(1), number(2)])),
+  ])));
+
+  let people = dress("People", serde_json::json!([{ "name": "Foo" }, { "name": "Bar" }])).unwrap();
+  assert_eq!(people, named("People", FinitioValue::Relation(vec![
+    Attributes::from([("name".to_string(), string("Foo"))]),
+    Attributes::from([("name".to_string(), string("Bar"))]),
+  ])));
+
+  // dressing rejects what inclusion rejects
+  assert!(dress("Id", serde_json::json!(-1)).is_err());
+  assert!(dress("Person", serde_json::json!({ "id": 1, "name": "Foo", "age": "42" })).is_err());
+  assert!(dress("Tags", serde_json::json!(["a", "a"])).is_err());
+  assert!(dress("People", serde_json::json!([{ "name": "Foo" }, { "name": "Foo" }])).is_err());
+}
+
+#[test]
+fn test_dress_lenient() {
+  let source = "
+PosNumber = .Number(n | n > 0)
+Settings = { port: PosNumber, debug: .Boolean, proxy: Nil | .String, since :? .Date }
+Ports = {.Number}
+Hosts = {{ id: .Number }}
+";
+  let schema = crate::schema::schema::test_schema(source);
+  let settings = &schema.types["Settings"];
+  let data = serde_json::json!({ "port": "8080", "debug": "true", "proxy": "", "since": "2023-01-31" });
+
+  assert!(settings.dress(&data).is_err(), "strings are not coerced in strict mode");
+
+  let dressed = settings.dress_with(&data, &DressOptions::lenient()).unwrap();
+  assert_eq!(dressed.to_json(), serde_json::json!({ "port": 8080, "debug": true, "proxy": null, "since": "2023-01-31" }));
+
+  // constraints are checked against the coerced value
+  let data = serde_json::json!({ "port": "-1", "debug": "true", "proxy": "" });
+  assert!(settings.dress_with(&data, &DressOptions::lenient()).is_err());
+
+  // which strings stand for Nil is configurable
+  let data = serde_json::json!({ "port": "80", "debug": "false", "proxy": "" });
+  let options = DressOptions { nil_strings: vec![String::from("-")], ..DressOptions::lenient() };
+  let dressed = settings.dress_with(&data, &options).unwrap();
+  assert_eq!(dressed.to_json()["proxy"], serde_json::json!(""));
+
+  // duplicates are detected on the coerced values
+  let ports = &schema.types["Ports"];
+  assert!(ports.dress_with(&serde_json::json!(["1", 2]), &DressOptions::lenient()).is_ok());
+  let err = ports.dress_with(&serde_json::json!(["1", 1, "1.0"]), &DressOptions::lenient()).unwrap_err();
+  assert_eq!(err.to_string(), "Set contains duplicated value: 1");
+  let hosts = &schema.types["Hosts"];
+  let err = hosts.dress_with(&serde_json::json!([{ "id": "1" }, { "id": 1 }]), &DressOptions::lenient()).unwrap_err();
+  assert_eq!(err.to_string(), r#"Relation contains duplicated tuple: {"id":1}"#);
+}
+
+#[test]
+fn test_undress_round_trip() {
+  let source = "
+PosNumber = .Number(n | n > 0)
+Event = {
+  at: .DateTime
+  tags: {.String}
+  where: <.Number, .Number>
+  score: PosNumber | .String
+  note :? .String
+}
+Events = {{ at: .DateTime, score: PosNumber }}
+";
+  let schema = crate::schema::schema::test_schema(source);
+  let event = &schema.types["Event"];
+  let data = serde_json::json!({
+    "at": "2023-01-31T10:00:00",
+    "tags": ["b", "a", "c"],
+    "where": [1, 2],
+    "score": "7",
+  });
+
+  let dressed = event.dress_with(&data, &DressOptions::lenient()).unwrap();
+  let undressed = event.undress(&dressed).unwrap();
+  assert_eq!(undressed, serde_json::json!({
+    "at": "2023-01-31T10:00:00Z",
+    "tags": ["a", "b", "c"],
+    "where": [1, 2],
+    "score": 7,
+  }));
+  assert!(event.include(&undressed).is_ok());
+
+  let events = &schema.types["Events"];
+  let data = serde_json::json!([
+    { "at": "2023-02-01T00:00:00+01:00", "score": 2 },
+    { "at": "2023-01-01T00:00:00Z", "score": 1 },
+  ]);
+  let undressed = events.undress(&events.dress(&data).unwrap()).unwrap();
+  assert_eq!(undressed[0]["score"], serde_json::json!(1));
+  assert_eq!(undressed, events.undress(&events.dress(&undressed).unwrap()).unwrap());
+
+  // values built by hand are checked against the type
+  let posnumber = &schema.types["PosNumber"];
+  assert_eq!(posnumber.undress(&FinitioValue::Number(3.into())).unwrap(), serde_json::json!(3));
+  assert!(posnumber.undress(&FinitioValue::Number((-3).into())).is_err());
+  let missing = FinitioValue::Tuple(Attributes::from([("note".to_string(), FinitioValue::String("x".into()))]));
+  assert!(event.undress(&missing).is_err());
+  let duplicates = FinitioValue::Set(vec![FinitioValue::String("a".into()), FinitioValue::String("a".into())]);
+  let tags = Type::Set(crate::schema::set::Set {
+    elm_type: Box::new(Type::Builtin(crate::schema::builtin::Builtin {
+      target: "String".to_string(),
+      position: crate::common::FilePosition { line: 1, column: 1 },
+    })),
+    position: crate::common::FilePosition { line: 1, column: 1 },
+  });
+  assert!(tags.undress(&duplicates).is_err());
+}
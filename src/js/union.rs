@@ -1,14 +1,11 @@
 use snafu::{Whatever, whatever};
 
-use crate::schema::{TypeInclude, union::Union};
+use crate::schema::{TypeInclude, TypeDress, TypeUndress, FinitioValue, DressOptions, union::Union};
 
 impl TypeInclude<serde_json::Value> for Union {
     fn include(&self, v: &serde_json::Value) -> Result<(), Whatever> {
         let found = self.candidates.iter().find(|x| {
-            match x.include(v) {
-                Ok(_) => true,
-                Err(_) => false,
-            }
+            x.include(v).is_ok()
         });
         match found {
             Some(_) => Ok(()),
@@ -17,6 +14,35 @@ impl TypeInclude<serde_json::Value> for Union {
     }
 }
 
+impl TypeDress<serde_json::Value> for Union {
+    fn dress_with(&self, v: &serde_json::Value, options: &DressOptions) -> Result<FinitioValue, Whatever> {
+        // The first candidate accepting the value wins
+        for (candidate, t) in self.candidates.iter().enumerate() {
+            if let Ok(value) = t.dress_with(v, options) {
+                return Ok(FinitioValue::Union { candidate, value: Box::new(value) });
+            }
+        }
+        whatever!("Value rejected by all types of the Union: {}", v)
+    }
+}
+
+impl TypeUndress<serde_json::Value> for Union {
+    fn undress(&self, value: &FinitioValue) -> Result<serde_json::Value, Whatever> {
+        if let FinitioValue::Union { candidate, value } = value.unnamed() {
+            if let Some(t) = self.candidates.get(*candidate) {
+                return t.undress(value);
+            }
+        }
+        // Values built by hand may not tell their candidate
+        for t in self.candidates.iter() {
+            if let Ok(v) = t.undress(value) {
+                return Ok(v);
+            }
+        }
+        whatever!("Value rejected by all types of the Union: {:?}", value)
+    }
+}
+
 #[cfg(test)]
 use crate::schema::{builtin::Builtin, r#type::Type};
 #[test]
@@ -58,3 +84,23 @@ fn test_include_union() {
     assert_eq!(union_t.include(&string).is_ok(), true, "'string' is a valid .Number|.String");
 
 }
+
+#[test]
+fn test_dress_union() {
+    use crate::common::FilePosition;
+
+    let position = FilePosition { line: 2, column: 2};
+    let union_t = Union {
+        position: position.clone(),
+        candidates: vec![
+            Type::Builtin(Builtin { position: position.clone(), target: String::from("String") }),
+            Type::Builtin(Builtin { position: position.clone(), target: String::from("Number") }),
+        ]
+    };
+
+    let dressed = union_t.dress(&serde_json::json!(12)).unwrap();
+    assert_eq!(dressed, FinitioValue::Union { candidate: 1, value: Box::new(FinitioValue::Number(12.into())) });
+
+    assert!(union_t.dress(&serde_json::Value::Null).is_err(), "null is not a valid .Number|.String");
+}
+
@@ -0,0 +1,441 @@
+use std::fmt;
+
+use serde_json::{json, Map, Value};
+use snafu::{Whatever, whatever};
+
+use crate::schema::{
+    heading::Heading, r#struct::Struct, relation::Relation, sub::Sub, tuple::Tuple, Constraint, Schema, Type,
+    TypeDef,
+};
+
+use super::DRAFT;
+
+/// Maximum number of references followed to find what a type is made of
+const MAX_DEPTH: usize = 64;
+
+/// A JSON Schema document, and what it could not express
+#[derive(Clone, Debug)]
+pub struct Export {
+    pub schema: Value,
+    pub untranslated: Vec<Untranslated>,
+}
+
+/// A constraint of a finitio type that JSON Schema cannot express, the
+/// exported schema accepts more values than the finitio type
+#[derive(Clone, Debug, PartialEq)]
+pub struct Untranslated {
+    /// The name of the type definition
+    pub type_name: String,
+    /// Where the constraint is within the type definition: attribute names,
+    /// `*` for the elements of sequences and sets, indexes for struct
+    /// components
+    pub path: Vec<String>,
+    pub constraint: String,
+}
+
+impl Untranslated {
+    pub fn location(&self) -> String {
+        let mut location = vec![self.type_name.clone()];
+        location.extend(self.path.iter().cloned());
+        location.join("/")
+    }
+}
+
+impl fmt::Display for Untranslated {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: `{}` cannot be expressed in JSON Schema", self.location(), self.constraint)
+    }
+}
+
+/// Exports all the type definitions of a schema under `$defs`, the document
+/// itself validates `root` when given
+pub fn export(schema: &Schema, root: Option<&str>) -> Result<Export, Whatever> {
+    let mut untranslated = vec![];
+    let mut defs = Map::new();
+    for (name, typedef) in schema.types.iter() {
+        let mut exporter = Exporter { type_name: name, untranslated: &mut untranslated };
+        let mut def = exporter.typedef(typedef);
+        if let Some(meta) = typedef.meta() {
+            describe(&mut def, meta);
+        }
+        defs.insert(name.clone(), def);
+    }
+
+    let mut document = Map::new();
+    document.insert(String::from("$schema"), json!(DRAFT));
+    if let Some(root) = root {
+        if !schema.types.contains_key(root) {
+            whatever!("No such type `{}`", root)
+        }
+        document.insert(String::from("$ref"), json!(format!("#/$defs/{}", root)));
+    }
+    document.insert(String::from("$defs"), Value::Object(defs));
+    Ok(Export { schema: Value::Object(document), untranslated })
+}
+
+fn reference(name: &str) -> Value {
+    json!({ "$ref": format!("#/$defs/{}", name) })
+}
+
+fn describe(schema: &mut Value, meta: &str) {
+    if let Value::Object(obj) = schema {
+        obj.insert(String::from("description"), json!(meta.trim()));
+    }
+}
+
+fn within(path: &[String], segment: &str) -> Vec<String> {
+    let mut path = path.to_vec();
+    path.push(segment.to_string());
+    path
+}
+
+struct Exporter<'a> {
+    type_name: &'a str,
+    untranslated: &'a mut Vec<Untranslated>,
+}
+
+impl<'a> Exporter<'a> {
+    fn report(&mut self, path: &[String], constraint: String) {
+        self.untranslated.push(Untranslated {
+            type_name: self.type_name.to_string(),
+            path: path.to_vec(),
+            constraint,
+        });
+    }
+
+    fn typedef(&mut self, typedef: &TypeDef) -> Value {
+        match typedef {
+            TypeDef::AnyType(_) => json!({}),
+            TypeDef::NilType(_) => json!({ "type": "null" }),
+            TypeDef::BuiltinType(t) => self.builtin(&[], &t.target.borrow().target),
+            TypeDef::RefType(t) => self.r#type(&[], &t.target.borrow().target),
+            TypeDef::SeqType(t) => {
+                json!({ "type": "array", "items": self.r#type(&within(&[], "*"), &t.target.borrow().elm_type) })
+            },
+            TypeDef::SetType(t) => json!({
+                "type": "array",
+                "items": self.r#type(&within(&[], "*"), &t.target.borrow().elm_type),
+                "uniqueItems": true,
+            }),
+            TypeDef::UnionType(t) => self.union(&[], &t.target.borrow().candidates),
+            TypeDef::StructType(t) => self.r#struct(&[], &t.target.borrow()),
+            TypeDef::SubType(t) => self.sub(&[], &t.target.borrow()),
+            TypeDef::TupleType(t) => self.tuple(&[], &t.target.borrow()),
+            TypeDef::RelationType(t) => self.relation(&[], &t.target.borrow()),
+        }
+    }
+
+    fn r#type(&mut self, path: &[String], t: &Type) -> Value {
+        match t {
+            Type::Any(_) => json!({}),
+            Type::Nil(_) => json!({ "type": "null" }),
+            Type::Builtin(b) => self.builtin(path, &b.target),
+            Type::Ref(r) => reference(r.name()),
+            Type::Seq(s) => json!({ "type": "array", "items": self.r#type(&within(path, "*"), &s.elm_type) }),
+            Type::Set(s) => json!({
+                "type": "array",
+                "items": self.r#type(&within(path, "*"), &s.elm_type),
+                "uniqueItems": true,
+            }),
+            Type::Union(u) => self.union(path, &u.candidates),
+            Type::Struct(s) => self.r#struct(path, s),
+            Type::Sub(s) => self.sub(path, s),
+            Type::Tuple(t) => self.tuple(path, t),
+            Type::Relation(r) => self.relation(path, r),
+        }
+    }
+
+    fn builtin(&mut self, path: &[String], target: &str) -> Value {
+        match target {
+            "Number" => json!({ "type": "number" }),
+            "String" => json!({ "type": "string" }),
+            "Boolean" => json!({ "type": "boolean" }),
+            "Date" => json!({ "type": "string", "format": "date" }),
+            "DateTime" => json!({ "type": "string", "format": "date-time" }),
+            target => {
+                self.report(path, format!(".{}", target));
+                json!({})
+            },
+        }
+    }
+
+    fn union(&mut self, path: &[String], candidates: &[Type]) -> Value {
+        let candidates: Vec<Value> = candidates.iter().map(|c| self.r#type(path, c)).collect();
+        json!({ "anyOf": candidates })
+    }
+
+    fn r#struct(&mut self, path: &[String], s: &Struct) -> Value {
+        let items: Vec<Value> = s.elements
+            .iter()
+            .enumerate()
+            .map(|(index, elm)| self.r#type(&within(path, &index.to_string()), elm))
+            .collect();
+        json!({
+            "type": "array",
+            "prefixItems": items,
+            "items": false,
+            "minItems": s.elements.len(),
+        })
+    }
+
+    fn heading(&mut self, path: &[String], heading: &Heading) -> Value {
+        let mut properties = Map::new();
+        let mut required = vec![];
+        let mut names: Vec<&String> = heading.attributes.keys().collect();
+        names.sort();
+        for name in names {
+            let attribute = &heading.attributes[name];
+            let mut property = self.r#type(&within(path, name), &attribute.att_type);
+            if let Some(meta) = &attribute.meta {
+                describe(&mut property, meta);
+            }
+            properties.insert(name.clone(), property);
+            if !attribute.optional {
+                required.push(name.clone());
+            }
+        }
+        let additional = match &heading.allow_extra {
+            Some(extra) => self.r#type(&within(path, "..."), extra),
+            None => json!(false),
+        };
+        json!({
+            "type": "object",
+            "properties": properties,
+            "required": required,
+            "additionalProperties": additional,
+        })
+    }
+
+    fn tuple(&mut self, path: &[String], tuple: &Tuple) -> Value {
+        for fk in tuple.foreign_keys.iter() {
+            let constraint = format!(
+                "ref({}({}) -> {}({}))",
+                fk.source,
+                fk.source_attributes.join(", "),
+                fk.target,
+                fk.target_attributes.join(", ")
+            );
+            self.report(path, constraint);
+        }
+        self.heading(path, &tuple.heading)
+    }
+
+    fn relation(&mut self, path: &[String], relation: &Relation) -> Value {
+        for key in relation.keys.iter() {
+            self.report(path, format!("key({})", key.join(", ")));
+        }
+        json!({
+            "type": "array",
+            "items": self.heading(path, &relation.heading),
+            "uniqueItems": true,
+        })
+    }
+
+    fn sub(&mut self, path: &[String], sub: &Sub) -> Value {
+        let mut schema = self.r#type(path, &sub.base_type);
+        let kind = kind(&sub.base_type, 0);
+        for constraint in sub.constraints.iter() {
+            match translate(constraint, kind) {
+                Some(keywords) => schema = merge(schema, keywords),
+                None => self.report(path, format!("{} | {}", constraint.param, constraint.expr.trim())),
+            }
+        }
+        schema
+    }
+}
+
+/// Adds keywords to a schema, through `allOf` when they clash with its own
+fn merge(schema: Value, keywords: Map<String, Value>) -> Value {
+    match schema {
+        Value::Object(mut obj) if keywords.keys().all(|k| !obj.contains_key(k)) => {
+            obj.extend(keywords);
+            Value::Object(obj)
+        },
+        schema => json!({ "allOf": [schema, Value::Object(keywords)] }),
+    }
+}
+
+/// What the values of a type are made of, as far as constraints are concerned
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Kind {
+    Number,
+    String,
+    Array,
+}
+
+fn kind(t: &Type, depth: usize) -> Option<Kind> {
+    if depth > MAX_DEPTH {
+        return None;
+    }
+    match t {
+        Type::Builtin(b) if b.target == "Number" => Some(Kind::Number),
+        Type::Builtin(b) if b.target == "String" => Some(Kind::String),
+        Type::Seq(_) | Type::Set(_) | Type::Struct(_) | Type::Relation(_) => Some(Kind::Array),
+        Type::Sub(s) => kind(&s.base_type, depth + 1),
+        Type::Ref(r) => kind(&r.target()?, depth + 1),
+        _ => None,
+    }
+}
+
+/// Translates constraints made of comparisons of the value (or its length)
+/// with literals, joined by `&&`
+fn translate(constraint: &Constraint, kind: Option<Kind>) -> Option<Map<String, Value>> {
+    let mut keywords = Map::new();
+    for part in constraint.expr.split("&&") {
+        for (keyword, value) in comparison(part.trim(), &constraint.param, kind)? {
+            if keywords.insert(keyword.to_string(), value).is_some() {
+                return None;
+            }
+        }
+    }
+    Some(keywords)
+}
+
+fn comparison(expr: &str, param: &str, kind: Option<Kind>) -> Option<Vec<(&'static str, Value)>> {
+    if expr.contains("||") || expr.contains('!') && !expr.contains("!=") {
+        return None;
+    }
+    let (lhs, op, rhs) = ["<=", ">=", "==", "<", ">"]
+        .iter()
+        .find_map(|op| expr.split_once(op).map(|(lhs, rhs)| (lhs.trim(), *op, rhs.trim())))?;
+    // Literals on the left are flipped to the right
+    let (subject, op, literal) = if lhs == param || lhs == format!("len({})", param) {
+        (lhs, op, rhs)
+    } else {
+        let flipped = match op {
+            "<=" => ">=",
+            ">=" => "<=",
+            "<" => ">",
+            ">" => "<",
+            op => op,
+        };
+        (rhs, flipped, lhs)
+    };
+
+    if subject == param {
+        if let (Some(Kind::String), "==") = (kind, op) {
+            let s: String = serde_json::from_str(literal).ok()?;
+            return Some(vec![("const", json!(s))]);
+        }
+        let n: serde_json::Number = literal.parse().ok()?;
+        if kind != Some(Kind::Number) {
+            return None;
+        }
+        let keyword = match op {
+            "<=" => "maximum",
+            ">=" => "minimum",
+            "<" => "exclusiveMaximum",
+            ">" => "exclusiveMinimum",
+            _ => "const",
+        };
+        return Some(vec![(keyword, Value::Number(n))]);
+    }
+
+    if subject == format!("len({})", param) {
+        let n: u64 = literal.parse().ok()?;
+        let (min, max) = match kind? {
+            Kind::String => ("minLength", "maxLength"),
+            Kind::Array => ("minItems", "maxItems"),
+            Kind::Number => return None,
+        };
+        return match op {
+            "<=" => Some(vec![(max, json!(n))]),
+            ">=" => Some(vec![(min, json!(n))]),
+            "<" if n > 0 => Some(vec![(max, json!(n - 1))]),
+            ">" => Some(vec![(min, json!(n + 1))]),
+            "==" => Some(vec![(min, json!(n)), (max, json!(n))]),
+            _ => None,
+        };
+    }
+    None
+}
+
+#[cfg(test)]
+fn export_source(source: &str, root: Option<&str>) -> Export {
+    let schema = crate::schema::schema::test_schema(source);
+    export(&schema, root).unwrap()
+}
+
+#[test]
+fn test_export_scalars() {
+    let export = export_source("
+/- A positive number -/
+PosNumber = .Number(n | n > 0 && n <= 100)
+Name = .String(s | len(s) >= 1 && len(s) < 33)
+Born = .Date
+Maybe = PosNumber | Nil
+Tags = {.String}(t | len(t) > 0)
+Pair = <.Number, .String>
+Even = .Number(n | n % 2 == 0)
+", Some("Maybe"));
+
+    assert_eq!(export.schema, json!({
+        "$schema": DRAFT,
+        "$ref": "#/$defs/Maybe",
+        "$defs": {
+            "PosNumber": {
+                "type": "number",
+                "exclusiveMinimum": 0,
+                "maximum": 100,
+                "description": "A positive number",
+            },
+            "Name": { "type": "string", "minLength": 1, "maxLength": 32 },
+            "Born": { "type": "string", "format": "date" },
+            "Maybe": { "anyOf": [{ "$ref": "#/$defs/PosNumber" }, { "type": "null" }] },
+            "Tags": { "type": "array", "items": { "type": "string" }, "uniqueItems": true, "minItems": 1 },
+            "Pair": {
+                "type": "array",
+                "prefixItems": [{ "type": "number" }, { "type": "string" }],
+                "items": false,
+                "minItems": 2,
+            },
+            "Even": { "type": "number" },
+        },
+    }));
+    assert_eq!(
+        export.untranslated.iter().map(Untranslated::to_string).collect::<Vec<_>>(),
+        vec!["Even: `n | n % 2 == 0` cannot be expressed in JSON Schema"]
+    );
+}
+
+#[test]
+fn test_export_headings() {
+    let export = export_source("
+String = .String
+Person = {
+  /- The full name -/
+  name: String
+  nick :? String
+  ...: .Number
+}
+People = {{ id: .Number, name: String }} key(id)
+Shop = { people: People, buyers: {{ id: .Number }} } ref(buyers(id) -> people(id))
+", None);
+
+    assert_eq!(export.schema["$defs"]["Person"], json!({
+        "type": "object",
+        "properties": {
+            "name": { "$ref": "#/$defs/String", "description": "The full name" },
+            "nick": { "$ref": "#/$defs/String" },
+        },
+        "required": ["name"],
+        "additionalProperties": { "type": "number" },
+    }));
+    assert_eq!(export.schema["$defs"]["People"], json!({
+        "type": "array",
+        "items": {
+            "type": "object",
+            "properties": { "id": { "type": "number" }, "name": { "$ref": "#/$defs/String" } },
+            "required": ["id", "name"],
+            "additionalProperties": false,
+        },
+        "uniqueItems": true,
+    }));
+    assert_eq!(
+        export.untranslated.iter().map(Untranslated::to_string).collect::<Vec<_>>(),
+        vec![
+            "People: `key(id)` cannot be expressed in JSON Schema",
+            "Shop: `ref(buyers(id) -> people(id))` cannot be expressed in JSON Schema",
+        ]
+    );
+}
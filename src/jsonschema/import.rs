@@ -0,0 +1,599 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use serde_json::{Map, Value};
+
+use crate::common::FilePosition;
+use crate::fio;
+use crate::fmt::format_schema;
+
+/// Keywords that only document a schema, imported as meta or ignored
+const ANNOTATIONS: [&str; 11] = [
+    "$schema", "$id", "$comment", "$anchor", "title", "description", "examples", "default", "readOnly",
+    "writeOnly", "deprecated",
+];
+
+/// A finitio schema imported from a JSON Schema document, and what could not
+/// be imported
+#[derive(Clone, Debug)]
+pub struct Imported {
+    pub schema: fio::Schema,
+    pub warnings: Vec<Warning>,
+}
+
+impl Imported {
+    /// The imported schema as `.fio` source
+    pub fn to_fio(&self) -> String {
+        format_schema(&self.schema)
+    }
+}
+
+/// A part of a JSON Schema document that has no finitio equivalent, the
+/// imported schema may accept more values than the document
+#[derive(Clone, Debug, PartialEq)]
+pub struct Warning {
+    /// The JSON pointer of the offending (sub) schema
+    pub pointer: String,
+    pub message: String,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}: {}", self.pointer, self.message)
+    }
+}
+
+/// Imports the `$defs` (or `definitions`) of a document as type definitions,
+/// in alphabetical order, and the document itself as `root_name` unless it
+/// only holds definitions
+pub fn import(document: &Value, root_name: &str) -> Imported {
+    let mut importer = Importer::default();
+
+    let mut defs = vec![];
+    for keyword in ["$defs", "definitions"] {
+        if let Some(Value::Object(entries)) = document.get(keyword) {
+            for (key, schema) in entries.iter() {
+                let name = importer.unique_name(&sanitize(key));
+                importer.def_names.insert(format!("#/{}/{}", keyword, key), name.clone());
+                defs.push((format!("/{}/{}", keyword, escape(key)), name, schema));
+            }
+        }
+    }
+    for (pointer, name, schema) in defs {
+        importer.define(&name, schema, &pointer);
+    }
+
+    let is_root = |keyword: &String| {
+        !ANNOTATIONS.contains(&keyword.as_str()) && keyword != "$defs" && keyword != "definitions"
+    };
+    match document {
+        Value::Object(obj) if !obj.keys().any(is_root) => {},
+        document => {
+            let name = importer.unique_name(root_name);
+            importer.define(&name, document, "");
+        },
+    }
+
+    Imported {
+        schema: fio::Schema { imports: vec![], type_defs: importer.typedefs },
+        warnings: importer.warnings,
+    }
+}
+
+/// Generated types are not found in any source file
+fn nowhere() -> FilePosition {
+    FilePosition { line: 0, column: 0 }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
+/// A type name made of the valid identifier characters of `name`
+fn sanitize(name: &str) -> String {
+    let mut sanitized: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' })
+        .collect();
+    if !sanitized.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        sanitized.insert(0, 'T');
+    }
+    sanitized
+}
+
+fn capitalize(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) => c.to_ascii_uppercase().to_string() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+/// Escapes a JSON pointer segment
+fn escape(segment: &str) -> String {
+    segment.replace('~', "~0").replace('/', "~1")
+}
+
+fn builtin(name: &str) -> fio::Type {
+    fio::Type::BuiltinType(fio::BuiltinType { name: name.to_string(), position: nowhere() })
+}
+
+fn any() -> fio::Type {
+    fio::Type::AnyType(fio::AnyType { position: nowhere() })
+}
+
+/// A literal of the constraint expression language
+fn literal(value: &Value) -> Option<String> {
+    match value {
+        Value::Null | Value::Bool(_) | Value::Number(_) => Some(value.to_string()),
+        Value::String(s) if !s.contains(['"', '\\']) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+#[derive(Default)]
+struct Importer {
+    typedefs: Vec<fio::TypeDef>,
+    names: HashSet<String>,
+    /// Type names by the `$ref` of their definition
+    def_names: HashMap<String, String>,
+    warnings: Vec<Warning>,
+}
+
+impl Importer {
+    fn warn(&mut self, pointer: &str, message: String) {
+        self.warnings.push(Warning { pointer: pointer.to_string(), message });
+    }
+
+    fn unique_name(&mut self, name: &str) -> String {
+        let mut unique = name.to_string();
+        let mut index = 1;
+        while unique == "Nil" || self.names.contains(&unique) {
+            index += 1;
+            unique = format!("{}{}", name, index);
+        }
+        self.names.insert(unique.clone());
+        unique
+    }
+
+    fn define(&mut self, name: &str, schema: &Value, pointer: &str) {
+        let target = self.r#type(schema, pointer, name);
+        let meta = schema.get("description").and_then(Value::as_str).map(|d| format!(" {} ", d.trim()));
+        self.typedefs.push(fio::TypeDef { meta, name: name.to_string(), target, position: nowhere() });
+    }
+
+    /// Moves a type to its own definition, for the places where the finitio
+    /// syntax does not allow it inline
+    fn hoist(&mut self, t: fio::Type, hint: &str) -> fio::Type {
+        let name = self.unique_name(hint);
+        self.typedefs.push(fio::TypeDef { meta: None, name: name.clone(), target: t, position: nowhere() });
+        fio::Type::RefType(fio::RefType { name, position: nowhere() })
+    }
+
+    fn r#type(&mut self, schema: &Value, pointer: &str, hint: &str) -> fio::Type {
+        let obj = match schema {
+            Value::Bool(true) => return any(),
+            Value::Object(obj) => obj,
+            schema => {
+                self.warn(pointer, format!("unsupported schema `{}`, imported as `.`", schema));
+                return any();
+            },
+        };
+
+        let mut handled: HashSet<&str> = ANNOTATIONS.iter().copied().collect();
+        handled.extend(["$defs", "definitions"]);
+        let t = if let Some(reference) = obj.get("$ref") {
+            handled.insert("$ref");
+            self.reference(reference, pointer)
+        } else if let Some(values) = obj.get("enum") {
+            handled.insert("enum");
+            self.enumeration(values, pointer)
+        } else if let Some(value) = obj.get("const") {
+            handled.insert("const");
+            self.enumeration(&Value::Array(vec![value.clone()]), pointer)
+        } else if let Some(keyword) = ["anyOf", "oneOf"].iter().copied().find(|k| obj.contains_key(*k)) {
+            handled.insert(keyword);
+            if keyword == "oneOf" {
+                self.warn(pointer, String::from("`oneOf` imported as a union, exclusivity is not checked"));
+            }
+            match &obj[&keyword.to_string()] {
+                Value::Array(candidates) => {
+                    let candidates: Vec<(String, &Value)> = candidates
+                        .iter()
+                        .enumerate()
+                        .map(|(index, c)| (format!("{}/{}/{}", pointer, keyword, index), c))
+                        .collect();
+                    self.union(candidates, hint)
+                },
+                _ => any(),
+            }
+        } else {
+            match obj.get("type") {
+                Some(Value::String(name)) => self.typed(name, obj, pointer, hint, &mut handled),
+                Some(Value::Array(names)) => {
+                    let names: Vec<&str> = names.iter().filter_map(Value::as_str).collect();
+                    let candidates: Vec<fio::Type> = names
+                        .iter()
+                        .map(|name| self.typed(name, obj, pointer, hint, &mut handled))
+                        .collect();
+                    self.candidates(candidates, hint)
+                },
+                _ if obj.contains_key("properties") || obj.contains_key("required") => {
+                    self.typed("object", obj, pointer, hint, &mut handled)
+                },
+                _ if obj.contains_key("items") || obj.contains_key("prefixItems") => {
+                    self.typed("array", obj, pointer, hint, &mut handled)
+                },
+                _ => any(),
+            }
+        };
+        handled.insert("type");
+
+        let mut unsupported: Vec<&String> = obj.keys().filter(|k| !handled.contains(k.as_str())).collect();
+        unsupported.sort();
+        for keyword in unsupported {
+            self.warn(pointer, format!("unsupported keyword `{}`", keyword));
+        }
+        t
+    }
+
+    fn reference(&mut self, reference: &Value, pointer: &str) -> fio::Type {
+        match reference.as_str().and_then(|r| self.def_names.get(r)) {
+            Some(name) => fio::Type::RefType(fio::RefType { name: name.clone(), position: nowhere() }),
+            None => {
+                self.warn(pointer, format!("unsupported reference {}, imported as `.`", reference));
+                any()
+            },
+        }
+    }
+
+    fn enumeration(&mut self, values: &Value, pointer: &str) -> fio::Type {
+        let values = match values {
+            Value::Array(values) if !values.is_empty() => values,
+            _ => {
+                self.warn(pointer, String::from("invalid enumeration, imported as `.`"));
+                return any();
+            },
+        };
+        let base = if values.iter().all(Value::is_string) {
+            builtin("String")
+        } else if values.iter().all(Value::is_number) {
+            builtin("Number")
+        } else if values.iter().all(Value::is_boolean) {
+            builtin("Boolean")
+        } else {
+            any()
+        };
+        let literals: Option<Vec<String>> = values.iter().map(literal).collect();
+        match literals {
+            Some(literals) => {
+                let parts: Vec<String> = literals.iter().map(|l| format!("v == {}", l)).collect();
+                self.constrain(base, "v", vec![parts.join(" || ")], "Enum")
+            },
+            None => {
+                self.warn(pointer, String::from("enumeration of structured values imported without its values"));
+                base
+            },
+        }
+    }
+
+    fn union(&mut self, candidates: Vec<(String, &Value)>, hint: &str) -> fio::Type {
+        let types: Vec<fio::Type> = candidates
+            .iter()
+            .enumerate()
+            .map(|(index, (pointer, c))| self.r#type(c, pointer, &format!("{}{}", hint, index + 1)))
+            .collect();
+        self.candidates(types, hint)
+    }
+
+    /// A union of types, flattened, with the candidates the syntax does not
+    /// allow inline moved to their own definitions
+    fn candidates(&mut self, types: Vec<fio::Type>, hint: &str) -> fio::Type {
+        let mut candidates = vec![];
+        for t in types {
+            match t {
+                fio::Type::UnionType(u) => candidates.extend(u.candidates),
+                t => candidates.push(t),
+            }
+        }
+        if candidates.len() == 1 {
+            return candidates.remove(0);
+        }
+        let candidates = candidates
+            .into_iter()
+            .enumerate()
+            .map(|(index, c)| match c {
+                fio::Type::TupleType(_) | fio::Type::RelationType(_) => {
+                    self.hoist(c, &format!("{}{}", hint, index + 1))
+                },
+                c => c,
+            })
+            .collect();
+        fio::Type::UnionType(fio::UnionType { candidates, position: nowhere() })
+    }
+
+    /// A sub type, constraints are joined by `&&`
+    fn constrain(&mut self, base: fio::Type, param: &str, parts: Vec<String>, hint: &str) -> fio::Type {
+        if parts.is_empty() {
+            return base;
+        }
+        let base = match base {
+            fio::Type::NilType(_) | fio::Type::UnionType(_) | fio::Type::SubType(_) => self.hoist(base, hint),
+            base => base,
+        };
+        let expr = if parts.len() == 1 {
+            parts[0].clone()
+        } else {
+            parts.iter().map(|p| format!("({})", p)).collect::<Vec<_>>().join(" && ")
+        };
+        fio::Type::SubType(fio::SubType {
+            base: Box::new(base),
+            constraints: vec![fio::Constraint {
+                param: param.to_string(),
+                expr: format!(" {}", expr),
+                position: nowhere(),
+            }],
+            position: nowhere(),
+        })
+    }
+
+    /// Comparisons of `subject` for the numeric facets of a schema
+    fn facets(&mut self, obj: &Map<String, Value>, subject: &str, facets: &[(&'static str, &str)], handled: &mut HashSet<&str>) -> Vec<String> {
+        let mut parts = vec![];
+        for (keyword, op) in facets.iter() {
+            if let Some(value) = obj.get(*keyword).filter(|v| v.is_number()) {
+                handled.insert(keyword);
+                if *op == "%" {
+                    parts.push(format!("{} % {} == 0", subject, value));
+                } else {
+                    parts.push(format!("{} {} {}", subject, op, value));
+                }
+            }
+        }
+        parts
+    }
+
+    fn typed(&mut self, name: &str, obj: &Map<String, Value>, pointer: &str, hint: &str, handled: &mut HashSet<&str>) -> fio::Type {
+        const NUMERIC: [(&str, &str); 5] = [
+            ("minimum", ">="),
+            ("exclusiveMinimum", ">"),
+            ("maximum", "<="),
+            ("exclusiveMaximum", "<"),
+            ("multipleOf", "%"),
+        ];
+        match name {
+            "null" => fio::Type::NilType(fio::NilType { position: nowhere() }),
+            "boolean" => builtin("Boolean"),
+            "number" | "integer" => {
+                let mut parts = self.facets(obj, "n", &NUMERIC, handled);
+                if name == "integer" {
+                    parts.insert(0, String::from("n % 1 == 0"));
+                }
+                self.constrain(builtin("Number"), "n", parts, hint)
+            },
+            "string" => {
+                handled.insert("format");
+                let base = match obj.get("format").and_then(Value::as_str) {
+                    Some("date") => builtin("Date"),
+                    Some("date-time") => builtin("DateTime"),
+                    Some(format) => {
+                        self.warn(pointer, format!("format `{}` is not checked", format));
+                        builtin("String")
+                    },
+                    None => builtin("String"),
+                };
+                if let Some(pattern) = obj.get("pattern") {
+                    handled.insert("pattern");
+                    self.warn(pointer, format!("pattern {} is not checked, finitio constraints have no regular expressions", pattern));
+                }
+                let parts = self.facets(obj, "len(s)", &[("minLength", ">="), ("maxLength", "<=")], handled);
+                self.constrain(base, "s", parts, hint)
+            },
+            "array" => {
+                let base = self.array(obj, pointer, hint, handled);
+                let parts = self.facets(obj, "len(a)", &[("minItems", ">="), ("maxItems", "<=")], handled);
+                self.constrain(base, "a", parts, hint)
+            },
+            "object" => self.object(obj, pointer, hint, handled),
+            name => {
+                self.warn(pointer, format!("unsupported type `{}`, imported as `.`", name));
+                any()
+            },
+        }
+    }
+
+    fn array(&mut self, obj: &Map<String, Value>, pointer: &str, hint: &str, handled: &mut HashSet<&str>) -> fio::Type {
+        handled.extend(["items", "prefixItems", "uniqueItems"]);
+        if let Some(Value::Array(prefix)) = obj.get("prefixItems") {
+            let elements = prefix
+                .iter()
+                .enumerate()
+                .map(|(index, elm)| self.r#type(elm, &format!("{}/prefixItems/{}", pointer, index), &format!("{}{}", hint, index + 1)))
+                .collect();
+            if obj.get("items") != Some(&Value::Bool(false)) {
+                self.warn(pointer, String::from("items after `prefixItems` are not allowed by the imported struct"));
+            }
+            return fio::Type::StructType(fio::StructType { elements, position: nowhere() });
+        }
+
+        let unique = obj.get("uniqueItems") == Some(&Value::Bool(true));
+        let items_pointer = format!("{}/items", pointer);
+        let elm_hint = format!("{}Item", hint);
+        let elm_type = match obj.get("items") {
+            Some(items) => self.r#type(items, &items_pointer, &elm_hint),
+            None => any(),
+        };
+        match (unique, elm_type) {
+            // Sets of tuples are relations
+            (true, fio::Type::TupleType(tuple)) => fio::Type::RelationType(fio::RelationType {
+                heading: tuple.heading,
+                keys: vec![],
+                position: nowhere(),
+            }),
+            (true, elm_type) => fio::Type::SetType(fio::SetType { elm_type: Box::new(elm_type), position: nowhere() }),
+            (false, elm_type) => fio::Type::SeqType(fio::SeqType { elm_type: Box::new(elm_type), position: nowhere() }),
+        }
+    }
+
+    fn object(&mut self, obj: &Map<String, Value>, pointer: &str, hint: &str, handled: &mut HashSet<&str>) -> fio::Type {
+        handled.extend(["properties", "required", "additionalProperties"]);
+        let required: Vec<&str> = match obj.get("required") {
+            Some(Value::Array(names)) => names.iter().filter_map(Value::as_str).collect(),
+            _ => vec![],
+        };
+        let empty = Map::new();
+        let properties = match obj.get("properties") {
+            Some(Value::Object(properties)) => properties,
+            _ => &empty,
+        };
+
+        let mut attributes = vec![];
+        for (name, property) in properties.iter() {
+            let property_pointer = format!("{}/properties/{}", pointer, escape(name));
+            if !is_identifier(name) {
+                self.warn(&property_pointer, format!("property `{}` is not a valid attribute name, skipped", name));
+                continue;
+            }
+            let att_type = self.r#type(property, &property_pointer, &format!("{}{}", hint, capitalize(name)));
+            let meta = property.get("description").and_then(Value::as_str).map(|d| format!(" {} ", d.trim()));
+            attributes.push(fio::Attribute {
+                meta,
+                name: name.clone(),
+                att_type,
+                optional: !required.contains(&name.as_str()),
+                position: nowhere(),
+            });
+        }
+        for name in required.iter().filter(|name| !properties.contains_key(**name)) {
+            if is_identifier(name) {
+                attributes.push(fio::Attribute {
+                    meta: None,
+                    name: name.to_string(),
+                    att_type: any(),
+                    optional: false,
+                    position: nowhere(),
+                });
+            } else {
+                self.warn(pointer, format!("required property `{}` is not a valid attribute name, skipped", name));
+            }
+        }
+
+        // Extra properties are allowed unless stated otherwise
+        let allow_extra = match obj.get("additionalProperties") {
+            Some(Value::Bool(false)) => None,
+            None | Some(Value::Bool(true)) => Some(any()),
+            Some(extra) => {
+                let extra_pointer = format!("{}/additionalProperties", pointer);
+                Some(self.r#type(extra, &extra_pointer, &format!("{}Extra", hint)))
+            },
+        };
+        let heading = fio::Heading {
+            attributes,
+            position: nowhere(),
+            allow_extra: allow_extra.map(|extra_type| Box::new(fio::AllowExtra { extra_type, position: nowhere() })),
+        };
+        fio::Type::TupleType(fio::TupleType { heading, foreign_keys: vec![], position: nowhere() })
+    }
+}
+
+#[cfg(test)]
+use serde_json::json;
+
+#[cfg(test)]
+fn import_and_load(document: Value) -> (Imported, crate::schema::Schema) {
+    let imported = import(&document, "Main");
+    let source = imported.to_fio();
+    let schema = crate::schema::schema::test_schema(&source);
+    (imported, schema)
+}
+
+#[test]
+fn test_import_objects() {
+    let (imported, schema) = import_and_load(json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "$defs": {
+            "person": {
+                "description": "A person",
+                "type": "object",
+                "properties": {
+                    "name": { "type": "string", "minLength": 1, "description": "The full name" },
+                    "age": { "type": "integer", "minimum": 0 },
+                    "emails": { "type": "array", "items": { "type": "string" }, "uniqueItems": true },
+                    "born": { "type": "string", "format": "date" },
+                },
+                "required": ["name"],
+                "additionalProperties": false,
+            },
+        },
+        "type": "array",
+        "items": { "$ref": "#/$defs/person" },
+    }));
+
+    assert_eq!(imported.to_fio(), "/- A person -/
+person = {
+  age :? .Number(n | (n % 1 == 0) && (n >= 0))
+  born :? .Date
+  emails :? {.String}
+  /- The full name -/
+  name: .String(s | len(s) >= 1)
+}
+
+Main = [person]
+");
+    assert!(imported.warnings.is_empty());
+
+    let main = &schema.types["Main"];
+    use crate::schema::TypeInclude;
+    assert!(main.include(&json!([{ "name": "Al", "age": 3, "emails": ["a@b.c"] }])).is_ok());
+    assert!(main.include(&json!([{ "name": "" }])).is_err());
+    assert!(main.include(&json!([{ "name": "Al", "age": 3.5 }])).is_err());
+    assert!(main.include(&json!([{ "name": "Al", "nick": "A" }])).is_err());
+}
+
+#[test]
+fn test_import_unions_and_enums() {
+    let (imported, schema) = import_and_load(json!({
+        "$defs": {
+            "Color": { "enum": ["red", "green"] },
+            "Shape": {
+                "anyOf": [
+                    { "type": "object", "properties": { "radius": { "type": "number" } }, "required": ["radius"] },
+                    { "type": "null" },
+                ],
+            },
+            "Id": { "type": ["string", "number"], "pattern": "^[a-z]+$" },
+            "Point": { "type": "array", "prefixItems": [{ "type": "number" }, { "type": "number" }], "items": false },
+            "Points": { "type": "array", "uniqueItems": true, "items": { "type": "object", "properties": { "x": { "type": "number" } } } },
+            "Odd": { "not": { "type": "null" } },
+        },
+    }));
+
+    assert_eq!(imported.to_fio(), "Color = .String(v | v == \"red\" || v == \"green\")
+
+Id = .String | .Number
+
+Odd = .
+
+Point = <.Number, .Number>
+
+Points = {{ x :? .Number, ... }}
+
+Shape1 = { radius: .Number, ... }
+
+Shape = Shape1 | Nil
+");
+    assert_eq!(
+        imported.warnings.iter().map(Warning::to_string).collect::<Vec<_>>(),
+        vec![
+            "#/$defs/Id: pattern \"^[a-z]+$\" is not checked, finitio constraints have no regular expressions",
+            "#/$defs/Odd: unsupported keyword `not`",
+        ]
+    );
+
+    use crate::schema::TypeInclude;
+    assert!(schema.types["Color"].include(&json!("red")).is_ok());
+    assert!(schema.types["Color"].include(&json!("blue")).is_err());
+    assert!(schema.types["Shape"].include(&json!({ "radius": 2 })).is_ok());
+}
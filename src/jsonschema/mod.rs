@@ -0,0 +1,9 @@
+//! Conversions between finitio schemas and JSON Schema (draft 2020-12)
+
+mod export;
+mod import;
+
+pub use export::{export, Export, Untranslated};
+pub use import::{import, Imported, Warning};
+
+pub const DRAFT: &str = "https://json-schema.org/draft/2020-12/schema";
@@ -1,4 +1,42 @@
+// The tests compare results with `true` and `false` on purpose
+#![cfg_attr(test, allow(clippy::bool_assert_comparison))]
+// Lints that the code written before clippy was enforced does not follow
+#![cfg_attr(test, allow(clippy::empty_line_after_outer_attr, clippy::needless_lifetimes))]
+#![allow(
+    clippy::extra_unused_lifetimes,
+    clippy::len_zero,
+    clippy::manual_map,
+    clippy::map_clone,
+    clippy::module_inception,
+    clippy::needless_return,
+    clippy::new_without_default,
+    clippy::partialeq_to_none,
+    clippy::ptr_arg,
+    clippy::question_mark,
+    clippy::redundant_closure,
+    clippy::redundant_field_names,
+    clippy::redundant_guards,
+    clippy::redundant_pattern_matching,
+    clippy::useless_conversion
+)]
+
+// Lets the code derived by finitio-derive refer to this crate from within
+extern crate self as finitio;
+
 pub mod common;
 pub mod fio;
 pub mod schema;
 pub mod js;
+pub mod fmt;
+pub mod lint;
+pub mod diff;
+pub mod algebra;
+pub mod jsonschema;
+pub mod sql;
+pub mod graphql;
+pub mod codegen;
+pub mod build;
+pub mod derive;
+pub mod lsp;
+
+pub use derive::Finitio;
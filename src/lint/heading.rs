@@ -0,0 +1,72 @@
+use crate::fio;
+use crate::schema::{self, Type};
+
+use super::{Rule, Warning};
+
+/// Calls `visit` on every heading (of tuples and relations) of the schema
+fn each_heading<F: FnMut(&fio::Heading)>(fschema: &fio::Schema, mut visit: F) {
+    for typedef in fschema.type_defs.iter() {
+        typedef.target.walk(&mut |t| match t {
+            fio::Type::TupleType(t) => visit(&t.heading),
+            fio::Type::RelationType(r) => visit(&r.heading),
+            _ => {},
+        });
+    }
+}
+
+fn accepts_nil(t: &Type) -> bool {
+    match t {
+        Type::Nil(_) => true,
+        Type::Ref(r) => r.target().is_some_and(|t| accepts_nil(&t)),
+        Type::Union(u) => u.candidates.iter().any(accepts_nil),
+        _ => false,
+    }
+}
+
+pub(crate) fn lint_optional_nil(fschema: &fio::Schema, schema: &schema::Schema) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+    each_heading(fschema, |heading| {
+        for attribute in heading.attributes.iter().filter(|a| a.optional) {
+            // Only unions are considered, an optional attribute typed with a
+            // plain (named) nullable type is a deliberate choice
+            let nullable = match &attribute.att_type {
+                fio::Type::UnionType(u) => u.candidates
+                    .iter()
+                    .filter_map(|c| schema.resolve_type(c).ok())
+                    .any(|c| accepts_nil(&c)),
+                _ => false,
+            };
+            if nullable {
+                warnings.push(Warning {
+                    rule: Rule::OptionalNil,
+                    message: format!(
+                        "Attribute `{}` is both optional and nullable, use either `:?` or `Nil |`",
+                        attribute.name
+                    ),
+                    position: attribute.position.clone(),
+                });
+            }
+        }
+    });
+    warnings
+}
+
+#[cfg(test)]
+use super::{lint_source, LintOptions};
+
+#[test]
+fn test_lint_optional_nil() {
+    let source = "
+Null = Nil
+Person = {
+  name :? Nil | .String
+  nick :? .String | Null
+  age: Nil | .Number
+  email :? .String
+}
+";
+    let warnings = lint_source(source, &LintOptions::default());
+    let lines: Vec<u32> = warnings.iter().map(|w| w.position.line).collect();
+    assert!(warnings.iter().all(|w| w.rule == Rule::OptionalNil));
+    assert_eq!(lines, vec![4, 5]);
+}
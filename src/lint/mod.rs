@@ -0,0 +1,138 @@
+mod heading;
+mod union;
+mod unused;
+
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::common::FilePosition;
+use crate::{fio, schema};
+
+/// The lint rules, each of them can be allowed (i.e. suppressed) individually
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Rule {
+    /// A type definition that is never used by the root types
+    UnusedType,
+    /// A union candidate that is subsumed by an earlier candidate
+    UnreachableCandidate,
+    /// An optional attribute whose type also accepts Nil
+    OptionalNil,
+}
+
+impl Rule {
+    pub const ALL: [Rule; 3] = [
+        Rule::UnusedType,
+        Rule::UnreachableCandidate,
+        Rule::OptionalNil,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Rule::UnusedType => "unused-type",
+            Rule::UnreachableCandidate => "unreachable-candidate",
+            Rule::OptionalNil => "optional-nil",
+        }
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Rule {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Rule::ALL
+            .iter()
+            .find(|rule| rule.name() == name)
+            .copied()
+            .ok_or_else(|| format!("Unknown lint rule: {}", name))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Warning {
+    pub rule: Rule,
+    pub message: String,
+    pub position: FilePosition,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: warning[{}]: {}",
+            self.position.line, self.position.column, self.rule, self.message
+        )
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct LintOptions {
+    /// Rules that should not be reported
+    pub allowed: HashSet<Rule>,
+    /// The entry point types of the schema, used by the `unused-type` rule.
+    /// The rule is skipped when no root is given.
+    pub roots: Vec<String>,
+}
+
+impl LintOptions {
+    pub fn is_enabled(&self, rule: Rule) -> bool {
+        !self.allowed.contains(&rule)
+    }
+}
+
+/// Lints a parsed schema, using its resolved counterpart to give a meaning to
+/// the type references. Warnings are sorted by position.
+pub fn lint(fschema: &fio::Schema, schema: &schema::Schema, options: &LintOptions) -> Vec<Warning> {
+    let mut warnings: Vec<Warning> = Vec::new();
+
+    if options.is_enabled(Rule::UnusedType) {
+        warnings.extend(unused::lint_unused_types(fschema, &options.roots));
+    }
+    if options.is_enabled(Rule::UnreachableCandidate) {
+        warnings.extend(union::lint_unreachable_candidates(fschema, schema));
+    }
+    if options.is_enabled(Rule::OptionalNil) {
+        warnings.extend(heading::lint_optional_nil(fschema, schema));
+    }
+
+    warnings.sort_by_key(|w| (w.position.line, w.position.column));
+    warnings
+}
+
+#[cfg(test)]
+pub(crate) fn lint_source(source: &str, options: &LintOptions) -> Vec<Warning> {
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    let fschema = fio::parse_schema(source).unwrap();
+    let path = PathBuf::from("test.fio");
+    let fios = HashMap::from([(path.clone(), fschema.clone())]);
+    let schemas = schema::Schema::from_fios(fios).unwrap();
+    lint(&fschema, schemas.get(&path).unwrap(), options)
+}
+
+#[test]
+fn test_rule_names() {
+    for rule in Rule::ALL {
+        assert_eq!(rule.name().parse::<Rule>(), Ok(rule));
+    }
+    assert!("no-such-rule".parse::<Rule>().is_err());
+}
+
+#[test]
+fn test_allowed_rules_are_not_reported() {
+    let source = "T = .Number | .Number";
+    assert_eq!(lint_source(source, &LintOptions::default()).len(), 1);
+
+    let options = LintOptions {
+        allowed: HashSet::from([Rule::UnreachableCandidate]),
+        ..Default::default()
+    };
+    assert!(lint_source(source, &options).is_empty());
+}
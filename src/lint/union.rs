@@ -0,0 +1,87 @@
+use crate::fio;
+use crate::fmt::FioFormat;
+use crate::schema::{self, is_subtype, Type};
+
+use super::{Rule, Warning};
+
+pub(crate) fn lint_unreachable_candidates(fschema: &fio::Schema, schema: &schema::Schema) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+    for typedef in fschema.type_defs.iter() {
+        typedef.target.walk(&mut |t| {
+            if let fio::Type::UnionType(union) = t {
+                warnings.extend(lint_union(union, schema));
+            }
+        });
+    }
+    warnings
+}
+
+fn lint_union(union: &fio::UnionType, schema: &schema::Schema) -> Vec<Warning> {
+    let resolved: Vec<Option<Type>> = union.candidates
+        .iter()
+        .map(|c| schema.resolve_type(c).ok())
+        .collect();
+
+    let mut warnings = Vec::new();
+    for (pos, candidate) in union.candidates.iter().enumerate() {
+        let later = match &resolved[pos] {
+            Some(later) => later,
+            None => continue,
+        };
+        let subsumed_by = (0..pos).find(|earlier| match &resolved[*earlier] {
+            Some(earlier) => is_subtype(later, earlier).is_ok(),
+            None => false,
+        });
+        if let Some(earlier) = subsumed_by {
+            warnings.push(Warning {
+                rule: Rule::UnreachableCandidate,
+                message: format!(
+                    "Union candidate `{}` can never match, values are already accepted by `{}`",
+                    candidate.format(0),
+                    union.candidates[earlier].format(0),
+                ),
+                position: candidate.position().clone(),
+            });
+        }
+    }
+    warnings
+}
+
+#[cfg(test)]
+use super::{lint_source, LintOptions};
+
+#[test]
+fn test_lint_unreachable_candidates() {
+    let source = "
+Number = .Number
+PosNumber = Number(n | n > 0)
+A = . | Number
+B = Number | PosNumber | Nil
+C = PosNumber | Number
+D = [Number] | [PosNumber]
+";
+    let warnings = lint_source(source, &LintOptions::default());
+    let messages: Vec<(u32, &str)> = warnings
+        .iter()
+        .map(|w| (w.position.line, w.message.as_str()))
+        .collect();
+    assert_eq!(messages, vec![
+        (4, "Union candidate `Number` can never match, values are already accepted by `.`"),
+        (5, "Union candidate `PosNumber` can never match, values are already accepted by `Number`"),
+        (7, "Union candidate `[PosNumber]` can never match, values are already accepted by `[Number]`"),
+    ]);
+}
+
+#[test]
+fn test_unreachable_tuples() {
+    let source = "
+String = .String
+Person = { name: String, age :? .Number }
+Named = { name: String }
+A = Person | Named
+B = Named | Person
+";
+    let warnings = lint_source(source, &LintOptions::default());
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].position.line, 5);
+}
@@ -0,0 +1,73 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::fio;
+
+use super::{Rule, Warning};
+
+/// Names of the types referenced by a type
+pub(crate) fn references(ftype: &fio::Type) -> Vec<String> {
+    let mut names = Vec::new();
+    ftype.walk(&mut |t| {
+        if let fio::Type::RefType(r) = t {
+            names.push(r.name.clone());
+        }
+    });
+    names
+}
+
+pub(crate) fn lint_unused_types(fschema: &fio::Schema, roots: &[String]) -> Vec<Warning> {
+    if roots.is_empty() {
+        return vec![];
+    }
+
+    let graph: HashMap<&str, Vec<String>> = fschema.type_defs
+        .iter()
+        .map(|t| (t.name.as_str(), references(&t.target)))
+        .collect();
+
+    let mut used: HashSet<String> = HashSet::new();
+    let mut pending: Vec<String> = roots.to_vec();
+    while let Some(name) = pending.pop() {
+        if !used.insert(name.clone()) {
+            continue;
+        }
+        if let Some(refs) = graph.get(name.as_str()) {
+            pending.extend(refs.iter().cloned());
+        }
+    }
+
+    fschema.type_defs
+        .iter()
+        .filter(|t| !used.contains(&t.name))
+        .map(|t| Warning {
+            rule: Rule::UnusedType,
+            message: format!("Type `{}` is never used", t.name),
+            position: t.position.clone(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+use super::{lint_source, LintOptions};
+
+#[test]
+fn test_lint_unused_types() {
+    let source = "
+String = .String
+Name = String
+Password = String
+Person = { name: Name, friends: [Person] }
+";
+    let no_roots = LintOptions::default();
+    assert!(lint_source(source, &no_roots).is_empty());
+
+    let options = LintOptions {
+        roots: vec!["Person".to_string()],
+        ..Default::default()
+    };
+    let warnings = lint_source(source, &options);
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].rule, Rule::UnusedType);
+    assert_eq!(warnings[0].message, "Type `Password` is never used");
+    assert_eq!(warnings[0].position.line, 4);
+}
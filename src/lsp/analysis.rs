@@ -0,0 +1,489 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use lsp_types::{
+    CompletionItem, CompletionItemKind, Diagnostic, DiagnosticSeverity, DocumentSymbol, Hover,
+    HoverContents, Location, MarkupContent, MarkupKind, Position, Range, SymbolKind, Url,
+};
+
+use crate::common::FilePosition;
+use crate::fio;
+use crate::fmt::FioFormat;
+use crate::lint::{self, LintOptions};
+
+use super::workspace::{Loaded, Workspace};
+
+/// Maximum number of references followed to find what a type resolves to
+const MAX_REF_CHAIN: usize = 16;
+
+/// Converts positions between byte offsets, finitio positions (1-based lines
+/// and columns in chars) and LSP positions (0-based lines and columns in
+/// UTF-16 code units)
+pub struct LineIndex<'a> {
+    source: &'a str,
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub fn new(source: &'a str) -> Self {
+        let mut line_starts = vec![0];
+        line_starts.extend(source.match_indices('\n').map(|(i, _)| i + 1));
+        Self { source, line_starts }
+    }
+
+    fn line(&self, line: usize) -> &'a str {
+        let start = self.line_starts.get(line).copied().unwrap_or(self.source.len());
+        let end = self.line_starts.get(line + 1).copied().unwrap_or(self.source.len());
+        self.source[start..end].trim_end_matches(['\n', '\r'])
+    }
+
+    pub fn offset(&self, position: &FilePosition) -> usize {
+        let line = position.line.saturating_sub(1) as usize;
+        let start = self.line_starts.get(line).copied().unwrap_or(self.source.len());
+        let column: usize = self.line(line)
+            .chars()
+            .take(position.column.saturating_sub(1))
+            .map(char::len_utf8)
+            .sum();
+        start + column
+    }
+
+    pub fn file_position(&self, offset: usize) -> FilePosition {
+        let line = match self.line_starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(next) => next - 1,
+        };
+        let column = self.source[self.line_starts[line]..offset].chars().count() + 1;
+        FilePosition { line: line as u32 + 1, column }
+    }
+
+    pub fn to_lsp(&self, position: &FilePosition) -> Position {
+        let line = position.line.saturating_sub(1);
+        let character: usize = self.line(line as usize)
+            .chars()
+            .take(position.column.saturating_sub(1))
+            .map(char::len_utf16)
+            .sum();
+        Position::new(line, character as u32)
+    }
+
+    pub fn from_lsp(&self, position: Position) -> FilePosition {
+        let mut units = 0;
+        let mut column = 1;
+        for c in self.line(position.line as usize).chars() {
+            if units >= position.character as usize {
+                break;
+            }
+            units += c.len_utf16();
+            column += 1;
+        }
+        FilePosition { line: position.line + 1, column }
+    }
+
+    /// The range of `len` chars starting at `position`, on a single line
+    pub fn range(&self, position: &FilePosition, len: usize) -> Range {
+        let end = FilePosition { line: position.line, column: position.column + len };
+        Range::new(self.to_lsp(position), self.to_lsp(&end))
+    }
+}
+
+/// A type name, where it is defined or used
+#[derive(Clone, Debug, PartialEq)]
+pub struct Occurrence {
+    pub name: String,
+    pub position: FilePosition,
+    pub definition: bool,
+}
+
+impl Occurrence {
+    fn covers(&self, position: &FilePosition) -> bool {
+        self.position.line == position.line
+            && self.position.column <= position.column
+            && position.column <= self.position.column + self.name.chars().count()
+    }
+}
+
+/// Position of the name of a type definition (its own position includes its meta)
+fn name_position(index: &LineIndex, typedef: &fio::TypeDef) -> FilePosition {
+    let mut offset = index.offset(&typedef.position);
+    if let Some(meta) = &typedef.meta {
+        offset += "/-".len() + meta.len() + "-/".len();
+    }
+    let rest = &index.source[offset.min(index.source.len())..];
+    offset += rest.len() - rest.trim_start().len();
+    index.file_position(offset)
+}
+
+pub fn occurrences(source: &str, fschema: &fio::Schema) -> Vec<Occurrence> {
+    let index = LineIndex::new(source);
+    let mut occurrences = Vec::new();
+    for typedef in fschema.type_defs.iter() {
+        occurrences.push(Occurrence {
+            name: typedef.name.clone(),
+            position: name_position(&index, typedef),
+            definition: true,
+        });
+        typedef.target.walk(&mut |t| {
+            if let fio::Type::RefType(r) = t {
+                occurrences.push(Occurrence {
+                    name: r.name.clone(),
+                    position: r.position.clone(),
+                    definition: false,
+                });
+            }
+        });
+    }
+    occurrences
+}
+
+/// A type definition found in a loaded schema
+pub struct Definition<'a> {
+    pub path: PathBuf,
+    pub typedef: &'a fio::TypeDef,
+}
+
+impl Definition<'_> {
+    fn location(&self, loaded: &Loaded) -> Option<Location> {
+        let source = loaded.sources.get(&self.path)?;
+        let index = LineIndex::new(source);
+        let position = name_position(&index, self.typedef);
+        Some(Location::new(
+            Url::from_file_path(&self.path).ok()?,
+            index.range(&position, self.typedef.name.chars().count()),
+        ))
+    }
+}
+
+/// Looks a type name up the way the resolver does: in the schema itself, then
+/// in the schemas it directly imports
+pub fn definition<'a>(loaded: &'a Loaded, path: &Path, name: &str) -> Option<Definition<'a>> {
+    let mut candidates = vec![path.to_path_buf()];
+    candidates.extend(loaded.imports(path).into_iter().map(|(p, _)| p));
+    candidates.into_iter().find_map(|path| {
+        let fschema = loaded.fios.get(&path)?;
+        let typedef = fschema.type_defs.iter().find(|t| t.name == name)?;
+        Some(Definition { path, typedef })
+    })
+}
+
+/// The type name under the cursor
+fn occurrence_at(loaded: &Loaded, path: &Path, position: Position) -> Option<Occurrence> {
+    let source = loaded.sources.get(path)?;
+    let fschema = loaded.fios.get(path)?;
+    let position = LineIndex::new(source).from_lsp(position);
+    occurrences(source, fschema)
+        .into_iter()
+        .find(|o| o.covers(&position))
+}
+
+pub fn diagnostics(loaded: &Loaded) -> Vec<Diagnostic> {
+    let source = match loaded.sources.get(&loaded.entry) {
+        Some(source) => source,
+        None => return vec![],
+    };
+    let index = LineIndex::new(source);
+    let (schema, problems) = loaded.validate();
+
+    let mut diagnostics: Vec<Diagnostic> = problems
+        .iter()
+        .map(|p| {
+            let position = index.to_lsp(&p.position);
+            Diagnostic {
+                range: Range::new(position, position),
+                severity: Some(DiagnosticSeverity::ERROR),
+                source: Some(String::from("finitio")),
+                message: p.message.clone(),
+                ..Default::default()
+            }
+        })
+        .collect();
+
+    if let (Some(schema), Some(fschema)) = (schema, loaded.fios.get(&loaded.entry)) {
+        let warnings = lint::lint(fschema, &schema, &LintOptions::default());
+        diagnostics.extend(warnings.into_iter().map(|w| {
+            let position = index.to_lsp(&w.position);
+            Diagnostic {
+                range: Range::new(position, position),
+                severity: Some(DiagnosticSeverity::WARNING),
+                code: Some(lsp_types::NumberOrString::String(w.rule.name().to_string())),
+                source: Some(String::from("finitio")),
+                message: w.message,
+                ..Default::default()
+            }
+        }));
+    }
+    diagnostics
+}
+
+pub fn goto_definition(workspace: &Workspace, path: &Path, position: Position) -> Option<Location> {
+    let loaded = workspace.load(path);
+    let occurrence = occurrence_at(&loaded, path, position)?;
+    definition(&loaded, path, &occurrence.name)?.location(&loaded)
+}
+
+pub fn references(
+    workspace: &Workspace,
+    path: &Path,
+    position: Position,
+    include_declaration: bool,
+) -> Vec<Location> {
+    let loaded = workspace.load(path);
+    let target = match occurrence_at(&loaded, path, position)
+        .and_then(|o| definition(&loaded, path, &o.name))
+    {
+        Some(target) => (target.path, target.typedef.name.clone()),
+        None => return vec![],
+    };
+
+    let mut seen: HashSet<(PathBuf, u32, usize)> = HashSet::new();
+    let mut locations = Vec::new();
+
+    // Usages may be in any open document or in the schemas they import
+    let mut entries: Vec<PathBuf> = workspace.documents().cloned().collect();
+    entries.push(path.to_path_buf());
+    for entry in entries {
+        let loaded = workspace.load(&entry);
+        for (file, fschema) in loaded.fios.iter() {
+            let source = &loaded.sources[file];
+            let index = LineIndex::new(source);
+            for occurrence in occurrences(source, fschema) {
+                if occurrence.name != target.1 || (occurrence.definition && !include_declaration) {
+                    continue;
+                }
+                let same_target = definition(&loaded, file, &occurrence.name)
+                    .is_some_and(|d| d.path == target.0);
+                let key = (file.clone(), occurrence.position.line, occurrence.position.column);
+                if same_target && seen.insert(key) {
+                    if let Ok(uri) = Url::from_file_path(file) {
+                        let range = index.range(&occurrence.position, occurrence.name.chars().count());
+                        locations.push(Location::new(uri, range));
+                    }
+                }
+            }
+        }
+    }
+    locations.sort_by_key(|l| (l.uri.to_string(), l.range.start.line, l.range.start.character));
+    locations
+}
+
+pub fn hover(workspace: &Workspace, path: &Path, position: Position) -> Option<Hover> {
+    let loaded = workspace.load(path);
+    let occurrence = occurrence_at(&loaded, path, position)?;
+    let found = definition(&loaded, path, &occurrence.name)?;
+
+    let mut value = format!(
+        "```finitio\n{} = {}\n```",
+        found.typedef.name,
+        found.typedef.target.format(0)
+    );
+
+    // Follow aliases to show what the type eventually is
+    let mut resolved = Definition { path: found.path.clone(), typedef: found.typedef };
+    for _ in 0..MAX_REF_CHAIN {
+        match &resolved.typedef.target {
+            fio::Type::RefType(r) => match definition(&loaded, &resolved.path, &r.name) {
+                Some(next) => resolved = next,
+                None => break,
+            },
+            _ => break,
+        }
+    }
+    if resolved.typedef.name != found.typedef.name {
+        value.push_str(&format!(
+            "\n\nResolves to `{}` = `{}`",
+            resolved.typedef.name,
+            resolved.typedef.target.format(0)
+        ));
+    }
+    if let Some(meta) = &found.typedef.meta {
+        value.push_str(&format!("\n\n{}", meta.trim()));
+    }
+
+    let source = loaded.sources.get(path)?;
+    let index = LineIndex::new(source);
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent { kind: MarkupKind::Markdown, value }),
+        range: Some(index.range(&occurrence.position, occurrence.name.chars().count())),
+    })
+}
+
+pub fn completion(workspace: &Workspace, path: &Path) -> Vec<CompletionItem> {
+    let loaded = workspace.load(path);
+    let mut files = vec![path.to_path_buf()];
+    files.extend(loaded.imports(path).into_iter().map(|(p, _)| p));
+
+    let mut items = vec![CompletionItem {
+        label: String::from("Nil"),
+        kind: Some(CompletionItemKind::KEYWORD),
+        ..Default::default()
+    }];
+    for file in files {
+        if let Some(fschema) = loaded.fios.get(&file) {
+            items.extend(fschema.type_defs.iter().map(|t| CompletionItem {
+                label: t.name.clone(),
+                kind: Some(CompletionItemKind::CLASS),
+                detail: Some(t.target.format(0)),
+                documentation: t.meta.as_ref().map(|m| lsp_types::Documentation::String(m.trim().to_string())),
+                ..Default::default()
+            }));
+        }
+    }
+    items
+}
+
+pub fn document_symbols(workspace: &Workspace, path: &Path) -> Vec<DocumentSymbol> {
+    let loaded = workspace.load(path);
+    let (source, fschema) = match (loaded.sources.get(path), loaded.fios.get(path)) {
+        (Some(source), Some(fschema)) => (source, fschema),
+        _ => return vec![],
+    };
+    let index = LineIndex::new(source);
+    fschema.type_defs
+        .iter()
+        .map(|t| {
+            let position = name_position(&index, t);
+            let range = index.range(&position, t.name.chars().count());
+            let kind = match t.target {
+                fio::Type::TupleType(_) | fio::Type::RelationType(_) => SymbolKind::STRUCT,
+                fio::Type::UnionType(_) => SymbolKind::ENUM,
+                _ => SymbolKind::CLASS,
+            };
+            #[allow(deprecated)]
+            DocumentSymbol {
+                name: t.name.clone(),
+                detail: Some(t.target.format(0)),
+                kind,
+                tags: None,
+                deprecated: None,
+                range,
+                selection_range: range,
+                children: None,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+use super::workspace::workspace;
+
+#[cfg(test)]
+fn uri(path: &str) -> Url {
+    Url::from_file_path(path).unwrap()
+}
+
+#[cfg(test)]
+const MAIN: &str = "@import scalars.fio
+
+/- A person -/
+Person = {
+  name: Name
+  friends: [Person]
+}
+People = {{ name: Name }}";
+
+#[cfg(test)]
+const SCALARS: &str = "String = .String
+/- The name of somebody -/
+Name = String";
+
+#[test]
+fn test_line_index() {
+    let index = LineIndex::new("ab\n€é = X\n");
+    let position = FilePosition { line: 2, column: 3 };
+    assert_eq!(index.offset(&position), 8);
+    assert_eq!(index.file_position(8), position);
+    assert_eq!(index.to_lsp(&position), Position::new(1, 2));
+    assert_eq!(index.from_lsp(Position::new(1, 2)), position);
+}
+
+#[test]
+fn test_occurrences() {
+    let fschema = fio::parse_schema(MAIN).unwrap();
+    let occurrences = occurrences(MAIN, &fschema);
+    let found: Vec<(&str, u32, usize, bool)> = occurrences
+        .iter()
+        .map(|o| (o.name.as_str(), o.position.line, o.position.column, o.definition))
+        .collect();
+    assert_eq!(found, vec![
+        ("Person", 4, 1, true),
+        ("Name", 5, 9, false),
+        ("Person", 6, 13, false),
+        ("People", 8, 1, true),
+        ("Name", 8, 19, false),
+    ]);
+}
+
+#[test]
+fn test_goto_definition_across_imports() {
+    let ws = workspace(&[("/ws/main.fio", MAIN), ("/ws/scalars.fio", SCALARS)]);
+    let location = goto_definition(&ws, Path::new("/ws/main.fio"), Position::new(4, 10)).unwrap();
+    assert_eq!(location, Location::new(
+        uri("/ws/scalars.fio"),
+        Range::new(Position::new(2, 0), Position::new(2, 4)),
+    ));
+    assert!(goto_definition(&ws, Path::new("/ws/main.fio"), Position::new(4, 3)).is_none());
+}
+
+#[test]
+fn test_references_across_imports() {
+    let ws = workspace(&[("/ws/main.fio", MAIN), ("/ws/scalars.fio", SCALARS)]);
+    let locations = references(&ws, Path::new("/ws/scalars.fio"), Position::new(2, 1), false);
+    let found: Vec<(Url, u32)> = locations.into_iter().map(|l| (l.uri, l.range.start.line)).collect();
+    assert_eq!(found, vec![(uri("/ws/main.fio"), 4), (uri("/ws/main.fio"), 7)]);
+
+    let locations = references(&ws, Path::new("/ws/main.fio"), Position::new(5, 13), true);
+    assert_eq!(locations.len(), 2);
+}
+
+#[test]
+fn test_hover() {
+    let ws = workspace(&[("/ws/main.fio", MAIN), ("/ws/scalars.fio", SCALARS)]);
+    let found = hover(&ws, Path::new("/ws/main.fio"), Position::new(4, 9)).unwrap();
+    match found.contents {
+        HoverContents::Markup(content) => assert_eq!(
+            content.value,
+            "```finitio\nName = String\n```\n\nResolves to `String` = `.String`\n\nThe name of somebody"
+        ),
+        contents => panic!("Unexpected hover contents: {:?}", contents),
+    }
+}
+
+#[test]
+fn test_completion_and_symbols() {
+    let ws = workspace(&[("/ws/main.fio", MAIN), ("/ws/scalars.fio", SCALARS)]);
+    let labels: Vec<String> = completion(&ws, Path::new("/ws/main.fio"))
+        .into_iter()
+        .map(|i| i.label)
+        .collect();
+    assert_eq!(labels, vec!["Nil", "Person", "People", "String", "Name"]);
+
+    let symbols: Vec<(String, SymbolKind)> = document_symbols(&ws, Path::new("/ws/main.fio"))
+        .into_iter()
+        .map(|s| (s.name, s.kind))
+        .collect();
+    assert_eq!(symbols, vec![
+        (String::from("Person"), SymbolKind::STRUCT),
+        (String::from("People"), SymbolKind::STRUCT),
+    ]);
+}
+
+#[test]
+fn test_diagnostics() {
+    let ws = workspace(&[
+        ("/ws/main.fio", "@import scalars.fio\nPerson = { name: Nme }\nAny = . | Name"),
+        ("/ws/scalars.fio", SCALARS),
+    ]);
+    let found = diagnostics(&ws.load(Path::new("/ws/main.fio")));
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].message, "no such type `Nme`");
+    assert_eq!(found[0].range.start, Position::new(1, 17));
+
+    let ws = workspace(&[
+        ("/ws/main.fio", "@import scalars.fio\nAny = . | Name"),
+        ("/ws/scalars.fio", SCALARS),
+    ]);
+    let found = diagnostics(&ws.load(Path::new("/ws/main.fio")));
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].severity, Some(DiagnosticSeverity::WARNING));
+    assert_eq!(found[0].range.start, Position::new(1, 10));
+}
@@ -0,0 +1,17 @@
+//! Loading of schemas being edited, and with the `lsp` feature the language
+//! server for .fio files
+
+#[cfg(feature = "lsp")]
+mod analysis;
+#[cfg(feature = "lsp")]
+mod server;
+mod workspace;
+
+#[cfg(feature = "lsp")]
+pub use analysis::{
+    completion, definition, diagnostics, document_symbols, goto_definition, hover, occurrences,
+    references, Definition, LineIndex, Occurrence,
+};
+#[cfg(feature = "lsp")]
+pub use server::run;
+pub use workspace::{Loaded, Problem, Workspace};
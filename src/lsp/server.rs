@@ -0,0 +1,167 @@
+use std::error::Error;
+use std::path::PathBuf;
+
+use lsp_server::{Connection, Message, Notification, Request, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, Notification as _,
+    PublishDiagnostics,
+};
+use lsp_types::request::{
+    Completion, DocumentSymbolRequest, GotoDefinition, HoverRequest, References, Request as _,
+};
+use lsp_types::{
+    CompletionOptions, CompletionResponse, DocumentSymbolResponse, GotoDefinitionResponse,
+    HoverProviderCapability, OneOf, PublishDiagnosticsParams, ServerCapabilities,
+    TextDocumentSyncCapability, TextDocumentSyncKind, Url,
+};
+use serde::de::DeserializeOwned;
+
+use super::analysis;
+use super::workspace::Workspace;
+
+type ServerResult<T> = Result<T, Box<dyn Error + Sync + Send>>;
+
+fn capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        definition_provider: Some(OneOf::Left(true)),
+        references_provider: Some(OneOf::Left(true)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        completion_provider: Some(CompletionOptions::default()),
+        document_symbol_provider: Some(OneOf::Left(true)),
+        ..Default::default()
+    }
+}
+
+/// Runs the language server over stdin/stdout until the client asks it to exit
+pub fn run() -> ServerResult<()> {
+    let (connection, io_threads) = Connection::stdio();
+    connection.initialize(serde_json::to_value(capabilities())?)?;
+
+    let mut server = Server { connection, workspace: Workspace::default() };
+    server.main_loop()?;
+
+    // The writer thread stops once the connection is dropped
+    drop(server);
+    io_threads.join()?;
+    Ok(())
+}
+
+struct Server {
+    connection: Connection,
+    workspace: Workspace,
+}
+
+fn to_path(uri: &Url) -> ServerResult<PathBuf> {
+    uri.to_file_path().map_err(|_| format!("Not a file uri: {}", uri).into())
+}
+
+impl Server {
+    fn main_loop(&mut self) -> ServerResult<()> {
+        while let Ok(message) = self.connection.receiver.recv() {
+            match message {
+                Message::Request(request) => {
+                    if self.connection.handle_shutdown(&request)? {
+                        return Ok(());
+                    }
+                    let id = request.id.clone();
+                    let response = match self.handle_request(request) {
+                        Ok(result) => Response::new_ok(id, result),
+                        Err(err) => Response::new_err(id, lsp_server::ErrorCode::RequestFailed as i32, err.to_string()),
+                    };
+                    self.connection.sender.send(Message::Response(response))?;
+                },
+                Message::Notification(notification) => self.handle_notification(notification)?,
+                Message::Response(_) => {},
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_request(&self, request: Request) -> ServerResult<serde_json::Value> {
+        let ws = &self.workspace;
+        let result = match request.method.as_str() {
+            GotoDefinition::METHOD => {
+                let params: lsp_types::GotoDefinitionParams = params(request)?;
+                let at = params.text_document_position_params;
+                let path = to_path(&at.text_document.uri)?;
+                let location = analysis::goto_definition(ws, &path, at.position);
+                serde_json::to_value(location.map(GotoDefinitionResponse::Scalar))?
+            },
+            References::METHOD => {
+                let params: lsp_types::ReferenceParams = params(request)?;
+                let at = params.text_document_position;
+                let path = to_path(&at.text_document.uri)?;
+                let include_declaration = params.context.include_declaration;
+                serde_json::to_value(analysis::references(ws, &path, at.position, include_declaration))?
+            },
+            HoverRequest::METHOD => {
+                let params: lsp_types::HoverParams = params(request)?;
+                let at = params.text_document_position_params;
+                let path = to_path(&at.text_document.uri)?;
+                serde_json::to_value(analysis::hover(ws, &path, at.position))?
+            },
+            Completion::METHOD => {
+                let params: lsp_types::CompletionParams = params(request)?;
+                let path = to_path(&params.text_document_position.text_document.uri)?;
+                serde_json::to_value(CompletionResponse::Array(analysis::completion(ws, &path)))?
+            },
+            DocumentSymbolRequest::METHOD => {
+                let params: lsp_types::DocumentSymbolParams = params(request)?;
+                let path = to_path(&params.text_document.uri)?;
+                let symbols = analysis::document_symbols(ws, &path);
+                serde_json::to_value(DocumentSymbolResponse::Nested(symbols))?
+            },
+            method => return Err(format!("Unsupported request: {}", method).into()),
+        };
+        Ok(result)
+    }
+
+    fn handle_notification(&mut self, notification: Notification) -> ServerResult<()> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params: lsp_types::DidOpenTextDocumentParams = serde_json::from_value(notification.params)?;
+                let path = to_path(&params.text_document.uri)?;
+                self.workspace.open(path, params.text_document.text);
+                self.publish_diagnostics()
+            },
+            DidChangeTextDocument::METHOD => {
+                let params: lsp_types::DidChangeTextDocumentParams = serde_json::from_value(notification.params)?;
+                let path = to_path(&params.text_document.uri)?;
+                // Full synchronization: the last change holds the whole document
+                if let Some(change) = params.content_changes.into_iter().last() {
+                    self.workspace.open(path, change.text);
+                }
+                self.publish_diagnostics()
+            },
+            DidCloseTextDocument::METHOD => {
+                let params: lsp_types::DidCloseTextDocumentParams = serde_json::from_value(notification.params)?;
+                let path = to_path(&params.text_document.uri)?;
+                self.workspace.close(&path);
+                self.publish(params.text_document.uri, vec![])?;
+                self.publish_diagnostics()
+            },
+            _ => Ok(()),
+        }
+    }
+
+    /// Documents may import each other, so all of them are checked again
+    fn publish_diagnostics(&self) -> ServerResult<()> {
+        for path in self.workspace.documents() {
+            let diagnostics = analysis::diagnostics(&self.workspace.load(path));
+            self.publish(Url::from_file_path(path).map_err(|_| "Invalid path")?, diagnostics)?;
+        }
+        Ok(())
+    }
+
+    fn publish(&self, uri: Url, diagnostics: Vec<lsp_types::Diagnostic>) -> ServerResult<()> {
+        let params = PublishDiagnosticsParams { uri, diagnostics, version: None };
+        let notification = Notification::new(PublishDiagnostics::METHOD.to_string(), params);
+        self.connection.sender.send(Message::Notification(notification))?;
+        Ok(())
+    }
+}
+
+fn params<P: DeserializeOwned>(request: Request) -> ServerResult<P> {
+    Ok(serde_json::from_value(request.params)?)
+}
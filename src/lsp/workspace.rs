@@ -0,0 +1,252 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::common::FilePosition;
+use crate::fio::{self, ParseError};
+use crate::schema;
+
+/// The documents opened in the editor. Their (possibly unsaved) content takes
+/// precedence over the files on disk.
+#[derive(Debug, Default)]
+pub struct Workspace {
+    documents: HashMap<PathBuf, String>,
+}
+
+/// A problem found while loading or validating a schema
+#[derive(Clone, Debug, PartialEq)]
+pub struct Problem {
+    pub path: PathBuf,
+    pub position: FilePosition,
+    pub message: String,
+}
+
+/// A schema and all the schemas it (transitively) imports
+#[derive(Debug)]
+pub struct Loaded {
+    pub entry: PathBuf,
+    pub sources: HashMap<PathBuf, String>,
+    pub fios: HashMap<PathBuf, fio::Schema>,
+    pub problems: Vec<Problem>,
+}
+
+impl Workspace {
+    pub fn open(&mut self, path: PathBuf, text: String) {
+        self.documents.insert(path, text);
+    }
+
+    pub fn close(&mut self, path: &Path) {
+        self.documents.remove(path);
+    }
+
+    pub fn documents(&self) -> impl Iterator<Item = &PathBuf> {
+        self.documents.keys()
+    }
+
+    pub fn source(&self, path: &Path) -> Option<String> {
+        match self.documents.get(path) {
+            Some(text) => Some(text.clone()),
+            None => fs::read_to_string(path).ok(),
+        }
+    }
+
+    /// Parses a schema and the schemas it imports
+    pub fn load(&self, entry: &Path) -> Loaded {
+        let mut loaded = Loaded {
+            entry: entry.to_path_buf(),
+            sources: HashMap::new(),
+            fios: HashMap::new(),
+            problems: Vec::new(),
+        };
+
+        let mut pending = vec![entry.to_path_buf()];
+        while let Some(path) = pending.pop() {
+            if loaded.sources.contains_key(&path) {
+                continue;
+            }
+            let source = match self.source(&path) {
+                Some(source) => source,
+                None => continue,
+            };
+            match fio::parse_schema(&source) {
+                Ok(fschema) => {
+                    let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+                    for import in fschema.imports.iter() {
+                        let import_path = base_dir.join(&import.filename);
+                        if self.source(&import_path).is_none() {
+                            loaded.problems.push(Problem {
+                                path: path.clone(),
+                                position: import.position.clone(),
+                                message: format!("Cannot read imported schema `{}`", import.filename),
+                            });
+                        } else {
+                            pending.push(import_path);
+                        }
+                    }
+                    loaded.fios.insert(path.clone(), fschema);
+                },
+                Err(err) => loaded.problems.push(Problem {
+                    path: path.clone(),
+                    position: err.position(),
+                    message: describe(&err),
+                }),
+            }
+            loaded.sources.insert(path, source);
+        }
+        loaded
+    }
+}
+
+fn describe(err: &ParseError) -> String {
+    match err {
+        ParseError::TrailingGarbage(garbage) => {
+            let line = garbage.fragment().lines().next().unwrap_or_default();
+            format!("Syntax error near `{}`", line.trim())
+        },
+        err => err.to_string(),
+    }
+}
+
+impl Loaded {
+    /// Paths of the schemas directly imported by a schema, with the import
+    pub fn imports(&self, path: &Path) -> Vec<(PathBuf, &fio::Import)> {
+        let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+        match self.fios.get(path) {
+            Some(fschema) => fschema.imports
+                .iter()
+                .map(|i| (base_dir.join(&i.filename), i))
+                .collect(),
+            None => vec![],
+        }
+    }
+
+    /// The parsed schemas needed to resolve a schema, or None if some of them
+    /// could not be loaded
+    pub fn closure(&self, path: &Path) -> Option<HashMap<PathBuf, fio::Schema>> {
+        let mut closure = HashMap::new();
+        let mut pending = vec![path.to_path_buf()];
+        while let Some(path) = pending.pop() {
+            if closure.contains_key(&path) {
+                continue;
+            }
+            let fschema = self.fios.get(&path)?;
+            pending.extend(self.imports(&path).into_iter().map(|(p, _)| p));
+            closure.insert(path, fschema.clone());
+        }
+        Some(closure)
+    }
+
+    /// Resolves the entry schema, reporting the problems found in the entry
+    /// schema itself or, on the import statements, in the schemas it imports
+    pub fn validate(&self) -> (Option<schema::Schema>, Vec<Problem>) {
+        let mut problems: Vec<Problem> = self.problems
+            .iter()
+            .filter(|p| p.path == self.entry)
+            .cloned()
+            .collect();
+
+        for (import_path, import) in self.imports(&self.entry) {
+            if !self.sources.contains_key(&import_path) {
+                // Already reported as unreadable
+                continue;
+            }
+            let invalid = match self.closure(&import_path) {
+                None => Some(String::from("has errors")),
+                Some(fios) => schema::Schema::from_fios(fios).err().map(|e| format!("is invalid: {}", e)),
+            };
+            if let Some(reason) = invalid {
+                problems.push(Problem {
+                    path: self.entry.clone(),
+                    position: import.position.clone(),
+                    message: format!("Imported schema `{}` {}", import.filename, reason),
+                });
+            }
+        }
+        if !problems.is_empty() {
+            return (None, problems);
+        }
+
+        let fios = match self.closure(&self.entry) {
+            Some(fios) => fios,
+            None => return (None, problems),
+        };
+        match schema::Schema::from_fios(fios) {
+            Ok(mut schemas) => (schemas.remove(&self.entry), problems),
+            Err(err) => {
+                problems.push(Problem {
+                    path: self.entry.clone(),
+                    position: err.position().clone(),
+                    message: err.message(),
+                });
+                (None, problems)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) fn workspace(files: &[(&str, &str)]) -> Workspace {
+    let mut workspace = Workspace::default();
+    for (path, text) in files {
+        workspace.open(PathBuf::from(path), text.to_string());
+    }
+    workspace
+}
+
+#[test]
+fn test_load_imports() {
+    let ws = workspace(&[
+        ("/ws/main.fio", "@import scalars.fio\nPerson = { name: String }"),
+        ("/ws/scalars.fio", "String = .String"),
+    ]);
+    let loaded = ws.load(Path::new("/ws/main.fio"));
+    assert!(loaded.problems.is_empty());
+    assert_eq!(loaded.fios.len(), 2);
+
+    let (schema, problems) = loaded.validate();
+    assert!(problems.is_empty());
+    assert!(schema.unwrap().types.contains_key("Person"));
+}
+
+#[test]
+fn test_load_problems() {
+    let ws = workspace(&[
+        ("/ws/main.fio", "@import missing.fio\n@import broken.fio\nPerson = { name: String }"),
+        ("/ws/broken.fio", "String = .String\nInteger ="),
+    ]);
+    let (schema, problems) = ws.load(Path::new("/ws/main.fio")).validate();
+    assert!(schema.is_none());
+    let messages: Vec<(u32, &str)> = problems
+        .iter()
+        .map(|p| (p.position.line, p.message.as_str()))
+        .collect();
+    assert_eq!(messages, vec![
+        (1, "Cannot read imported schema `missing.fio`"),
+        (2, "Imported schema `broken.fio` has errors"),
+    ]);
+}
+
+#[test]
+fn test_validation_problems() {
+    let ws = workspace(&[("/ws/main.fio", "Person = { name: String }")]);
+    let (schema, problems) = ws.load(Path::new("/ws/main.fio")).validate();
+    assert!(schema.is_none());
+    assert_eq!(problems, vec![Problem {
+        path: PathBuf::from("/ws/main.fio"),
+        position: FilePosition { line: 1, column: 18 },
+        message: String::from("no such type `String`"),
+    }]);
+}
+
+#[test]
+fn test_invalid_constraint_problems() {
+    // Half-typed constraints are reported, not fatal
+    let ws = workspace(&[("/ws/main.fio", "T = .Number(n | n > 0 || !n)")]);
+    let (schema, problems) = ws.load(Path::new("/ws/main.fio")).validate();
+    assert!(schema.is_none());
+    assert_eq!(problems, vec![Problem {
+        path: PathBuf::from("/ws/main.fio"),
+        position: FilePosition { line: 1, column: 13 },
+        message: String::from("invalid constraint `n | n > 0 || !n`"),
+    }]);
+}
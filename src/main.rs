@@ -1,9 +1,16 @@
+use finitio::codegen;
+use finitio::diff;
 use finitio::fio::parse_file;
+use finitio::fmt;
+use finitio::graphql;
+use finitio::lint;
 use finitio::js;
+use finitio::jsonschema;
 use finitio::schema;
+use finitio::sql;
 use snafu::ErrorCompat;
 use std::error::Error;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::BufReader;
 use std::path::{PathBuf};
 use clap::{Parser, Subcommand};
@@ -35,6 +42,151 @@ enum Commands {
         #[arg(short, long)]
         /// the path to the json file to be validated
         json: String,
+        #[arg(long)]
+        /// Accept string encodings of numbers, booleans, dates and nil, and
+        /// output the coerced data
+        coerce: bool,
+    },
+    /// Outputs the canonical json encoding of data valid for a finitio type
+    Normalize {
+        #[arg(short, long)]
+        /// The type of the data
+        r#type: String,
+        #[arg(short, long)]
+        /// The path to the entry point schema file (.fio)
+        schema: String,
+        #[arg(short, long)]
+        /// the path to the json file to be normalized
+        json: String,
+    },
+    /// Reports the changes between two versions of json data valid for a finitio type
+    DiffData {
+        #[arg(short, long)]
+        /// The type of the data
+        r#type: String,
+        #[arg(short, long)]
+        /// The path to the entry point schema file (.fio)
+        schema: String,
+        #[arg(long)]
+        /// The path to the json file of the old version
+        old: String,
+        #[arg(long)]
+        /// The path to the json file of the new version
+        new: String,
+        #[arg(short, long)]
+        /// An attribute identifying the tuples of relations, ignored where
+        /// tuples share key values
+        key: Vec<String>,
+    },
+    /// Formats a finitio schema (.fio) in place
+    Fmt {
+        #[arg(short, long)]
+        /// The path to the schema file (.fio) to format
+        schema: String,
+        #[arg(long)]
+        /// Do not write the file, exit with a non-zero status if it is not formatted
+        check: bool,
+    },
+    /// Reports smells in a finitio schema
+    Lint {
+        #[arg(short, long)]
+        /// The path to the entry point schema file (.fio)
+        schema: String,
+        #[arg(short, long)]
+        /// An entry point type of the schema, types it does not use are reported
+        root: Vec<String>,
+        #[arg(short = 'A', long)]
+        /// A lint rule that should not be reported
+        allow: Vec<lint::Rule>,
+    },
+    /// Reports the changes between two versions of a finitio schema
+    Diff {
+        #[arg(long)]
+        /// The path to the entry point schema file (.fio) of the old version
+        old: String,
+        #[arg(long)]
+        /// The path to the entry point schema file (.fio) of the new version
+        new: String,
+        #[arg(long, default_value = "breaking")]
+        /// The compatibility (full, backward, forward) every change must have,
+        /// by default only breaking changes make the command fail
+        require: diff::Compatibility,
+    },
+    /// Exports a finitio schema as a JSON Schema (draft 2020-12)
+    JsonSchema {
+        #[arg(short, long)]
+        /// The path to the entry point schema file (.fio)
+        schema: String,
+        #[arg(short, long)]
+        /// The type the exported document validates, all types are available
+        /// under `$defs` anyway
+        root: Option<String>,
+    },
+    /// Imports a JSON Schema document as a finitio schema (.fio)
+    FromJsonSchema {
+        #[arg(short, long)]
+        /// The path to the JSON Schema document
+        json: String,
+        #[arg(short, long, default_value = "Main")]
+        /// The name of the type imported from the document itself
+        name: String,
+    },
+    /// Exports the relation types of a finitio schema as SQL tables
+    Sql {
+        #[arg(short, long)]
+        /// The path to the entry point schema file (.fio)
+        schema: String,
+        #[arg(short, long, default_value = "postgres")]
+        /// The SQL dialect (postgres, sqlite)
+        dialect: sql::Dialect,
+    },
+    /// Imports the tables of SQL DDL as finitio relation types (.fio)
+    FromSql {
+        #[arg(short = 'q', long)]
+        /// The path to the SQL file of CREATE TABLE statements
+        sql: String,
+        #[arg(long, default_value = "optional")]
+        /// How nullable columns are imported (optional, nil)
+        nullable: sql::Nullable,
+    },
+    /// Exports the types of a finitio schema as a GraphQL schema (SDL)
+    Graphql {
+        #[arg(short, long)]
+        /// The path to the entry point schema file (.fio)
+        schema: String,
+    },
+    /// Generates source code mirroring the types of a finitio schema
+    Gen {
+        #[command(subcommand)]
+        generator: Generator,
+    },
+}
+
+#[derive(Subcommand)]
+enum Generator {
+    /// Generates Rust types, with serde derives
+    Rust {
+        #[arg(short, long)]
+        /// The path to the entry point schema file (.fio)
+        schema: String,
+        #[arg(long, default_value = "BTreeSet")]
+        /// The collection of sets and relations (BTreeSet, HashSet)
+        sets: codegen::rust::SetCollection,
+    },
+    /// Generates Python pydantic models
+    Python {
+        #[arg(short, long)]
+        /// The path to the entry point schema file (.fio)
+        schema: String,
+    },
+    /// Generates TypeScript types, and optionally type guards
+    Typescript {
+        #[arg(short, long)]
+        /// The path to the entry point schema file (.fio)
+        schema: String,
+        #[arg(long)]
+        /// Also generates an `isName(value)` type guard for each type
+        guards: bool,
     },
 }
 
@@ -54,7 +206,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
             Ok(())
         },
-        Commands::Validate { json, schema, r#type } => {
+        Commands::Validate { json, schema, r#type, coerce } => {
             let entry_path = PathBuf::from(schema);
             let fios = match parse_file(&entry_path) {
                 Ok(schema) => schema,
@@ -74,6 +226,17 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
             let target = entry_schema.types.get(r#type);
             match target {
+                Some(t) if *coerce => {
+                    match schema::TypeDress::dress_with(t, &data, &schema::DressOptions::lenient()) {
+                        Ok(value) => println!("{}", value.to_json()),
+                        Err(e) => {
+                            eprintln!("Invalid data: {}", e);
+                            for cause in ErrorCompat::iter_chain(&e) {
+                                eprintln!("due to: {}", cause);
+                            }
+                        }
+                    }
+                },
                 Some(t) => {
                     let t = t.to_owned();
                     match schema::TypeInclude::include(&t, &data) {
@@ -90,12 +253,272 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             }
             Ok(())
         },
+        Commands::Normalize { json, schema, r#type } => {
+            let entry_path = PathBuf::from(schema);
+            let fios = match parse_file(&entry_path) {
+                Ok(schema) => schema,
+                Err(err) => panic!("Your schema is invalid: {}", err),
+            };
+
+            let schemas = match schema::Schema::from_fios(fios) {
+                Ok(schema) => schema,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    panic!();
+                },
+            };
+
+            let entry_schema = schemas.get(&entry_path).expect("Found entry schema in map of validated schemas");
+            let data = load_json(json)?;
+
+            let target = match entry_schema.types.get(r#type) {
+                Some(t) => t,
+                None => panic!("Could not find the targetted type"),
+            };
+            match js::normalize(target, &data) {
+                Ok(normalized) => println!("{}", normalized),
+                Err(e) => {
+                    eprintln!("Invalid data: {}", e);
+                    for cause in ErrorCompat::iter_chain(&e) {
+                        eprintln!("due to: {}", cause);
+                    }
+                    std::process::exit(1);
+                }
+            }
+            Ok(())
+        },
+        Commands::DiffData { r#type, schema, old, new, key } => {
+            let entry_path = PathBuf::from(schema);
+            let fios = match parse_file(&entry_path) {
+                Ok(schema) => schema,
+                Err(err) => panic!("Your schema is invalid: {}", err),
+            };
+
+            let schemas = match schema::Schema::from_fios(fios) {
+                Ok(schema) => schema,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    panic!();
+                },
+            };
+
+            let entry_schema = schemas.get(&entry_path).expect("Found entry schema in map of validated schemas");
+            let target = match entry_schema.types.get(r#type) {
+                Some(t) => t,
+                None => panic!("Could not find the targetted type"),
+            };
+
+            let options = diff::DataDiffOptions { key: key.clone() };
+            match diff::diff_data(target, &load_json(old)?, &load_json(new)?, &options) {
+                Ok(changes) => {
+                    for change in changes.iter() {
+                        println!("{}", change);
+                    }
+                    if !changes.is_empty() {
+                        std::process::exit(1);
+                    }
+                },
+                Err(e) => {
+                    eprintln!("{}", e);
+                    for cause in ErrorCompat::iter_chain(&e) {
+                        eprintln!("due to: {}", cause);
+                    }
+                    std::process::exit(2);
+                }
+            }
+            Ok(())
+        },
+        Commands::Fmt { schema, check } => {
+            let source = fs::read_to_string(schema)?;
+            let formatted = match fmt::format_source(&source) {
+                Ok(formatted) => formatted,
+                Err(err) => panic!("Your schema is invalid: {}", err),
+            };
+
+            if formatted == source {
+                return Ok(());
+            }
+            if *check {
+                eprintln!("{} is not formatted", schema);
+                std::process::exit(1);
+            }
+            fs::write(schema, formatted)?;
+            Ok(())
+        },
+        Commands::Lint { schema, root, allow } => {
+            let entry_path = PathBuf::from(schema);
+            let fios = match parse_file(&entry_path) {
+                Ok(schema) => schema,
+                Err(err) => panic!("Your schema is invalid: {}", err),
+            };
+            let entry_fio = fios.get(&entry_path).expect("Found entry schema in map of parsed schemas").clone();
+
+            let schemas = match schema::Schema::from_fios(fios) {
+                Ok(schema) => schema,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    panic!();
+                },
+            };
+            let entry_schema = schemas.get(&entry_path).expect("Found entry schema in map of validated schemas");
+
+            let options = lint::LintOptions {
+                allowed: allow.iter().cloned().collect(),
+                roots: root.clone(),
+            };
+            let warnings = lint::lint(&entry_fio, entry_schema, &options);
+            for warning in warnings.iter() {
+                println!("{}:{}", schema, warning);
+            }
+            if !warnings.is_empty() {
+                std::process::exit(1);
+            }
+            Ok(())
+        },
+        Commands::Diff { old, new, require } => {
+            let changes = diff::diff(&load_version(old), &load_version(new));
+            let mut failed = false;
+            for change in changes.iter() {
+                let path = match change.side {
+                    diff::Side::Old => old,
+                    diff::Side::New => new,
+                };
+                println!("{}:{}", path, change);
+                failed |= change.compatibility == diff::Compatibility::Breaking
+                    || !change.compatibility.satisfies(*require);
+            }
+            if failed {
+                std::process::exit(1);
+            }
+            Ok(())
+        },
+        Commands::JsonSchema { schema, root } => {
+            let entry_path = PathBuf::from(schema);
+            let fios = match parse_file(&entry_path) {
+                Ok(schema) => schema,
+                Err(err) => panic!("Your schema is invalid: {}", err),
+            };
+
+            let schemas = match schema::Schema::from_fios(fios) {
+                Ok(schema) => schema,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    panic!();
+                },
+            };
+
+            let entry_schema = schemas.get(&entry_path).expect("Found entry schema in map of validated schemas");
+            let export = jsonschema::export(entry_schema, root.as_deref())?;
+            for untranslated in export.untranslated.iter() {
+                eprintln!("{}:{}", schema, untranslated);
+            }
+            println!("{}", serde_json::to_string_pretty(&export.schema)?);
+            Ok(())
+        },
+        Commands::FromJsonSchema { json, name } => {
+            let document: serde_json::Value = serde_json::from_str(&fs::read_to_string(json)?)?;
+            let imported = jsonschema::import(&document, name);
+            for warning in imported.warnings.iter() {
+                eprintln!("{}{}", json, warning);
+            }
+            print!("{}", imported.to_fio());
+            Ok(())
+        },
+        Commands::Sql { schema, dialect } => {
+            let entry_schema = load_schema(schema);
+            let export = sql::export(&entry_schema, *dialect);
+            for untranslated in export.untranslated.iter() {
+                eprintln!("{}:{}", schema, untranslated);
+            }
+            print!("{}", export.sql);
+            Ok(())
+        },
+        Commands::FromSql { sql: path, nullable } => {
+            let options = sql::Options { nullable: *nullable };
+            let imported = sql::import(&fs::read_to_string(path)?, &options);
+            for warning in imported.warnings.iter() {
+                eprintln!("{}:{}", path, warning);
+            }
+            print!("{}", imported.to_fio());
+            Ok(())
+        },
+        Commands::Graphql { schema } => {
+            let entry_schema = load_schema(schema);
+            let export = graphql::export(&entry_schema);
+            for untranslated in export.untranslated.iter() {
+                eprintln!("{}:{}", schema, untranslated);
+            }
+            print!("{}", export.sdl);
+            Ok(())
+        },
+        Commands::Gen { generator: Generator::Rust { schema, sets } } => {
+            let options = codegen::rust::Options { sets: *sets };
+            let entry_schema = load_schema(schema);
+            let generated = codegen::rust::generate(&entry_schema, &options);
+            for unchecked in generated.unchecked.iter() {
+                eprintln!("{}:{}", schema, unchecked);
+            }
+            print!("{}", generated.code);
+            Ok(())
+        },
+        Commands::Gen { generator: Generator::Python { schema } } => {
+            let entry_schema = load_schema(schema);
+            let generated = codegen::python::generate(&entry_schema);
+            for unchecked in generated.unchecked.iter() {
+                eprintln!("{}:{}", schema, unchecked);
+            }
+            print!("{}", generated.code);
+            Ok(())
+        },
+        Commands::Gen { generator: Generator::Typescript { schema, guards } } => {
+            let options = codegen::typescript::Options { guards: *guards };
+            let entry_schema = load_schema(schema);
+            let generated = codegen::typescript::generate(&entry_schema, &options);
+            for unchecked in generated.unchecked.iter() {
+                eprintln!("{}:{}", schema, unchecked);
+            }
+            print!("{}", generated.code);
+            Ok(())
+        },
     }
 }
 
+fn load_version(schema: &str) -> diff::Version {
+    let entry_path = PathBuf::from(schema);
+    let fios = match parse_file(&entry_path) {
+        Ok(schema) => schema,
+        Err(err) => panic!("Your schema is invalid: {}", err),
+    };
+    let mut schemas = match schema::Schema::from_fios(fios.clone()) {
+        Ok(schema) => schema,
+        Err(e) => {
+            eprintln!("{}", e);
+            panic!();
+        },
+    };
+    let entry_schema = schemas.remove(&entry_path).expect("Found entry schema in map of validated schemas");
+    diff::Version::new(&fios, entry_schema)
+}
+
 pub fn load_json(filename: &String) -> Result<serde_json::Value, Box<dyn Error>> {
     let file = File::open(filename)?;
     let reader = BufReader::new(file);
     let data = serde_json::from_reader(reader)?;
     Ok(data)
 }
+
+fn load_schema(schema: &str) -> schema::Schema {
+    let entry_path = PathBuf::from(schema);
+    let fios = match parse_file(&entry_path) {
+        Ok(schema) => schema,
+        Err(err) => panic!("Your schema is invalid: {}", err),
+    };
+    let mut schemas = match schema::Schema::from_fios(fios) {
+        Ok(schema) => schema,
+        Err(e) => {
+            eprintln!("{}", e);
+            panic!();
+        },
+    };
+    schemas.remove(&entry_path).expect("Found entry schema in map of validated schemas")
+}
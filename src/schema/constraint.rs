@@ -33,9 +33,69 @@ impl Constraint {
     Ok(())
   }
 
+  /// The attributes the expression accesses on its parameter, i.e. the `x`
+  /// in `p.x`, in order of first appearance
+  pub fn attributes(&self) -> Vec<String> {
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+    let chars: Vec<char> = self.expr.chars().collect();
+    let mut attributes: Vec<String> = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+      let c = chars[i];
+      if c == '"' || c == '\'' {
+        // skip string literals, escaped quotes included
+        i += 1;
+        while i < chars.len() && chars[i] != c {
+          i += if chars[i] == '\\' { 2 } else { 1 };
+        }
+        i += 1;
+      } else if is_ident(c) {
+        let start = i;
+        while i < chars.len() && is_ident(chars[i]) {
+          i += 1;
+        }
+        let ident: String = chars[start..i].iter().collect();
+        let after_dot = start > 0 && chars[start - 1] == '.';
+        if ident == self.param && !after_dot && i < chars.len() && chars[i] == '.' {
+          let start = i + 1;
+          let mut end = start;
+          while end < chars.len() && is_ident(chars[end]) {
+            end += 1;
+          }
+          let name: String = chars[start..end].iter().collect();
+          if !name.is_empty() && !attributes.contains(&name) {
+            attributes.push(name);
+          }
+          i = end;
+        }
+      } else {
+        i += 1;
+      }
+    }
+    attributes
+  }
+
 }
 
 pub trait ConstraintExecute<T> {
   fn execute(&self, _: &T) -> Result<bool, Whatever>;
 }
 
+
+#[test]
+fn test_constraint_attributes() {
+  let position = FilePosition { line: 1, column: 1 };
+  let constraint = Constraint::new(
+    "p".to_string(),
+    " p.password == p.confirm && len(p.password) > 3 && p.name != \"p.x\" && q.p.y".to_string(),
+    position,
+  );
+  assert_eq!(constraint.attributes(), vec!["password", "confirm", "name"]);
+
+  let constraint = Constraint::new(
+    "p".to_string(),
+    r#" p.name != "a\"p.x" && p.nick != 'b\'p.y' && p.age > 0"#.to_string(),
+    FilePosition { line: 1, column: 1 },
+  );
+  assert_eq!(constraint.attributes(), vec!["name", "nick", "age"]);
+}
@@ -10,16 +10,76 @@ pub enum ValidationError {
         position: FilePosition,
         identifier: String,
     },
+    DuplicateAttribute {
+        first_seen: FilePosition,
+        position: FilePosition,
+        name: String,
+    },
     NoSuchType {
         position: FilePosition,
         name: String,
     },
+    NoSuchAttribute {
+        position: FilePosition,
+        name: String,
+    },
+    ForeignKeyArity {
+        position: FilePosition,
+        source: usize,
+        target: usize,
+    },
+    /// A foreign key uses an attribute that is not relation-valued
+    NotARelation {
+        position: FilePosition,
+        name: String,
+    },
+    /// The expression of a constraint does not compile
+    InvalidConstraint {
+        position: FilePosition,
+        constraint: String,
+    },
+}
+
+impl ValidationError {
+    pub fn position(&self) -> &FilePosition {
+        match self {
+            ValidationError::DuplicateIdentifier { position, .. } => position,
+            ValidationError::DuplicateAttribute { position, .. } => position,
+            ValidationError::NoSuchType { position, .. } => position,
+            ValidationError::NoSuchAttribute { position, .. } => position,
+            ValidationError::ForeignKeyArity { position, .. } => position,
+            ValidationError::NotARelation { position, .. } => position,
+            ValidationError::InvalidConstraint { position, .. } => position,
+        }
+    }
+
+    /// Describes the error, without its position
+    pub fn message(&self) -> String {
+        match self {
+            ValidationError::DuplicateIdentifier { first_seen, identifier, .. } => format!(
+                "type `{}` is already defined at {}:{}",
+                identifier, first_seen.line, first_seen.column
+            ),
+            ValidationError::DuplicateAttribute { first_seen, name, .. } => format!(
+                "attribute `{}` is already defined at {}:{}",
+                name, first_seen.line, first_seen.column
+            ),
+            ValidationError::NoSuchType { name, .. } => format!("no such type `{}`", name),
+            ValidationError::NoSuchAttribute { name, .. } => format!("no such attribute `{}`", name),
+            ValidationError::ForeignKeyArity { source, target, .. } => format!(
+                "foreign key maps {} attribute(s) to {} attribute(s)",
+                source, target
+            ),
+            ValidationError::NotARelation { name, .. } => format!("attribute `{}` is not a relation", name),
+            ValidationError::InvalidConstraint { constraint, .. } => format!("invalid constraint `{}`", constraint),
+        }
+    }
 }
 
 impl fmt::Display for ValidationError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        // FIXME Replace this by a proper implementation of Display
-        write!(f, "{:?}", self)
+        let position = self.position();
+        write!(f, "{}:{}: {}", position.line, position.column, self.message())
     }
 }
 
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{hash_map::Entry as HashMapEntry, HashMap};
 
 use crate::common::FilePosition;
 use crate::fio;
@@ -10,6 +10,8 @@ use super::typemap::TypeMap;
 #[derive(Clone, Debug)]
 pub struct Heading {
     pub attributes: HashMap<String, Attribute>,
+    /// The type of extra attributes (`...`), None if they are not allowed
+    pub allow_extra: Option<Box<Type>>,
     pub position: FilePosition,
 }
 
@@ -17,34 +19,55 @@ pub struct Heading {
 pub struct Attribute {
     pub name: String,
     pub att_type: Type,
-    pub optional: bool
+    pub optional: bool,
+    /// The `/- meta -/` documenting the attribute
+    pub meta: Option<String>,
 }
 
 impl Heading {
-    pub(crate) fn from_fio(fheading: &fio::Heading) -> Self {
-        let attributes = fheading
-            .attributes
-            .iter()
-            .fold(HashMap::new(), |mut acc, att| {
-                let att_type = Type::from_fio(&att.att_type);
-                let attribute = Attribute {
-                    name: att.name.to_string(),
-                    att_type,
-                    optional: att.optional
-                };
-                acc.entry(att.name.to_string()).or_insert(attribute);
-                acc
-            });
-        Self {
+    /// The heading of a parsed heading, attribute names must be unique
+    pub(crate) fn from_fio(fheading: &fio::Heading) -> Result<Self, ValidationError> {
+        let mut attributes: HashMap<String, Attribute> = HashMap::new();
+        let mut positions: HashMap<&str, &FilePosition> = HashMap::new();
+        for att in fheading.attributes.iter() {
+            match positions.entry(&att.name) {
+                HashMapEntry::Occupied(entry) => {
+                    return Err(ValidationError::DuplicateAttribute {
+                        first_seen: (*entry.get()).clone(),
+                        position: att.position.clone(),
+                        name: att.name.to_owned(),
+                    })
+                }
+                HashMapEntry::Vacant(entry) => {
+                    entry.insert(&att.position);
+                }
+            }
+            let attribute = Attribute {
+                name: att.name.to_string(),
+                att_type: Type::from_fio(&att.att_type)?,
+                optional: att.optional,
+                meta: att.meta.clone(),
+            };
+            attributes.insert(att.name.to_string(), attribute);
+        }
+        let allow_extra = match &fheading.allow_extra {
+            Some(extra) => Some(Box::new(Type::from_fio(&extra.extra_type)?)),
+            None => None,
+        };
+        Ok(Self {
             attributes,
+            allow_extra,
             position: fheading.position.clone(),
-        }
+        })
     }
 
     pub(crate) fn resolve(&mut self, type_map: &TypeMap) -> Result<(), ValidationError> {
         for (_, att) in self.attributes.iter_mut() {
             att.att_type.resolve(type_map)?
         }
+        if let Some(extra) = self.allow_extra.as_mut() {
+            extra.resolve(type_map)?
+        }
         Ok(())
     }
 }
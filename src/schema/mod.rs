@@ -9,15 +9,19 @@ pub mod seq;
 pub mod set;
 pub mod r#struct;
 pub mod sub;
+pub mod subtype;
 pub mod tuple;
 pub mod r#type;
 pub mod typedef;
 pub mod typemap;
 pub mod union;
+pub mod value;
 pub mod heading;
 pub mod constraint;
 
-pub use r#type::{Type, TypeRef, TypeInclude};
+pub use r#type::{Type, TypeRef, TypeInclude, TypeDress, TypeUndress, DressOptions};
+pub use value::{FinitioValue, Attributes};
 pub use schema::Schema;
 pub use typedef::TypeDef;
 pub use constraint::Constraint;
+pub use subtype::{is_subtype, NotSubtype};
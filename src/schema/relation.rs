@@ -7,19 +7,44 @@ use super::heading::Heading;
 #[derive(Clone, Debug)]
 pub struct Relation {
     pub heading: Heading,
+    /// The candidate keys, no two tuples may share the values of a key
+    pub keys: Vec<Vec<String>>,
     pub position: FilePosition,
 }
 
 impl Relation {
-    pub(crate) fn from_fio(ftuple: &fio::RelationType) -> Self {
-        let heading = Heading::from_fio(&ftuple.heading);
-        Self {
+    pub(crate) fn from_fio(ftuple: &fio::RelationType) -> Result<Self, ValidationError> {
+        let heading = Heading::from_fio(&ftuple.heading)?;
+        let keys = ftuple.keys.iter().map(|key| key.attributes.clone()).collect();
+        Ok(Self {
             heading,
+            keys,
             position: ftuple.position.clone(),
+        })
+    }
+
+    /// Ensures that keys only use attributes of the (parsed) relation
+    pub(crate) fn check_fio(frelation: &fio::RelationType) -> Result<(), ValidationError> {
+        for key in frelation.keys.iter() {
+            for name in key.attributes.iter() {
+                if !frelation.heading.attributes.iter().any(|att| &att.name == name) {
+                    return Err(ValidationError::NoSuchAttribute {
+                        position: key.position.clone(),
+                        name: name.clone(),
+                    });
+                }
+            }
         }
+        Ok(())
     }
 
     pub(crate) fn resolve(&mut self, type_map: &TypeMap) -> Result<(), ValidationError> {
         self.heading.resolve(type_map)
     }
 }
+
+/// Whether tuples that satisfy the candidate `keys` also satisfy `key`, i.e.
+/// whether one of the keys only uses attributes of `key`
+pub(crate) fn implies_key(keys: &[Vec<String>], key: &[String]) -> bool {
+    keys.iter().any(|k| k.iter().all(|name| key.contains(name)))
+}
@@ -7,6 +7,7 @@ use super::set::Set;
 use super::sub::Sub;
 use super::tuple::Tuple;
 use super::union::Union;
+use super::r#type::Type;
 use super::{
     builtin::Builtin, errors::ValidationError, r#ref::Ref, typedef::TypeDefStr, typemap::TypeMap,
     TypeDef,
@@ -63,10 +64,13 @@ impl Schema {
                 }
                 //
 
+                Self::check_headings(&typedef.target)?;
+
                 match &typedef.target {
                     fio::Type::NilType(t) => ns.add_type(
                         TypeDef::NilType(TypeDefStr {
                             name: typedef.name.clone(),
+                            meta: typedef.meta.clone(),
                             target: Rc::new(RefCell::new(Nil::from_fio(t))),
                         }),
                         &mut type_map,
@@ -74,6 +78,7 @@ impl Schema {
                     fio::Type::AnyType(t) => ns.add_type(
                         TypeDef::AnyType(TypeDefStr {
                             name: typedef.name.clone(),
+                            meta: typedef.meta.clone(),
                             target: Rc::new(RefCell::new(Any::from_fio(t))),
                         }),
                         &mut type_map,
@@ -81,6 +86,7 @@ impl Schema {
                     fio::Type::BuiltinType(t) => ns.add_type(
                         TypeDef::BuiltinType(TypeDefStr {
                             name: typedef.name.clone(),
+                            meta: typedef.meta.clone(),
                             target: Rc::new(RefCell::new(Builtin::from_fio(t))),
                         }),
                         &mut type_map,
@@ -88,6 +94,7 @@ impl Schema {
                     fio::Type::RefType(t) => ns.add_type(
                         TypeDef::RefType(TypeDefStr {
                             name: typedef.name.clone(),
+                            meta: typedef.meta.clone(),
                             target: Rc::new(RefCell::new(Ref::from_fio(t))),
                         }),
                         &mut type_map,
@@ -95,49 +102,56 @@ impl Schema {
                     fio::Type::SeqType(t) => ns.add_type(
                         TypeDef::SeqType(TypeDefStr {
                             name: typedef.name.clone(),
-                            target: Rc::new(RefCell::new(Seq::from_fio(t))),
+                            meta: typedef.meta.clone(),
+                            target: Rc::new(RefCell::new(Seq::from_fio(t)?)),
                         }),
                         &mut type_map,
                     ),
                     fio::Type::SetType(t) => ns.add_type(
                         TypeDef::SetType(TypeDefStr {
                             name: typedef.name.clone(),
-                            target: Rc::new(RefCell::new(Set::from_fio(t))),
+                            meta: typedef.meta.clone(),
+                            target: Rc::new(RefCell::new(Set::from_fio(t)?)),
                         }),
                         &mut type_map,
                     ),
                     fio::Type::UnionType(t) => ns.add_type(
                         TypeDef::UnionType(TypeDefStr {
                             name: typedef.name.clone(),
-                            target: Rc::new(RefCell::new(Union::from_fio(t))),
+                            meta: typedef.meta.clone(),
+                            target: Rc::new(RefCell::new(Union::from_fio(t)?)),
                         }),
                         &mut type_map,
                     ),
                     fio::Type::StructType(t) => ns.add_type(
                         TypeDef::StructType(TypeDefStr {
                             name: typedef.name.clone(),
-                            target: Rc::new(RefCell::new(Struct::from_fio(t))),
+                            meta: typedef.meta.clone(),
+                            target: Rc::new(RefCell::new(Struct::from_fio(t)?)),
                         }),
                         &mut type_map,
                     ),
                     fio::Type::SubType(t) => ns.add_type(
                         TypeDef::SubType(TypeDefStr {
                             name: typedef.name.clone(),
-                            target: Rc::new(RefCell::new(Sub::from_fio(t))),
+                            meta: typedef.meta.clone(),
+                            target: Rc::new(RefCell::new(Sub::from_fio(t)?)),
                         }),
                         &mut type_map,
                     ),
                     fio::Type::TupleType(t) => ns.add_type(
                         TypeDef::TupleType(TypeDefStr {
                             name: typedef.name.clone(),
-                            target: Rc::new(RefCell::new(Tuple::from_fio(t))),
+                            meta: typedef.meta.clone(),
+                            target: Rc::new(RefCell::new(Tuple::from_fio(t)?)),
                         }),
                         &mut type_map,
                     ),
                     fio::Type::RelationType(t) => ns.add_type(
                         TypeDef::RelationType(TypeDefStr {
                             name: typedef.name.clone(),
-                            target: Rc::new(RefCell::new(Relation::from_fio(t))),
+                            meta: typedef.meta.clone(),
+                            target: Rc::new(RefCell::new(Relation::from_fio(t)?)),
                         }),
                         &mut type_map,
                     ),
@@ -149,6 +163,32 @@ impl Schema {
         Ok(schemas)
     }
 
+    fn check_headings(ftype: &fio::Type) -> Result<(), ValidationError> {
+        let mut result = Ok(());
+        ftype.walk(&mut |t| {
+            if result.is_err() {
+                return;
+            }
+            result = match t {
+                fio::Type::TupleType(t) => Tuple::check_fio(t),
+                fio::Type::RelationType(r) => Relation::check_fio(r),
+                _ => Ok(()),
+            };
+        });
+        result
+    }
+
+    /// Resolves a type against the type definitions of this schema
+    pub fn resolve_type(&self, ftype: &fio::Type) -> Result<Type, ValidationError> {
+        let mut type_map = TypeMap::new();
+        for typedef in self.types.values() {
+            type_map.insert(typedef);
+        }
+        let mut resolved = Type::from_fio(ftype)?;
+        resolved.resolve(&type_map)?;
+        Ok(resolved)
+    }
+
     fn resolve(&mut self, type_map: &TypeMap) -> Result<(), ValidationError> {
         for ud_type in self.types.values_mut() {
             ud_type.resolve(type_map)?;
@@ -161,3 +201,79 @@ impl Schema {
         self.types.insert(type_.name().to_owned(), type_);
     }
 }
+
+#[cfg(test)]
+pub(crate) fn from_source(source: &str) -> Result<HashMap<PathBuf, Schema>, ValidationError> {
+    let fschema = fio::parse_schema(source).unwrap();
+    Schema::from_fios(HashMap::from([(PathBuf::from("test.fio"), fschema)]))
+}
+
+/// The schema of a valid source, panics with the source otherwise
+#[cfg(test)]
+pub(crate) fn test_schema(source: &str) -> Schema {
+    from_source(source)
+        .unwrap_or_else(|e| panic!("{}\n{}", e, source))
+        .remove(&PathBuf::from("test.fio"))
+        .unwrap()
+}
+
+#[test]
+fn test_duplicate_attributes() {
+    let err = from_source("Person = {\n  name: .String\n  name: .Number\n}").unwrap_err();
+    match err {
+        ValidationError::DuplicateAttribute { first_seen, position, name } => {
+            assert_eq!(name, "name");
+            assert_eq!(first_seen, FilePosition { line: 2, column: 3 });
+            assert_eq!(position, FilePosition { line: 3, column: 3 });
+        },
+        e => panic!("Unexpected error: {:?}", e),
+    }
+
+    // Nested headings and relations are checked too
+    let err = from_source("People = [{{ id: .Number, id: .String }}]").unwrap_err();
+    assert!(matches!(err, ValidationError::DuplicateAttribute { .. }));
+
+    // Same attribute in distinct headings is fine
+    assert!(from_source("Person = { name: .String, pet: { name: .String } }").is_ok());
+}
+
+#[test]
+fn test_resolve_type_duplicate_attributes() {
+    let schema = test_schema("Name = .String");
+    let fschema = fio::parse_schema("T = { name: Name, name: .Number }").unwrap();
+    let err = schema.resolve_type(&fschema.type_defs[0].target).unwrap_err();
+    assert_eq!(err.to_string(), "1:19: attribute `name` is already defined at 1:7");
+}
+
+#[test]
+fn test_relation_key_attributes() {
+    let err = from_source("People = {{ id: .Number }} key(id) key(name)").unwrap_err();
+    assert_eq!(err.to_string(), "1:36: no such attribute `name`");
+}
+
+#[test]
+fn test_tuple_foreign_key_attributes() {
+    let err = from_source("T = { a: {{ id: .Number }}, b: {{ id: .Number }} } ref(a(id) -> c(id))").unwrap_err();
+    assert_eq!(err.to_string(), "1:52: no such attribute `c`");
+    let err = from_source("T = { a: {{ id: .Number }}, b: {{ id: .Number }} } ref(a(ref) -> b(id))").unwrap_err();
+    assert_eq!(err.to_string(), "1:52: no such attribute `a.ref`");
+    let err = from_source("T = { a: {{ id: .Number }}, b: {{ id: .Number }} } ref(a(id) -> b(id, id))").unwrap_err();
+    assert_eq!(err.to_string(), "1:52: foreign key maps 1 attribute(s) to 2 attribute(s)");
+
+    // Relations are checked through references
+    let source = "Orders = {{ customer: .Number }}\nIds = Customers\nCustomers = {{ id: .Number }}";
+    let err = from_source(&format!("{}\nT = {{ o: Orders, c: Ids }} ref(o(customer) -> c(ident))", source)).unwrap_err();
+    assert_eq!(err.to_string(), "4:27: no such attribute `c.ident`");
+    assert!(from_source(&format!("{}\nT = {{ o: Orders, c: Ids }} ref(o(customer) -> c(id))", source)).is_ok());
+    let err = from_source("T = { a: {{ id: .Number }}, b: .Number } ref(a(id) -> b(id))").unwrap_err();
+    assert_eq!(err.to_string(), "1:42: attribute `b` is not a relation");
+}
+
+#[test]
+fn test_tuple_constraint_attributes() {
+    let err = from_source("T = { a: .Number, b :? .Number }(t | t.a > 0 && t.c > 0)").unwrap_err();
+    assert_eq!(err.to_string(), "1:34: no such attribute `t.c`");
+    let err = from_source("T = { a: .Number }\nU = T(t | defined(t.b))").unwrap_err();
+    assert_eq!(err.to_string(), "2:7: no such attribute `t.b`");
+    assert!(from_source("T = { a: .Number, ... }(t | defined(t.b))").is_ok());
+}
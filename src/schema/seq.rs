@@ -12,12 +12,12 @@ pub struct Seq {
 }
 
 impl Seq {
-    pub(crate) fn from_fio(fseq: &fio::SeqType) -> Self {
-        let elm_type = Type::from_fio(&fseq.elm_type);
-        Self {
+    pub(crate) fn from_fio(fseq: &fio::SeqType) -> Result<Self, ValidationError> {
+        let elm_type = Type::from_fio(&fseq.elm_type)?;
+        Ok(Self {
             elm_type: Box::new(elm_type),
             position: fseq.position.clone(),
-        }
+        })
     }
 
     pub(crate) fn resolve(&mut self, type_map: &TypeMap) -> Result<(), ValidationError> {
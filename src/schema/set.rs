@@ -12,12 +12,12 @@ pub struct Set {
 }
 
 impl Set {
-    pub(crate) fn from_fio(fset: &fio::SetType) -> Self {
-        let elm_type = Type::from_fio(&fset.elm_type);
-        Self {
+    pub(crate) fn from_fio(fset: &fio::SetType) -> Result<Self, ValidationError> {
+        let elm_type = Type::from_fio(&fset.elm_type)?;
+        Ok(Self {
             elm_type: Box::new(elm_type),
             position: fset.position.clone(),
-        }
+        })
     }
 
     pub(crate) fn resolve(&mut self, type_map: &TypeMap) -> Result<(), ValidationError> {
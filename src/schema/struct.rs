@@ -12,17 +12,17 @@ pub struct Struct {
 }
 
 impl Struct {
-    pub(crate) fn from_fio(fstruct: &fio::StructType) -> Self {
+    pub(crate) fn from_fio(fstruct: &fio::StructType) -> Result<Self, ValidationError> {
         let elements = fstruct
             .elements
             .iter()
             .map(|ftype| Type::from_fio(ftype))
-            .collect();
+            .collect::<Result<_, _>>()?;
 
-        Self {
+        Ok(Self {
             elements: elements,
             position: fstruct.position.clone(),
-        }
+        })
     }
 
     pub(crate) fn resolve(&mut self, type_map: &TypeMap) -> Result<(), ValidationError> {
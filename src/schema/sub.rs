@@ -3,9 +3,14 @@ use crate::fio;
 
 use super::constraint::Constraint;
 use super::errors::ValidationError;
+use super::heading::Heading;
 use super::r#type::Type;
+use super::typedef::TypeDef;
 use super::typemap::TypeMap;
 
+/// Maximum number of references followed to find the heading of a sub type
+const MAX_DEPTH: usize = 64;
+
 #[derive(Clone, Debug)]
 pub struct Sub {
     pub base_type: Box<Type>,
@@ -14,22 +19,66 @@ pub struct Sub {
 }
 
 impl Sub {
-    pub(crate) fn from_fio(fseq: &fio::SubType) -> Self {
-        let base_type = Type::from_fio(&fseq.base);
+    pub(crate) fn from_fio(fseq: &fio::SubType) -> Result<Self, ValidationError> {
+        let base_type = Type::from_fio(&fseq.base)?;
         let constraints: Vec<Constraint> = fseq.constraints.iter().map(|c| {
-            let mut c = Constraint::new(c.param.clone(), c.expr.clone(), c.position.clone());
-            c.compile().unwrap();
-            c
-        }).collect();
-        Self {
+            let mut constraint = Constraint::new(c.param.clone(), c.expr.clone(), c.position.clone());
+            match constraint.compile() {
+                Ok(()) => Ok(constraint),
+                Err(_) => Err(ValidationError::InvalidConstraint {
+                    position: c.position.clone(),
+                    constraint: format!("{} | {}", c.param, c.expr.trim()),
+                }),
+            }
+        }).collect::<Result<_, _>>()?;
+        Ok(Self {
             base_type: Box::new(base_type),
             constraints,
             position: fseq.position.clone(),
-        }
+        })
     }
 
     pub(crate) fn resolve(&mut self, type_map: &TypeMap) -> Result<(), ValidationError> {
         self.base_type.resolve(type_map)?;
+        self.check_attributes(type_map)
+    }
+
+    /// Ensures that constraints on tuples only access attributes of their
+    /// heading (unless extra attributes are allowed)
+    fn check_attributes(&self, type_map: &TypeMap) -> Result<(), ValidationError> {
+        let heading = match base_heading(&self.base_type, type_map, 0) {
+            Some(heading) if heading.allow_extra.is_none() => heading,
+            _ => return Ok(()),
+        };
+        for constraint in self.constraints.iter() {
+            for name in constraint.attributes() {
+                if !heading.attributes.contains_key(&name) {
+                    return Err(ValidationError::NoSuchAttribute {
+                        position: constraint.position.clone(),
+                        name: format!("{}.{}", constraint.param, name),
+                    });
+                }
+            }
+        }
         Ok(())
     }
 }
+
+/// The heading of the tuple type a type is (a sub type of), looked up by name
+/// through references so that it does not depend on the resolution order
+fn base_heading(t: &Type, type_map: &TypeMap, depth: usize) -> Option<Heading> {
+    if depth > MAX_DEPTH {
+        return None;
+    }
+    match t {
+        Type::Tuple(tuple) => Some(tuple.heading.clone()),
+        Type::Sub(sub) => base_heading(&sub.base_type, type_map, depth + 1),
+        Type::Ref(r) => match type_map.get(&r.name().to_string())? {
+            TypeDef::TupleType(t) => t.target.try_borrow().ok().map(|t| t.heading.clone()),
+            TypeDef::SubType(t) => t.target.try_borrow().ok().and_then(|t| base_heading(&t.base_type, type_map, depth + 1)),
+            TypeDef::RefType(t) => t.target.try_borrow().ok().and_then(|t| base_heading(&t.target, type_map, depth + 1)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
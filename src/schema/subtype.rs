@@ -0,0 +1,543 @@
+use std::collections::BTreeSet;
+use std::fmt;
+
+use crate::codegen::{comparisons, Comparison, Kind, Literal};
+
+use super::constraint::Constraint;
+use super::heading::Heading;
+use super::relation::implies_key;
+use super::r#type::{Type, TypeRef};
+use super::sub::Sub;
+
+/// Maximum nesting of types followed when comparing two types, protects
+/// against degenerate (e.g. self-aliasing) type definitions
+const MAX_DEPTH: usize = 64;
+
+/// Explains why a type could not be proven to be a subtype of another one
+#[derive(Clone, Debug, PartialEq)]
+pub struct NotSubtype {
+    /// Where the two types differ: attribute names, `*` for the elements of
+    /// sequences and sets, indexes for struct components
+    pub path: Vec<String>,
+    pub reason: String,
+}
+
+impl NotSubtype {
+    fn new(reason: String) -> Self {
+        Self { path: vec![], reason }
+    }
+
+    fn within(mut self, segment: &str) -> Self {
+        self.path.insert(0, segment.to_string());
+        self
+    }
+}
+
+impl fmt::Display for NotSubtype {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.reason)
+        } else {
+            write!(f, "{}: {}", self.path.join("/"), self.reason)
+        }
+    }
+}
+
+/// Decides whether every value of `a` is also a value of `b`.
+///
+/// The check is structural and conservative: constraints of sub types are
+/// compared textually, unless they compare values (or their length) with
+/// literals, so an error means that the inclusion could not be proven, not
+/// that a counter example exists.
+pub fn is_subtype(a: &Type, b: &Type) -> Result<(), NotSubtype> {
+    Checker::default().check(a, b)
+}
+
+#[derive(Default)]
+struct Checker {
+    /// Pairs of type definitions currently being compared, assumed to be in a
+    /// subtype relation so that recursive definitions terminate
+    assumed: Vec<(usize, usize)>,
+    depth: usize,
+}
+
+fn describe(t: &Type) -> String {
+    match t {
+        Type::Nil(_) => String::from("Nil"),
+        Type::Any(_) => String::from("."),
+        Type::Builtin(b) => format!(".{}", b.target),
+        Type::Ref(_) => String::from("a reference"),
+        Type::Seq(_) => String::from("a sequence"),
+        Type::Set(_) => String::from("a set"),
+        Type::Union(_) => String::from("a union"),
+        Type::Struct(_) => String::from("a struct"),
+        Type::Sub(_) => String::from("a constrained type"),
+        Type::Tuple(_) => String::from("a tuple"),
+        Type::Relation(_) => String::from("a relation"),
+    }
+}
+
+fn deref(r: &TypeRef) -> Result<Type, NotSubtype> {
+    r.target().ok_or_else(|| match r {
+        TypeRef::Unresolved { name, .. } => NotSubtype::new(format!("Unresolved reference to `{}`", name)),
+        _ => NotSubtype::new(String::from("Dangling type reference")),
+    })
+}
+
+fn same_constraints(a: &Sub, b: &Sub) -> bool {
+    a.constraints.len() == b.constraints.len()
+        && a.constraints.iter().zip(b.constraints.iter()).all(|(a, b)| {
+            a.param == b.param && a.expr.trim() == b.expr.trim()
+        })
+}
+
+/// Maximum number of alternatives the constraints of a type are expanded to
+const MAX_ALTERNATIVES: usize = 64;
+
+/// What the values of a type are made of, as far as constraints are concerned
+fn kind(t: &Type, depth: usize) -> Option<Kind> {
+    if depth > MAX_DEPTH {
+        return None;
+    }
+    match t {
+        Type::Builtin(b) if b.target == "Number" => Some(Kind::Number),
+        Type::Builtin(b) if b.target == "String" => Some(Kind::String),
+        Type::Seq(_) | Type::Set(_) => Some(Kind::Collection),
+        Type::Ref(r) => kind(&deref(r).ok()?, depth + 1),
+        Type::Sub(s) => kind(&s.base_type, depth + 1),
+        _ => None,
+    }
+}
+
+/// The constraints values of a type satisfy, those of its base types included
+fn constraints(t: &Type, found: &mut Vec<Constraint>, depth: usize) {
+    if depth > MAX_DEPTH {
+        return;
+    }
+    match t {
+        Type::Ref(r) => {
+            if let Ok(target) = deref(r) {
+                constraints(&target, found, depth + 1);
+            }
+        },
+        Type::Sub(s) => {
+            found.extend(s.constraints.iter().cloned());
+            constraints(&s.base_type, found, depth + 1);
+        },
+        _ => {},
+    }
+}
+
+/// Whether a comparison holds for a value
+fn holds(op: &str, value: &Literal, literal: &Literal) -> bool {
+    match (value, literal) {
+        (Literal::Number(v), Literal::Number(l)) => match op {
+            "<" => v < l,
+            "<=" => v <= l,
+            ">" => v > l,
+            ">=" => v >= l,
+            "==" => v == l,
+            "!=" => v != l,
+            _ => false,
+        },
+        (Literal::String(v), Literal::String(l)) => match op {
+            "==" => v == l,
+            "!=" => v != l,
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Whether every value satisfying `a` satisfies `b`
+fn comparison_implies(a: &Comparison, b: &Comparison) -> bool {
+    if a.subject != b.subject {
+        return false;
+    }
+    let (x, y) = match (&a.literal, &b.literal) {
+        (Literal::Number(x), Literal::Number(y)) => (*x, *y),
+        (Literal::String(_), Literal::String(_)) => {
+            return match a.op {
+                "==" => holds(b.op, &a.literal, &b.literal),
+                _ => a.op == b.op && a.literal == b.literal,
+            }
+        },
+        _ => return false,
+    };
+    match (a.op, b.op) {
+        ("==", _) => holds(b.op, &a.literal, &b.literal),
+        ("<", "<" | "<=") | (">", ">" | ">=") => if a.op == "<" { x <= y } else { x >= y },
+        ("<=", "<=") | (">=", ">=") => if a.op == "<=" { x <= y } else { x >= y },
+        ("<=", "<") => x < y,
+        (">=", ">") => x > y,
+        ("<", "!=") => y >= x,
+        ("<=", "!=") => y > x,
+        (">", "!=") => y <= x,
+        (">=", "!=") => y < x,
+        ("!=", "!=") => x == y,
+        _ => false,
+    }
+}
+
+/// Whether the constraints of `a` (and of its base types) imply those of
+/// `b`, as far as they are comparisons with literals
+fn implies(a: &Type, b: &Sub) -> bool {
+    let kind = match (kind(a, 0), kind(&b.base_type, 0)) {
+        (Some(ka), Some(kb)) if ka == kb => ka,
+        _ => return false,
+    };
+    let mut premises = vec![];
+    constraints(a, &mut premises, 0);
+    // The conjunction of the premises, as alternatives of conjunctions
+    let mut alternatives: Vec<Vec<Comparison>> = vec![vec![]];
+    for premise in premises.iter() {
+        let premise = match comparisons(premise, kind) {
+            Some(premise) => premise,
+            None => continue,
+        };
+        if alternatives.len() * premise.len() > MAX_ALTERNATIVES {
+            return false;
+        }
+        alternatives = alternatives
+            .iter()
+            .flat_map(|conjunction| premise.iter().map(move |parts| [conjunction.clone(), parts.clone()].concat()))
+            .collect();
+    }
+    b.constraints.iter().all(|constraint| match comparisons(constraint, kind) {
+        Some(conclusion) => alternatives.iter().all(|conjunction| {
+            conclusion.iter().any(|parts| parts.iter().all(|b| conjunction.iter().any(|a| comparison_implies(a, b))))
+        }),
+        None => false,
+    })
+}
+
+impl Checker {
+    fn check(&mut self, a: &Type, b: &Type) -> Result<(), NotSubtype> {
+        if self.depth > MAX_DEPTH {
+            return Err(NotSubtype::new(String::from("Types are too deeply nested to be compared")));
+        }
+        self.depth += 1;
+        let result = self.check_types(a, b);
+        self.depth -= 1;
+        result
+    }
+
+    fn check_types(&mut self, a: &Type, b: &Type) -> Result<(), NotSubtype> {
+        match (a, b) {
+            (_, Type::Any(_)) => Ok(()),
+            (Type::Ref(ra), Type::Ref(rb)) => {
+                let pair = match (ra.id(), rb.id()) {
+                    (Some(ia), Some(ib)) => (ia, ib),
+                    _ => return self.check(&deref(ra)?, &deref(rb)?),
+                };
+                if pair.0 == pair.1 || self.assumed.contains(&pair) {
+                    return Ok(());
+                }
+                self.assumed.push(pair);
+                let result = self.check(&deref(ra)?, &deref(rb)?);
+                self.assumed.pop();
+                result
+            },
+            (Type::Ref(ra), Type::Union(ub)) => {
+                // Keep the reference while looking for a candidate, so that
+                // recursive definitions are recognized
+                if ub.candidates.iter().any(|c| self.check(a, c).is_ok()) {
+                    return Ok(());
+                }
+                self.check(&deref(ra)?, b)
+            },
+            (Type::Ref(ra), b) => self.check(&deref(ra)?, b),
+            (a, Type::Ref(rb)) => self.check(a, &deref(rb)?),
+            (Type::Union(ua), b) => {
+                for candidate in ua.candidates.iter() {
+                    self.check(candidate, b)?;
+                }
+                Ok(())
+            },
+            (Type::Sub(sa), b) => {
+                if let Type::Sub(sb) = b {
+                    if same_constraints(sa, sb) {
+                        return self.check(&sa.base_type, &sb.base_type);
+                    }
+                    if implies(a, sb) && self.check(a, &sb.base_type).is_ok() {
+                        return Ok(());
+                    }
+                }
+                // Values of a sub type are values of its base type
+                match self.check(&sa.base_type, b) {
+                    Ok(()) => Ok(()),
+                    Err(err) => match b {
+                        Type::Union(_) => self.check_candidates(a, b),
+                        _ => Err(err),
+                    },
+                }
+            },
+            (a, Type::Union(_)) => self.check_candidates(a, b),
+            (_, Type::Sub(sb)) => {
+                let constraint = sb.constraints
+                    .iter()
+                    .map(|c| format!("{} |{}", c.param, c.expr))
+                    .collect::<Vec<String>>()
+                    .join(", ");
+                Err(NotSubtype::new(format!("Cannot prove that values satisfy the constraint `{}`", constraint)))
+            },
+            (Type::Nil(_), Type::Nil(_)) => Ok(()),
+            (Type::Builtin(ba), Type::Builtin(bb)) if ba.target == bb.target => Ok(()),
+            (Type::Seq(sa), Type::Seq(sb)) => {
+                self.check(&sa.elm_type, &sb.elm_type).map_err(|e| e.within("*"))
+            },
+            (Type::Set(sa), Type::Set(sb)) => {
+                self.check(&sa.elm_type, &sb.elm_type).map_err(|e| e.within("*"))
+            },
+            (Type::Struct(sa), Type::Struct(sb)) => {
+                if sa.elements.len() != sb.elements.len() {
+                    return Err(NotSubtype::new(format!(
+                        "A struct of {} elements is not a struct of {} elements",
+                        sa.elements.len(),
+                        sb.elements.len()
+                    )));
+                }
+                for (index, (ea, eb)) in sa.elements.iter().zip(sb.elements.iter()).enumerate() {
+                    self.check(ea, eb).map_err(|e| e.within(&index.to_string()))?;
+                }
+                Ok(())
+            },
+            (Type::Tuple(ta), Type::Tuple(tb)) => {
+                self.check_heading(&ta.heading, &tb.heading)?;
+                match tb.foreign_keys.iter().find(|fk| !ta.foreign_keys.iter().any(|other| other.same(fk))) {
+                    Some(fk) => Err(NotSubtype::new(format!("Cannot prove that foreign key {} holds", fk))),
+                    None => Ok(()),
+                }
+            },
+            (Type::Relation(ra), Type::Relation(rb)) => {
+                self.check_heading(&ra.heading, &rb.heading)?;
+                match rb.keys.iter().find(|key| !implies_key(&ra.keys, key)) {
+                    Some(key) => Err(NotSubtype::new(format!("Cannot prove that key({}) holds", key.join(", ")))),
+                    None => Ok(()),
+                }
+            },
+            (a, b) => Err(NotSubtype::new(format!("{} is not {}", describe(a), describe(b)))),
+        }
+    }
+
+    fn check_candidates(&mut self, a: &Type, b: &Type) -> Result<(), NotSubtype> {
+        if let Type::Union(ub) = b {
+            if ub.candidates.iter().any(|c| self.check(a, c).is_ok()) {
+                return Ok(());
+            }
+        }
+        Err(NotSubtype::new(format!("{} is not included in any candidate of the union", describe(a))))
+    }
+
+    fn check_heading(&mut self, a: &Heading, b: &Heading) -> Result<(), NotSubtype> {
+        let names: BTreeSet<&String> = a.attributes.keys().chain(b.attributes.keys()).collect();
+        for name in names {
+            let missing = || NotSubtype::new(String::from("Attribute is required but may be missing")).within(name);
+            match (a.attributes.get(name), b.attributes.get(name)) {
+                (Some(aatt), Some(batt)) => {
+                    if aatt.optional && !batt.optional {
+                        return Err(missing());
+                    }
+                    self.check(&aatt.att_type, &batt.att_type).map_err(|e| e.within(name))?;
+                },
+                (Some(aatt), None) => match &b.allow_extra {
+                    Some(extra) => self.check(&aatt.att_type, extra).map_err(|e| e.within(name))?,
+                    None => return Err(NotSubtype::new(String::from("Attribute is not allowed")).within(name)),
+                },
+                (None, Some(batt)) => {
+                    if !batt.optional {
+                        return Err(missing());
+                    }
+                    // The attribute may still be present as an extra one
+                    if let Some(extra) = &a.allow_extra {
+                        self.check(extra, &batt.att_type).map_err(|e| e.within(name))?;
+                    }
+                },
+                (None, None) => {},
+            }
+        }
+        match (&a.allow_extra, &b.allow_extra) {
+            (Some(aextra), Some(bextra)) => self.check(aextra, bextra).map_err(|e| e.within("...")),
+            (Some(_), None) => Err(NotSubtype::new(String::from("Extra attributes are not allowed"))),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+use crate::{common::FilePosition, fio};
+
+#[cfg(test)]
+fn check(source: &str, a: &str, b: &str) -> Result<(), String> {
+    let schema = super::schema::test_schema(source);
+    let resolve = |name: &str| {
+        let fref = fio::Type::RefType(fio::RefType {
+            name: name.to_string(),
+            position: FilePosition { line: 1, column: 1 },
+        });
+        schema.resolve_type(&fref).unwrap()
+    };
+    is_subtype(&resolve(a), &resolve(b)).map_err(|e| e.to_string())
+}
+
+#[test]
+fn test_is_subtype_scalars() {
+    let source = r#"
+Number = .Number
+String = .String
+PosNumber = Number(n | n > 0)
+Positive = Number(x | x > 0)
+Small = PosNumber(n | n < 10)
+Below10 = Number(n | n < 10)
+Below100 = Number(n | n <= 100 && n != 100)
+Tiny = Number(n | n == 1 || n == 2)
+Color = String(s | s == "red" || s == "blue")
+NotBlack = String(s | s != "black")
+Even = Number(n | n % 2 == 0 && n > 0 || n < 0)
+Even2 = Number(m | m % 2 == 0 && m > 0 || m < 0)
+Scalar = Number | String
+Maybe = Nil | PosNumber
+Any = .
+"#;
+    assert_eq!(check(source, "Number", "Number"), Ok(()));
+    assert_eq!(check(source, "PosNumber", "Number"), Ok(()));
+    assert_eq!(check(source, "Small", "PosNumber"), Ok(()));
+    assert_eq!(check(source, "Small", "Number"), Ok(()));
+    assert_eq!(check(source, "Number", "Scalar"), Ok(()));
+    assert_eq!(check(source, "PosNumber", "Scalar"), Ok(()));
+    assert_eq!(check(source, "Small", "Maybe"), Ok(()));
+    assert_eq!(check(source, "Scalar", "Any"), Ok(()));
+    assert_eq!(check(source, "String", "Number"), Err(String::from(".String is not .Number")));
+    assert_eq!(
+        check(source, "Number", "PosNumber"),
+        Err(String::from("Cannot prove that values satisfy the constraint `n | n > 0`"))
+    );
+    // Comparisons with literals are compared semantically
+    assert_eq!(check(source, "PosNumber", "Positive"), Ok(()));
+    assert_eq!(check(source, "Small", "Positive"), Ok(()));
+    assert_eq!(check(source, "Below10", "Below100"), Ok(()));
+    assert!(check(source, "Below100", "Below10").is_err());
+    assert_eq!(check(source, "Tiny", "Below10"), Ok(()));
+    assert_eq!(check(source, "Color", "NotBlack"), Ok(()));
+    assert!(check(source, "NotBlack", "Color").is_err());
+    // Other constraints, e.g. mixing `&&` and `||`, are compared textually
+    assert!(check(source, "Even", "Even2").is_err());
+    assert_eq!(
+        check(source, "Scalar", "Number"),
+        Err(String::from(".String is not .Number"))
+    );
+    assert_eq!(
+        check(source, "Any", "Scalar"),
+        Err(String::from(". is not included in any candidate of the union"))
+    );
+}
+
+#[test]
+fn test_is_subtype_collections() {
+    let source = "
+Number = .Number
+PosNumber = Number(n | n > 0)
+Numbers = [Number]
+PosNumbers = [PosNumber]
+NumberSet = {Number}
+Pair = <Number, Number>
+PosPair = <PosNumber, PosNumber>
+Triple = <Number, Number, Number>
+";
+    assert_eq!(check(source, "PosNumbers", "Numbers"), Ok(()));
+    assert_eq!(
+        check(source, "Numbers", "PosNumbers"),
+        Err(String::from("*: Cannot prove that values satisfy the constraint `n | n > 0`"))
+    );
+    assert_eq!(check(source, "Numbers", "NumberSet"), Err(String::from("a sequence is not a set")));
+    assert_eq!(check(source, "PosPair", "Pair"), Ok(()));
+    assert_eq!(
+        check(source, "Pair", "Triple"),
+        Err(String::from("A struct of 2 elements is not a struct of 3 elements"))
+    );
+}
+
+#[test]
+fn test_is_subtype_headings() {
+    let source = "
+String = .String
+Number = .Number
+Person = { name: String, age :? Number }
+NewPerson = { name: String }
+Named = { name: String, ... }
+Strings = { name: String, ...: String }
+Adult = { name: String, age: Number }
+People = {{ name: String, age :? Number }}
+NewPeople = {{ name: String }}
+";
+    assert_eq!(check(source, "NewPerson", "Person"), Ok(()));
+    assert_eq!(check(source, "Adult", "Person"), Ok(()));
+    assert_eq!(check(source, "Person", "Named"), Ok(()));
+    assert_eq!(check(source, "NewPeople", "People"), Ok(()));
+    assert_eq!(
+        check(source, "Person", "Adult"),
+        Err(String::from("age: Attribute is required but may be missing"))
+    );
+    assert_eq!(
+        check(source, "Person", "NewPerson"),
+        Err(String::from("age: Attribute is not allowed"))
+    );
+    assert_eq!(
+        check(source, "Person", "Strings"),
+        Err(String::from("age: .Number is not .String"))
+    );
+    assert_eq!(check(source, "Named", "NewPerson"), Err(String::from("Extra attributes are not allowed")));
+    // `age` may be an extra attribute of any type in Named
+    assert_eq!(check(source, "Named", "Person"), Err(String::from("age: . is not .Number")));
+    assert_eq!(check(source, "People", "Person"), Err(String::from("a relation is not a tuple")));
+}
+
+#[test]
+fn test_is_subtype_keys() {
+    let source = "
+String = .String
+Number = .Number
+People = {{ id: Number, name: String }}
+ById = {{ id: Number, name: String }} key(id)
+ByIdName = {{ id: Number, name: String }} key(id, name)
+ByName = {{ id: Number, name: String }} key(name)
+";
+    assert_eq!(check(source, "ById", "People"), Ok(()));
+    assert_eq!(check(source, "ById", "ByIdName"), Ok(()));
+    assert_eq!(check(source, "ById", "ById"), Ok(()));
+    assert_eq!(check(source, "People", "ById"), Err(String::from("Cannot prove that key(id) holds")));
+    assert_eq!(check(source, "ByIdName", "ById"), Err(String::from("Cannot prove that key(id) holds")));
+    assert_eq!(check(source, "ByName", "ById"), Err(String::from("Cannot prove that key(id) holds")));
+}
+
+#[test]
+fn test_is_subtype_foreign_keys() {
+    let source = "
+Number = .Number
+Ids = {{ id: Number, other: Number }}
+Free = { a: Ids, b: Ids }
+Linked = { a: Ids, b: Ids } ref(a(id) -> b(id))
+Pairs = { a: Ids, b: Ids } ref(a(id, other) -> b(id, other))
+Swapped = { a: Ids, b: Ids } ref(a(other, id) -> b(other, id))
+";
+    assert_eq!(check(source, "Linked", "Free"), Ok(()));
+    assert_eq!(check(source, "Pairs", "Swapped"), Ok(()));
+    assert_eq!(
+        check(source, "Free", "Linked"),
+        Err(String::from("Cannot prove that foreign key a(id) -> b(id) holds"))
+    );
+    assert!(check(source, "Pairs", "Linked").is_err());
+}
+
+#[test]
+fn test_is_subtype_recursive() {
+    let source = "
+Number = .Number
+PosNumber = Number(n | n > 0)
+List = { head: Number, tail: Nil | List }
+PosList = { head: PosNumber, tail: Nil | PosList }
+";
+    assert_eq!(check(source, "PosList", "List"), Ok(()));
+    assert_eq!(check(source, "List", "List"), Ok(()));
+    assert!(check(source, "List", "PosList").is_err());
+}
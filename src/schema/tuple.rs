@@ -1,26 +1,161 @@
+use std::fmt;
+
 use crate::common::FilePosition;
 use crate::fio;
 
 use super::errors::ValidationError;
 use super::heading::Heading;
+use super::r#type::Type;
+use super::typedef::TypeDef;
 use super::typemap::TypeMap;
 
+const MAX_DEPTH: usize = 64;
+
 #[derive(Clone, Debug)]
 pub struct Tuple {
     pub heading: Heading,
+    pub foreign_keys: Vec<ForeignKey>,
+    pub position: FilePosition,
+}
+
+/// Every tuple of the `source` relation-valued attribute must match a tuple
+/// of the `target` one on the given attributes (pairwise)
+#[derive(Clone, Debug, PartialEq)]
+pub struct ForeignKey {
+    pub source: String,
+    pub source_attributes: Vec<String>,
+    pub target: String,
+    pub target_attributes: Vec<String>,
     pub position: FilePosition,
 }
 
+impl ForeignKey {
+    /// Whether both foreign keys constrain the same attributes in the same way,
+    /// whatever the order of the attribute pairs
+    pub fn same(&self, other: &ForeignKey) -> bool {
+        let pairs = |fk: &ForeignKey| -> Vec<(String, String)> {
+            let mut pairs: Vec<(String, String)> = fk.source_attributes
+                .iter()
+                .cloned()
+                .zip(fk.target_attributes.iter().cloned())
+                .collect();
+            pairs.sort();
+            pairs
+        };
+        self.source == other.source && self.target == other.target && pairs(self) == pairs(other)
+    }
+}
+
+impl fmt::Display for ForeignKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}({}) -> {}({})",
+            self.source,
+            self.source_attributes.join(", "),
+            self.target,
+            self.target_attributes.join(", ")
+        )
+    }
+}
+
 impl Tuple {
-    pub(crate) fn from_fio(ftuple: &fio::TupleType) -> Self {
-        let heading = Heading::from_fio(&ftuple.heading);
-        Self {
+    pub(crate) fn from_fio(ftuple: &fio::TupleType) -> Result<Self, ValidationError> {
+        let heading = Heading::from_fio(&ftuple.heading)?;
+        let foreign_keys = ftuple.foreign_keys
+            .iter()
+            .map(|fk| ForeignKey {
+                source: fk.source.clone(),
+                source_attributes: fk.source_attributes.clone(),
+                target: fk.target.clone(),
+                target_attributes: fk.target_attributes.clone(),
+                position: fk.position.clone(),
+            })
+            .collect();
+        Ok(Self {
             heading,
+            foreign_keys,
             position: ftuple.position.clone(),
+        })
+    }
+
+    /// Ensures that foreign keys use attributes of the (parsed) tuple, the
+    /// attributes of the relations are checked once resolved
+    pub(crate) fn check_fio(ftuple: &fio::TupleType) -> Result<(), ValidationError> {
+        for fk in ftuple.foreign_keys.iter() {
+            if fk.source_attributes.len() != fk.target_attributes.len() {
+                return Err(ValidationError::ForeignKeyArity {
+                    position: fk.position.clone(),
+                    source: fk.source_attributes.len(),
+                    target: fk.target_attributes.len(),
+                });
+            }
+            for relation in [&fk.source, &fk.target] {
+                if !ftuple.heading.attributes.iter().any(|att| &att.name == relation) {
+                    return Err(ValidationError::NoSuchAttribute {
+                        position: fk.position.clone(),
+                        name: relation.clone(),
+                    });
+                }
+            }
         }
+        Ok(())
     }
 
     pub(crate) fn resolve(&mut self, type_map: &TypeMap) -> Result<(), ValidationError> {
-        self.heading.resolve(type_map)
+        self.heading.resolve(type_map)?;
+        self.check_foreign_key_attributes(type_map)
+    }
+
+    /// Ensures that foreign keys relate relation-valued attributes, whether
+    /// their relation types are defined inline or referenced, and use their
+    /// attributes
+    fn check_foreign_key_attributes(&self, type_map: &TypeMap) -> Result<(), ValidationError> {
+        for fk in self.foreign_keys.iter() {
+            for (relation, names) in [(&fk.source, &fk.source_attributes), (&fk.target, &fk.target_attributes)] {
+                let attribute = match self.heading.attributes.get(relation) {
+                    Some(attribute) => attribute,
+                    None => return Err(ValidationError::NoSuchAttribute {
+                        position: fk.position.clone(),
+                        name: relation.clone(),
+                    }),
+                };
+                let heading = match relation_heading(&attribute.att_type, type_map, 0) {
+                    Some(heading) => heading,
+                    None => return Err(ValidationError::NotARelation {
+                        position: fk.position.clone(),
+                        name: relation.clone(),
+                    }),
+                };
+                for name in names.iter() {
+                    if !heading.attributes.contains_key(name) {
+                        return Err(ValidationError::NoSuchAttribute {
+                            position: fk.position.clone(),
+                            name: format!("{}.{}", relation, name),
+                        });
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The heading of the relation type a type is (a sub type of), looked up by
+/// name through references so that it does not depend on the resolution order
+fn relation_heading(t: &Type, type_map: &TypeMap, depth: usize) -> Option<Heading> {
+    if depth > MAX_DEPTH {
+        return None;
+    }
+    match t {
+        Type::Relation(relation) => Some(relation.heading.clone()),
+        Type::Sub(sub) => relation_heading(&sub.base_type, type_map, depth + 1),
+        Type::Ref(r) => match type_map.get(&r.name().to_string())? {
+            TypeDef::RelationType(t) => t.target.try_borrow().ok().map(|t| t.heading.clone()),
+            TypeDef::SubType(t) => t.target.try_borrow().ok().and_then(|t| relation_heading(&t.base_type, type_map, depth + 1)),
+            TypeDef::RefType(t) => t.target.try_borrow().ok().and_then(|t| relation_heading(&t.target, type_map, depth + 1)),
+            _ => None,
+        },
+        _ => None,
     }
 }
@@ -18,12 +18,50 @@ use super::sub::Sub;
 use super::tuple::Tuple;
 use super::typedef::TypeDef;
 use super::union::Union;
+use super::value::FinitioValue;
 use super::{errors::ValidationError, typemap::TypeMap};
 
 pub trait TypeInclude<T> {
     fn include(&self, _: &T) -> Result<(), Whatever>;
 }
 
+/// Dressing accepts the same data as `TypeInclude` and returns it as a typed
+/// value
+pub trait TypeDress<T> {
+    fn dress_with(&self, _: &T, _: &DressOptions) -> Result<FinitioValue, Whatever>;
+
+    /// Dresses in strict mode
+    fn dress(&self, v: &T) -> Result<FinitioValue, Whatever> {
+        self.dress_with(v, &DressOptions::default())
+    }
+}
+
+/// Undressing is the inverse of dressing: it encodes a typed value back into
+/// data accepted by the type
+pub trait TypeUndress<T> {
+    fn undress(&self, _: &FinitioValue) -> Result<T, Whatever>;
+}
+
+/// How data is dressed, strict by default
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DressOptions {
+    /// Accept the canonical string encodings of numbers, booleans and dates,
+    /// for data coming from CSV files, query strings, environment variables...
+    pub coerce: bool,
+    /// The strings accepted as Nil when coercing
+    pub nil_strings: Vec<String>,
+}
+
+impl DressOptions {
+    /// Coerces strings, with `""` and `"null"` standing for Nil
+    pub fn lenient() -> Self {
+        Self {
+            coerce: true,
+            nil_strings: vec![String::new(), String::from("null")],
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum Type {
     Nil(Nil),
@@ -61,58 +99,87 @@ pub enum TypeRef {
 #[derive(Clone, Debug)]
 pub struct AnyRef {
     pub any_: Weak<RefCell<Any>>,
+    pub name: String,
 }
 
 #[derive(Clone, Debug)]
 pub struct NilRef {
     pub nil_: Weak<RefCell<Nil>>,
+    pub name: String,
 }
 
 #[derive(Clone, Debug)]
 pub struct BuiltinRef {
     pub builtin_: Weak<RefCell<Builtin>>,
+    pub name: String,
 }
 
 #[derive(Clone, Debug)]
 pub struct RefRef {
     pub ref_: Weak<RefCell<Ref>>,
+    pub name: String,
 }
 
 #[derive(Clone, Debug)]
 pub struct SeqRef {
     pub seq_: Weak<RefCell<Seq>>,
+    pub name: String,
 }
 
 #[derive(Clone, Debug)]
 pub struct SetRef {
     pub set_: Weak<RefCell<Set>>,
+    pub name: String,
 }
 #[derive(Clone, Debug)]
 pub struct UnionRef {
     pub union_: Weak<RefCell<Union>>,
+    pub name: String,
 }
 
 #[derive(Clone, Debug)]
 pub struct StructRef {
     pub struct_: Weak<RefCell<Struct>>,
+    pub name: String,
 }
 
 #[derive(Clone, Debug)]
 pub struct SubRef {
     pub sub_: Weak<RefCell<Sub>>,
+    pub name: String,
 }
 
 #[derive(Clone, Debug)]
 pub struct TupleRef {
     pub tuple_: Weak<RefCell<Tuple>>,
+    pub name: String,
 }
 
 #[derive(Clone, Debug)]
 pub struct RelationRef {
     pub relation_: Weak<RefCell<Relation>>,
+    pub name: String,
 }
 
 impl TypeRef {
+    /// The name of the referenced type definition
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Any(r) => &r.name,
+            Self::Nil(r) => &r.name,
+            Self::Builtin(r) => &r.name,
+            Self::Ref(r) => &r.name,
+            Self::Seq(r) => &r.name,
+            Self::Set(r) => &r.name,
+            Self::Union(r) => &r.name,
+            Self::Struct(r) => &r.name,
+            Self::Sub(r) => &r.name,
+            Self::Tuple(r) => &r.name,
+            Self::Relation(r) => &r.name,
+            Self::Unresolved { name, .. } => name,
+        }
+    }
+
     pub fn position(&self) -> FilePosition {
         match self {
             Self::Any(r) => r.any_.upgrade().unwrap().borrow().position.clone(),
@@ -132,6 +199,45 @@ impl TypeRef {
             } => position.clone(),
         }
     }
+    /// Returns (a copy of) the type this reference points to, or None if the
+    /// reference has not been resolved
+    pub fn target(&self) -> Option<Type> {
+        match self {
+            Self::Any(r) => r.any_.upgrade().map(|t| Type::Any(t.borrow().clone())),
+            Self::Nil(r) => r.nil_.upgrade().map(|t| Type::Nil(t.borrow().clone())),
+            Self::Builtin(r) => r.builtin_.upgrade().map(|t| Type::Builtin(t.borrow().clone())),
+            Self::Ref(r) => r.ref_.upgrade().map(|t| t.borrow().target.clone()),
+            Self::Seq(r) => r.seq_.upgrade().map(|t| Type::Seq(t.borrow().clone())),
+            Self::Set(r) => r.set_.upgrade().map(|t| Type::Set(t.borrow().clone())),
+            Self::Union(r) => r.union_.upgrade().map(|t| Type::Union(t.borrow().clone())),
+            Self::Struct(r) => r.struct_.upgrade().map(|t| Type::Struct(t.borrow().clone())),
+            Self::Sub(r) => r.sub_.upgrade().map(|t| Type::Sub(t.borrow().clone())),
+            Self::Tuple(r) => r.tuple_.upgrade().map(|t| Type::Tuple(t.borrow().clone())),
+            Self::Relation(r) => r.relation_.upgrade().map(|t| Type::Relation(t.borrow().clone())),
+            Self::Unresolved { .. } => None,
+        }
+    }
+
+    /// Identifies the type definition this reference points to, None if the
+    /// reference has not been resolved
+    pub(crate) fn id(&self) -> Option<usize> {
+        let ptr = match self {
+            Self::Any(r) => r.any_.as_ptr() as *const (),
+            Self::Nil(r) => r.nil_.as_ptr() as *const (),
+            Self::Builtin(r) => r.builtin_.as_ptr() as *const (),
+            Self::Ref(r) => r.ref_.as_ptr() as *const (),
+            Self::Seq(r) => r.seq_.as_ptr() as *const (),
+            Self::Set(r) => r.set_.as_ptr() as *const (),
+            Self::Union(r) => r.union_.as_ptr() as *const (),
+            Self::Struct(r) => r.struct_.as_ptr() as *const (),
+            Self::Sub(r) => r.sub_.as_ptr() as *const (),
+            Self::Tuple(r) => r.tuple_.as_ptr() as *const (),
+            Self::Relation(r) => r.relation_.as_ptr() as *const (),
+            Self::Unresolved { .. } => return None,
+        };
+        Some(ptr as usize)
+    }
+
     pub(crate) fn resolve(&mut self, type_map: &TypeMap) -> Result<(), ValidationError> {
         if let Self::Unresolved {
             name,
@@ -143,36 +249,47 @@ impl TypeRef {
                 Some(udtype) => match udtype {
                     TypeDef::AnyType(any_) => TypeRef::Any(AnyRef {
                         any_: Rc::downgrade(&any_.target),
+                        name: name.clone(),
                     }),
                     TypeDef::NilType(nil_) => TypeRef::Nil(NilRef {
                         nil_: Rc::downgrade(&nil_.target),
+                        name: name.clone(),
                     }),
                     TypeDef::BuiltinType(builtin_) => TypeRef::Builtin(BuiltinRef {
                         builtin_: Rc::downgrade(&builtin_.target),
+                        name: name.clone(),
                     }),
                     TypeDef::RefType(ref_) => TypeRef::Ref(RefRef {
                         ref_: Rc::downgrade(&ref_.target),
+                        name: name.clone(),
                     }),
                     TypeDef::SeqType(seq_) => TypeRef::Seq(SeqRef {
                         seq_: Rc::downgrade(&seq_.target),
+                        name: name.clone(),
                     }),
                     TypeDef::SetType(set_) => TypeRef::Set(SetRef {
                         set_: Rc::downgrade(&set_.target),
+                        name: name.clone(),
                     }),
                     TypeDef::UnionType(union_) => TypeRef::Union(UnionRef {
                         union_: Rc::downgrade(&union_.target),
+                        name: name.clone(),
                     }),
                     TypeDef::StructType(struct_) => TypeRef::Struct(StructRef {
                         struct_: Rc::downgrade(&struct_.target),
+                        name: name.clone(),
                     }),
                     TypeDef::SubType(sub_) => TypeRef::Sub(SubRef {
                         sub_: Rc::downgrade(&sub_.target),
+                        name: name.clone(),
                     }),
                     TypeDef::TupleType(tuple_) => TypeRef::Tuple(TupleRef {
                         tuple_: Rc::downgrade(&tuple_.target),
+                        name: name.clone(),
                     }),
                     TypeDef::RelationType(relation_) => TypeRef::Relation(RelationRef {
                         relation_: Rc::downgrade(&relation_.target),
+                        name: name.clone(),
                     }),
                 },
                 None => {
@@ -188,8 +305,9 @@ impl TypeRef {
 }
 
 impl Type {
-    pub fn from_fio(ftype: &fio::Type) -> Self {
-        match ftype {
+    /// The type of a parsed type, references are left unresolved
+    pub fn from_fio(ftype: &fio::Type) -> Result<Self, ValidationError> {
+        Ok(match ftype {
             fio::Type::NilType(t) => Self::Nil(Nil::from_fio(t)),
             fio::Type::AnyType(t) => Self::Any(Any::from_fio(t)),
             fio::Type::BuiltinType(t) => Self::Builtin(Builtin::from_fio(t)),
@@ -197,14 +315,14 @@ impl Type {
                 name: t.name.clone(),
                 position: t.position.clone(),
             }),
-            fio::Type::SeqType(t) => Self::Seq(Seq::from_fio(t)),
-            fio::Type::SetType(t) => Self::Set(Set::from_fio(t)),
-            fio::Type::UnionType(t) => Self::Union(Union::from_fio(t)),
-            fio::Type::StructType(t) => Self::Struct(Struct::from_fio(t)),
-            fio::Type::SubType(t) => Self::Sub(Sub::from_fio(t)),
-            fio::Type::TupleType(t) => Self::Tuple(Tuple::from_fio(t)),
-            fio::Type::RelationType(t) => Self::Relation(Relation::from_fio(t)),
-        }
+            fio::Type::SeqType(t) => Self::Seq(Seq::from_fio(t)?),
+            fio::Type::SetType(t) => Self::Set(Set::from_fio(t)?),
+            fio::Type::UnionType(t) => Self::Union(Union::from_fio(t)?),
+            fio::Type::StructType(t) => Self::Struct(Struct::from_fio(t)?),
+            fio::Type::SubType(t) => Self::Sub(Sub::from_fio(t)?),
+            fio::Type::TupleType(t) => Self::Tuple(Tuple::from_fio(t)?),
+            fio::Type::RelationType(t) => Self::Relation(Relation::from_fio(t)?),
+        })
     }
 
     pub fn from_fio_ref(fref: &fio::RefType) -> Self {
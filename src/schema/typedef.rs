@@ -12,6 +12,7 @@ use super::set::Set;
 use super::sub::Sub;
 use super::tuple::Tuple;
 use super::union::Union;
+use super::r#type::Type;
 use super::{errors::ValidationError, typemap::TypeMap};
 
 #[derive(Clone, Debug)]
@@ -32,10 +33,28 @@ pub enum TypeDef {
 #[derive(Clone, Debug)]
 pub struct TypeDefStr<T> {
     pub name: String,
+    /// The `/- meta -/` documenting the definition
+    pub meta: Option<String>,
     pub target: Rc<RefCell<T>>,
 }
 
 impl TypeDef {
+    pub fn meta(&self) -> Option<&str> {
+        match self {
+            TypeDef::AnyType(t) => t.meta.as_deref(),
+            TypeDef::NilType(t) => t.meta.as_deref(),
+            TypeDef::BuiltinType(t) => t.meta.as_deref(),
+            TypeDef::RefType(t) => t.meta.as_deref(),
+            TypeDef::SeqType(t) => t.meta.as_deref(),
+            TypeDef::SetType(t) => t.meta.as_deref(),
+            TypeDef::UnionType(t) => t.meta.as_deref(),
+            TypeDef::StructType(t) => t.meta.as_deref(),
+            TypeDef::SubType(t) => t.meta.as_deref(),
+            TypeDef::TupleType(t) => t.meta.as_deref(),
+            TypeDef::RelationType(t) => t.meta.as_deref(),
+        }
+    }
+
     pub fn name(&self) -> String {
         match self {
             TypeDef::AnyType(t) => t.name.clone(),
@@ -52,6 +71,23 @@ impl TypeDef {
         }
     }
 
+    /// A copy of the defined type, references of a `RefType` are kept as is
+    pub fn target_type(&self) -> Type {
+        match self {
+            TypeDef::AnyType(t) => Type::Any(t.target.borrow().clone()),
+            TypeDef::NilType(t) => Type::Nil(t.target.borrow().clone()),
+            TypeDef::BuiltinType(t) => Type::Builtin(t.target.borrow().clone()),
+            TypeDef::RefType(t) => t.target.borrow().target.clone(),
+            TypeDef::SeqType(t) => Type::Seq(t.target.borrow().clone()),
+            TypeDef::SetType(t) => Type::Set(t.target.borrow().clone()),
+            TypeDef::UnionType(t) => Type::Union(t.target.borrow().clone()),
+            TypeDef::StructType(t) => Type::Struct(t.target.borrow().clone()),
+            TypeDef::SubType(t) => Type::Sub(t.target.borrow().clone()),
+            TypeDef::TupleType(t) => Type::Tuple(t.target.borrow().clone()),
+            TypeDef::RelationType(t) => Type::Relation(t.target.borrow().clone()),
+        }
+    }
+
     pub(crate) fn resolve(&mut self, type_map: &TypeMap) -> Result<(), ValidationError> {
         match self {
             TypeDef::AnyType(t) => t.target.borrow_mut().resolve(type_map),
@@ -12,17 +12,17 @@ pub struct Union {
 }
 
 impl Union {
-    pub(crate) fn from_fio(funion: &fio::UnionType) -> Self {
+    pub(crate) fn from_fio(funion: &fio::UnionType) -> Result<Self, ValidationError> {
         let candidates = funion
             .candidates
             .iter()
             .map(|ftype| Type::from_fio(ftype))
-            .collect();
+            .collect::<Result<_, _>>()?;
 
-        Self {
+        Ok(Self {
             candidates: candidates,
             position: funion.position.clone(),
-        }
+        })
     }
 
     pub(crate) fn resolve(&mut self, type_map: &TypeMap) -> Result<(), ValidationError> {
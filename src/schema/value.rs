@@ -0,0 +1,90 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, FixedOffset, NaiveDate, SecondsFormat};
+
+/// The attributes of a dressed tuple, by name
+pub type Attributes = BTreeMap<String, FinitioValue>;
+
+/// A value dressed by a finitio type: the structure of the raw data is
+/// replaced by the semantics of the type that accepted it
+#[derive(Clone, Debug, PartialEq)]
+pub enum FinitioValue {
+    Nil,
+    /// A value accepted by `.`, kept as is
+    Any(serde_json::Value),
+    Number(serde_json::Number),
+    String(String),
+    Boolean(bool),
+    Date(NaiveDate),
+    DateTime(DateTime<FixedOffset>),
+    Seq(Vec<FinitioValue>),
+    /// The elements of a set, in their original order. Dressing an array
+    /// with duplicated elements fails, as `include` does, instead of
+    /// deduplicating it
+    Set(Vec<FinitioValue>),
+    Struct(Vec<FinitioValue>),
+    Tuple(Attributes),
+    /// The tuples of a relation, in their original order. As for sets,
+    /// duplicated tuples are rejected rather than deduplicated
+    Relation(Vec<Attributes>),
+    /// A value accepted by the candidate at index `candidate` of a union
+    Union {
+        candidate: usize,
+        value: Box<FinitioValue>,
+    },
+    /// A value accepted by the type definition `name`
+    Named {
+        name: String,
+        value: Box<FinitioValue>,
+    },
+}
+
+impl FinitioValue {
+    /// The name of the type definition that accepted the value, if any
+    pub fn type_name(&self) -> Option<&str> {
+        match self {
+            FinitioValue::Named { name, .. } => Some(name),
+            _ => None,
+        }
+    }
+
+    /// The value without the names and union candidates it is tagged with
+    pub fn untagged(&self) -> &FinitioValue {
+        match self {
+            FinitioValue::Named { value, .. } | FinitioValue::Union { value, .. } => value.untagged(),
+            value => value,
+        }
+    }
+
+    /// The value without the type names it is tagged with
+    pub fn unnamed(&self) -> &FinitioValue {
+        match self {
+            FinitioValue::Named { value, .. } => value.unnamed(),
+            value => value,
+        }
+    }
+
+    /// The plain JSON encoding of the value, dates as ISO 8601 strings
+    pub fn to_json(&self) -> serde_json::Value {
+        use serde_json::Value;
+
+        let object = |attributes: &Attributes| {
+            Value::Object(attributes.iter().map(|(name, value)| (name.clone(), value.to_json())).collect())
+        };
+        match self {
+            FinitioValue::Nil => Value::Null,
+            FinitioValue::Any(value) => value.clone(),
+            FinitioValue::Number(n) => Value::Number(n.clone()),
+            FinitioValue::String(s) => Value::String(s.clone()),
+            FinitioValue::Boolean(b) => Value::Bool(*b),
+            FinitioValue::Date(d) => Value::String(d.format("%Y-%m-%d").to_string()),
+            FinitioValue::DateTime(d) => Value::String(d.to_rfc3339_opts(SecondsFormat::AutoSi, true)),
+            FinitioValue::Seq(values) | FinitioValue::Set(values) | FinitioValue::Struct(values) => {
+                Value::Array(values.iter().map(FinitioValue::to_json).collect())
+            },
+            FinitioValue::Tuple(attributes) => object(attributes),
+            FinitioValue::Relation(tuples) => Value::Array(tuples.iter().map(object).collect()),
+            FinitioValue::Union { value, .. } | FinitioValue::Named { value, .. } => value.to_json(),
+        }
+    }
+}
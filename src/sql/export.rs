@@ -0,0 +1,409 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::codegen::{comparisons, render, snake_case, unique_name, Comparison, Kind, Literal, Subject};
+use crate::schema::{heading::Heading, relation::Relation, Constraint, Schema, Type};
+
+use super::{identifier, string, Dialect};
+
+/// Maximum number of references followed to find what a type is made of
+const MAX_DEPTH: usize = 64;
+
+/// The DDL of the tables, and what it could not express
+#[derive(Clone, Debug)]
+pub struct Export {
+    pub sql: String,
+    pub untranslated: Vec<Untranslated>,
+}
+
+/// A constraint of a relation type that the tables do not enforce, they
+/// accept more rows than the finitio type
+#[derive(Clone, Debug, PartialEq)]
+pub struct Untranslated {
+    /// The name of the type definition
+    pub type_name: String,
+    /// The attribute whose type has the constraint, None for the relation
+    pub attribute: Option<String>,
+    pub constraint: String,
+}
+
+impl fmt::Display for Untranslated {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.attribute {
+            Some(attribute) => write!(f, "{}/{}", self.type_name, attribute)?,
+            None => write!(f, "{}", self.type_name)?,
+        }
+        write!(f, ": `{}` cannot be expressed in SQL", self.constraint)
+    }
+}
+
+/// What the values of a column are
+#[derive(Clone, Debug, PartialEq)]
+enum Base {
+    Number,
+    Integer,
+    String,
+    Boolean,
+    Date,
+    DateTime,
+    /// Any other value, stored as JSON
+    Json,
+}
+
+impl Base {
+    fn sql_type(&self, dialect: Dialect) -> &'static str {
+        match (dialect, self) {
+            (Dialect::Postgres, Base::Number) => "DOUBLE PRECISION",
+            (Dialect::Sqlite, Base::Number) => "REAL",
+            // 32 bits only on Postgres, finitio numbers go beyond
+            (Dialect::Postgres, Base::Integer) => "BIGINT",
+            (Dialect::Sqlite, Base::Integer) => "INTEGER",
+            (_, Base::String) => "TEXT",
+            (Dialect::Postgres, Base::Boolean) => "BOOLEAN",
+            (Dialect::Sqlite, Base::Boolean) => "INTEGER",
+            (Dialect::Postgres, Base::Date) => "DATE",
+            (Dialect::Postgres, Base::DateTime) => "TIMESTAMP WITH TIME ZONE",
+            (Dialect::Postgres, Base::Json) => "JSONB",
+            (Dialect::Sqlite, Base::Date | Base::DateTime | Base::Json) => "TEXT",
+        }
+    }
+
+    fn kind(&self) -> Option<Kind> {
+        match self {
+            Base::Number | Base::Integer => Some(Kind::Number),
+            Base::String => Some(Kind::String),
+            _ => None,
+        }
+    }
+}
+
+/// The column of an attribute
+struct Column {
+    base: Base,
+    nullable: bool,
+    /// The constraints of the sub types the attribute type is made of
+    constraints: Vec<Constraint>,
+}
+
+/// Exports the relation type definitions of a schema as `CREATE TABLE`
+/// statements, the other type definitions are ignored.
+///
+/// Tables are named after the type definitions, in snake case (suffixed with
+/// a number when two names collide), and columns after the attributes, all
+/// quoted. Numbers whose constraints make them integers are
+/// `BIGINT` (`INTEGER` on SQLite) columns, nested values are stored as JSON.
+/// The first key whose attributes are all required is the primary key, the
+/// others are unique constraints. Constraints comparing values (or their length) with literals
+/// are translated to `CHECK` clauses.
+pub fn export(schema: &Schema, dialect: Dialect) -> Export {
+    let mut exporter = Exporter { schema, dialect, untranslated: vec![], type_name: String::new() };
+    let mut statements = vec![];
+    let mut tables = HashSet::new();
+    for (name, typedef) in schema.types.iter() {
+        exporter.type_name = name.clone();
+        let relation = match typedef.target_type() {
+            Type::Relation(relation) => relation,
+            // The relation is a table, its constraints are not enforced
+            Type::Sub(sub) => match exporter.relation(&sub.base_type, 0) {
+                Some(relation) => {
+                    for constraint in sub.constraints.iter() {
+                        exporter.report(None, constraint_text(constraint));
+                    }
+                    relation
+                },
+                None => continue,
+            },
+            _ => continue,
+        };
+        let table = unique_name(&mut tables, &snake_case(name));
+        statements.push(exporter.table(&table, &relation, typedef.meta()));
+    }
+
+    let mut sql = String::from("-- Generated by finitio, do not edit\n");
+    for statement in statements.iter() {
+        sql.push('\n');
+        sql.push_str(statement);
+    }
+    Export { sql, untranslated: exporter.untranslated }
+}
+
+fn constraint_text(constraint: &Constraint) -> String {
+    format!("{} | {}", constraint.param, constraint.expr.trim())
+}
+
+/// An SQL comment for a `/- meta -/`
+fn comment(meta: &str, indent: &str) -> String {
+    meta.trim().lines().map(|line| format!("{}-- {}\n", indent, line.trim()).replace("-- \n", "--\n")).collect()
+}
+
+/// Whether a comparison makes numbers integers, i.e. is `n % 1 == 0`
+fn is_integer(comparison: &Comparison) -> bool {
+    comparison.subject == Subject::Remainder(1.0) && comparison.op == "==" && comparison.literal == Literal::Number(0.0)
+}
+
+struct Exporter<'a> {
+    schema: &'a Schema,
+    dialect: Dialect,
+    untranslated: Vec<Untranslated>,
+    /// The type definition being exported
+    type_name: String,
+}
+
+impl<'a> Exporter<'a> {
+    fn report(&mut self, attribute: Option<&str>, constraint: String) {
+        self.untranslated.push(Untranslated {
+            type_name: self.type_name.clone(),
+            attribute: attribute.map(String::from),
+            constraint,
+        });
+    }
+
+    fn target(&self, name: &str) -> Option<Type> {
+        self.schema.types.get(name).map(|typedef| typedef.target_type())
+    }
+
+    /// The relation a type is a sub type of
+    fn relation(&self, t: &Type, depth: usize) -> Option<Relation> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        match t {
+            Type::Relation(relation) => Some(relation.clone()),
+            Type::Ref(r) => self.relation(&self.target(r.name())?, depth + 1),
+            Type::Sub(sub) => self.relation(&sub.base_type, depth + 1),
+            _ => None,
+        }
+    }
+
+    /// Resolves what the column of a type is made of
+    fn resolve(&self, t: &Type, column: &mut Column, depth: usize) {
+        if depth > MAX_DEPTH {
+            column.base = Base::Json;
+            return;
+        }
+        match t {
+            Type::Builtin(b) => {
+                column.base = match b.target.as_str() {
+                    "Number" => Base::Number,
+                    "String" => Base::String,
+                    "Boolean" => Base::Boolean,
+                    "Date" => Base::Date,
+                    "DateTime" => Base::DateTime,
+                    _ => Base::Json,
+                }
+            },
+            Type::Nil(_) => column.nullable = true,
+            Type::Ref(r) => match self.target(r.name()) {
+                Some(target) => self.resolve(&target, column, depth + 1),
+                None => column.base = Base::Json,
+            },
+            Type::Sub(sub) => {
+                column.constraints.extend(sub.constraints.iter().cloned());
+                self.resolve(&sub.base_type, column, depth + 1);
+            },
+            Type::Union(u) => {
+                let others: Vec<&Type> = u.candidates.iter().filter(|c| !matches!(c, Type::Nil(_))).collect();
+                if others.len() < u.candidates.len() {
+                    column.nullable = true;
+                }
+                match others.as_slice() {
+                    [t] => self.resolve(t, column, depth + 1),
+                    _ => column.base = Base::Json,
+                }
+            },
+            Type::Any(_) | Type::Seq(_) | Type::Set(_) | Type::Struct(_) | Type::Tuple(_) | Type::Relation(_) => {
+                column.base = Base::Json
+            },
+        }
+    }
+
+    /// The `CREATE TABLE` statement of a relation
+    fn table(&mut self, table: &str, relation: &Relation, meta: Option<&str>) -> String {
+        let heading: &Heading = &relation.heading;
+        if heading.allow_extra.is_some() {
+            self.report(None, String::from("..."));
+        }
+        let mut attribute_names: Vec<&String> = heading.attributes.keys().collect();
+        attribute_names.sort();
+
+        let mut lines = vec![];
+        let mut comments = vec![];
+        let mut required = vec![];
+        for attribute_name in attribute_names {
+            let attribute = &heading.attributes[attribute_name];
+            let mut column = Column { base: Base::Json, nullable: attribute.optional, constraints: vec![] };
+            self.resolve(&attribute.att_type, &mut column, 0);
+            if !column.nullable {
+                required.push(attribute_name.clone());
+            }
+
+            let mut checks = vec![];
+            let name = identifier(attribute_name);
+            for constraint in column.constraints.iter() {
+                let alternatives = column.base.kind().and_then(|kind| comparisons(constraint, kind));
+                let mut alternatives = match alternatives {
+                    Some(alternatives) => alternatives,
+                    None => {
+                        self.report(Some(attribute_name), constraint_text(constraint));
+                        continue;
+                    },
+                };
+                // `n % 1 == 0` is the type of the column
+                if column.base == Base::Number && alternatives.len() == 1 && alternatives[0].iter().any(is_integer) {
+                    column.base = Base::Integer;
+                    alternatives[0].retain(|comparison| !is_integer(comparison));
+                    if alternatives[0].is_empty() {
+                        continue;
+                    }
+                }
+                match check(&alternatives, &name, &column.base) {
+                    Some(check) => checks.push(format!(" CHECK ({})", check)),
+                    None => self.report(Some(attribute_name), constraint_text(constraint)),
+                }
+            }
+
+            let mut line = String::new();
+            if let (Some(meta), Dialect::Sqlite) = (&attribute.meta, self.dialect) {
+                line.push_str(&comment(meta, "  "));
+            }
+            let not_null = if column.nullable { "" } else { " NOT NULL" };
+            line.push_str(&format!("  {} {}{}{}", name, column.base.sql_type(self.dialect), not_null, checks.concat()));
+            lines.push(line);
+            if let (Some(meta), Dialect::Postgres) = (&attribute.meta, self.dialect) {
+                comments.push(format!("COMMENT ON COLUMN {}.{} IS {};\n", identifier(table), name, string(meta.trim())));
+            }
+        }
+
+        let mut primary = false;
+        for key in relation.keys.iter() {
+            let columns: Vec<String> = key.iter().map(|name| identifier(name)).collect();
+            if !primary && key.iter().all(|name| required.contains(name)) {
+                primary = true;
+                lines.push(format!("  PRIMARY KEY ({})", columns.join(", ")));
+            } else {
+                lines.push(format!("  UNIQUE ({})", columns.join(", ")));
+            }
+        }
+
+        let mut statement = String::new();
+        if let (Some(meta), Dialect::Sqlite) = (meta, self.dialect) {
+            statement.push_str(&comment(meta, ""));
+        }
+        statement.push_str(&format!("CREATE TABLE {} (\n{}\n);\n", identifier(table), lines.join(",\n")));
+        if let (Some(meta), Dialect::Postgres) = (meta, self.dialect) {
+            statement.push_str(&format!("COMMENT ON TABLE {} IS {};\n", identifier(table), string(meta.trim())));
+        }
+        statement.push_str(&comments.concat());
+        statement
+    }
+}
+
+/// A constraint as the condition of a `CHECK` clause on a column
+fn check(alternatives: &[Vec<Comparison>], column: &str, base: &Base) -> Option<String> {
+    // Only integers have a remainder in both dialects
+    let remainders = alternatives.iter().flatten().any(|c| matches!(c.subject, Subject::Remainder(_)));
+    if remainders && base != &Base::Integer {
+        return None;
+    }
+    Some(render(alternatives, "AND", "OR", |comparison| {
+        let op = match comparison.op {
+            "==" => "=",
+            "!=" => "<>",
+            op => op,
+        };
+        match (&comparison.subject, &comparison.literal) {
+            (Subject::Length, Literal::Number(n)) => format!("length({}) {} {}", column, op, n),
+            (Subject::Remainder(d), Literal::Number(r)) => format!("{} % {} {} {}", column, d, op, r),
+            (_, Literal::Number(n)) => format!("{} {} {}", column, op, n),
+            (_, Literal::String(s)) => format!("{} {} {}", column, op, string(s)),
+        }
+    }))
+}
+
+#[cfg(test)]
+fn export_source(source: &str, dialect: Dialect) -> Export {
+    let schema = crate::schema::schema::test_schema(source);
+    export(&schema, dialect)
+}
+
+#[cfg(test)]
+const PEOPLE: &str = r#"
+Id = .Number(i | i % 1 == 0 && i > 0)
+Name = .String(s | len(s) > 0)
+/- The people we know -/
+People = {{
+  id: Id
+  /- The full name -/
+  name: Name
+  email :? .String
+  born: .Date|Nil
+  admin: .Boolean
+  tags: {.String}
+  score: .Number(s | s % 2 == 0)
+  status: .String(s | s == "active" || s == "left")
+}} key(id) key(email)
+Person = { name: Name }
+"#;
+
+#[test]
+fn test_export_postgres() {
+    let export = export_source(PEOPLE, Dialect::Postgres);
+    assert_eq!(export.sql, r#"-- Generated by finitio, do not edit
+
+CREATE TABLE "people" (
+  "admin" BOOLEAN NOT NULL,
+  "born" DATE,
+  "email" TEXT,
+  "id" BIGINT NOT NULL CHECK ("id" > 0),
+  "name" TEXT NOT NULL CHECK (length("name") > 0),
+  "score" DOUBLE PRECISION NOT NULL,
+  "status" TEXT NOT NULL CHECK (("status" = 'active') OR ("status" = 'left')),
+  "tags" JSONB NOT NULL,
+  PRIMARY KEY ("id"),
+  UNIQUE ("email")
+);
+COMMENT ON TABLE "people" IS 'The people we know';
+COMMENT ON COLUMN "people"."name" IS 'The full name';
+"#);
+    assert_eq!(
+        export.untranslated.iter().map(Untranslated::to_string).collect::<Vec<_>>(),
+        vec!["People/score: `s | s % 2 == 0` cannot be expressed in SQL"]
+    );
+}
+
+#[test]
+fn test_export_sqlite() {
+    let export = export_source(PEOPLE, Dialect::Sqlite);
+    assert_eq!(export.sql, r#"-- Generated by finitio, do not edit
+
+-- The people we know
+CREATE TABLE "people" (
+  "admin" INTEGER NOT NULL,
+  "born" TEXT,
+  "email" TEXT,
+  "id" INTEGER NOT NULL CHECK ("id" > 0),
+  -- The full name
+  "name" TEXT NOT NULL CHECK (length("name") > 0),
+  "score" REAL NOT NULL,
+  "status" TEXT NOT NULL CHECK (("status" = 'active') OR ("status" = 'left')),
+  "tags" TEXT NOT NULL,
+  PRIMARY KEY ("id"),
+  UNIQUE ("email")
+);
+"#);
+}
+
+#[test]
+fn test_export_table_names() {
+    let export = export_source("Order = {{ id: .Number }}\norder = {{ id: .Number }}", Dialect::Sqlite);
+    assert_eq!(export.sql, r#"-- Generated by finitio, do not edit
+
+CREATE TABLE "order" (
+  "id" REAL NOT NULL
+);
+
+CREATE TABLE "order2" (
+  "id" REAL NOT NULL
+);
+"#);
+}
@@ -0,0 +1,925 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::codegen::pascal_case;
+use crate::common::FilePosition;
+use crate::fio;
+use crate::fmt::format_schema;
+use crate::schema::Constraint;
+
+/// Words ending the type of a column, and starting its constraints
+const CONSTRAINT_KEYWORDS: [&str; 13] = [
+    "constraint", "not", "null", "primary", "unique", "check", "default", "references", "collate", "generated",
+    "autoincrement", "identity", "on",
+];
+
+/// How nullable columns are imported
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Nullable {
+    /// As optional attributes, `name :? T`: rows omit null values
+    #[default]
+    Optional,
+    /// As unions with Nil, `name: T|Nil`: rows have null values
+    Nil,
+}
+
+impl Nullable {
+    pub const ALL: [Nullable; 2] = [Nullable::Optional, Nullable::Nil];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Nullable::Optional => "optional",
+            Nullable::Nil => "nil",
+        }
+    }
+}
+
+impl fmt::Display for Nullable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Nullable {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Nullable::ALL
+            .iter()
+            .find(|n| n.name() == name)
+            .copied()
+            .ok_or_else(|| format!("Unknown nullable columns import: {}", name))
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Options {
+    pub nullable: Nullable,
+}
+
+/// A finitio schema imported from SQL DDL, and what could not be imported
+#[derive(Clone, Debug)]
+pub struct Imported {
+    pub schema: fio::Schema,
+    pub warnings: Vec<Warning>,
+}
+
+impl Imported {
+    /// The imported schema as `.fio` source
+    pub fn to_fio(&self) -> String {
+        format_schema(&self.schema)
+    }
+}
+
+/// A part of the DDL that has no finitio equivalent, the imported relation
+/// types may accept more rows than the tables
+#[derive(Clone, Debug, PartialEq)]
+pub struct Warning {
+    pub table: String,
+    pub message: String,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.table, self.message)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Word(String),
+    /// A quoted identifier
+    Quoted(String),
+    String(String),
+    Number(String),
+    Symbol(String),
+}
+
+impl Token {
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self, Token::Word(word) if word.eq_ignore_ascii_case(keyword))
+    }
+
+    fn is_symbol(&self, symbol: &str) -> bool {
+        matches!(self, Token::Symbol(s) if s == symbol)
+    }
+
+    /// The name of an identifier token
+    fn identifier(&self) -> Option<&str> {
+        match self {
+            Token::Word(name) | Token::Quoted(name) => Some(name),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Word(s) | Token::Number(s) | Token::Symbol(s) => write!(f, "{}", s),
+            Token::Quoted(s) => write!(f, "\"{}\"", s.replace('"', "\"\"")),
+            Token::String(s) => write!(f, "'{}'", s.replace('\'', "''")),
+        }
+    }
+}
+
+/// The SQL text of tokens
+fn text(tokens: &[Token]) -> String {
+    let mut text = String::new();
+    for (i, token) in tokens.iter().enumerate() {
+        let glued = i == 0
+            || token.is_symbol(")")
+            || token.is_symbol(",")
+            || tokens[i - 1].is_symbol("(")
+            || (token.is_symbol("(") && matches!(tokens[i - 1], Token::Word(_)));
+        if !glued {
+            text.push(' ');
+        }
+        text.push_str(&token.to_string());
+    }
+    text
+}
+
+/// Splits SQL into tokens, comments are skipped
+fn tokenize(sql: &str) -> Vec<Token> {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut tokens = vec![];
+    let mut i = 0;
+    // The content of a quoted token, quotes are escaped by doubling them
+    let quoted = |i: &mut usize, close: char| {
+        let mut content = String::new();
+        *i += 1;
+        while *i < chars.len() {
+            if chars[*i] == close {
+                if chars.get(*i + 1) == Some(&close) {
+                    content.push(close);
+                    *i += 2;
+                    continue;
+                }
+                *i += 1;
+                break;
+            }
+            content.push(chars[*i]);
+            *i += 1;
+        }
+        content
+    };
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '-' && chars.get(i + 1) == Some(&'-') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c == '/' && chars.get(i + 1) == Some(&'*') {
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                i += 1;
+            }
+            i += 2;
+        } else if c == '\'' {
+            tokens.push(Token::String(quoted(&mut i, '\'')));
+        } else if c == '"' {
+            tokens.push(Token::Quoted(quoted(&mut i, '"')));
+        } else if c == '`' {
+            tokens.push(Token::Quoted(quoted(&mut i, '`')));
+        } else if c == '[' && chars.get(i + 1) != Some(&']') && !matches!(tokens.last(), Some(Token::Word(_))) {
+            // SQL Server identifiers, but not the brackets of `int[]`
+            tokens.push(Token::Quoted(quoted(&mut i, ']')));
+        } else if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            tokens.push(Token::Number(chars[start..i].iter().collect()));
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '$') {
+                i += 1;
+            }
+            tokens.push(Token::Word(chars[start..i].iter().collect()));
+        } else {
+            let pair: String = chars[i..chars.len().min(i + 2)].iter().collect();
+            let symbol = match pair.as_str() {
+                "<=" | ">=" | "<>" | "!=" | "==" | "||" | "::" => pair,
+                _ => c.to_string(),
+            };
+            i += symbol.chars().count();
+            tokens.push(Token::Symbol(symbol));
+        }
+    }
+    tokens
+}
+
+/// Splits tokens at the given symbol, outside of parentheses
+fn split<'t>(tokens: &'t [Token], separator: &str) -> Vec<&'t [Token]> {
+    let mut parts = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, token) in tokens.iter().enumerate() {
+        if token.is_symbol("(") {
+            depth += 1;
+        } else if token.is_symbol(")") {
+            depth -= 1;
+        } else if depth == 0 && token.is_symbol(separator) {
+            parts.push(&tokens[start..i]);
+            start = i + 1;
+        }
+    }
+    parts.push(&tokens[start..]);
+    parts.into_iter().filter(|part| !part.is_empty()).collect()
+}
+
+/// The index of the parenthesis closing the one at `open`
+fn closing(tokens: &[Token], open: usize) -> Option<usize> {
+    let mut depth = 0;
+    for (i, token) in tokens.iter().enumerate().skip(open) {
+        if token.is_symbol("(") {
+            depth += 1;
+        } else if token.is_symbol(")") {
+            depth -= 1;
+            if depth == 0 {
+                return Some(i);
+            }
+        }
+    }
+    None
+}
+
+/// The identifiers of a parenthesized list, like the columns of a key
+fn identifiers(tokens: &[Token]) -> Vec<String> {
+    split(tokens, ",")
+        .iter()
+        .filter_map(|part| part.first().and_then(Token::identifier).map(String::from))
+        .collect()
+}
+
+/// Generated types are not found in any source file
+fn nowhere() -> FilePosition {
+    FilePosition { line: 0, column: 0 }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn builtin(name: &str) -> fio::Type {
+    fio::Type::BuiltinType(fio::BuiltinType { name: name.to_string(), position: nowhere() })
+}
+
+fn any() -> fio::Type {
+    fio::Type::AnyType(fio::AnyType { position: nowhere() })
+}
+
+fn meta(text: &str) -> String {
+    format!(" {} ", text.trim())
+}
+
+/// A column of a `CREATE TABLE` statement
+struct Column {
+    name: String,
+    att_type: fio::Type,
+    /// The parts of the constraint of the attribute type, joined by `&&`
+    parts: Vec<String>,
+    nullable: bool,
+    meta: Vec<String>,
+}
+
+/// A `CREATE TABLE` statement
+struct Table {
+    name: String,
+    type_name: String,
+    columns: Vec<Column>,
+    keys: Vec<Vec<String>>,
+    meta: Vec<String>,
+}
+
+#[derive(Default)]
+struct Importer {
+    tables: Vec<Table>,
+    names: HashSet<String>,
+    warnings: Vec<Warning>,
+}
+
+/// Imports the `CREATE TABLE` statements of SQL DDL as relation type
+/// definitions, in order, named after the tables in pascal case. The other
+/// statements are ignored, but for the `COMMENT ON` ones of PostgreSQL that
+/// document the tables and columns.
+///
+/// Column types are mapped to builtins, integer types are numbers without
+/// remainder and lengths of character types are constraints. `CHECK`
+/// clauses on a column become constraints of its attribute type when they
+/// are made of comparisons, `IN`, `BETWEEN`, `AND`, `OR`, `NOT` and
+/// `length`, the others are kept in its meta. Primary keys and unique
+/// constraints are the keys of the relation.
+pub fn import(sql: &str, options: &Options) -> Imported {
+    let mut importer = Importer::default();
+    let tokens = tokenize(sql);
+    let statements = split(&tokens, ";");
+    for statement in statements.iter() {
+        importer.statement(statement);
+    }
+    for statement in statements.iter() {
+        importer.comment(statement);
+    }
+
+    let type_defs = importer.tables
+        .into_iter()
+        .map(|table| {
+            let attributes = table.columns
+                .into_iter()
+                .map(|column| {
+                    let mut att_type = constrain(column.att_type, &param(&column.name), column.parts);
+                    let optional = column.nullable && options.nullable == Nullable::Optional;
+                    if column.nullable && options.nullable == Nullable::Nil {
+                        let nil = fio::Type::NilType(fio::NilType { position: nowhere() });
+                        att_type = fio::Type::UnionType(fio::UnionType { candidates: vec![att_type, nil], position: nowhere() });
+                    }
+                    fio::Attribute {
+                        meta: if column.meta.is_empty() { None } else { Some(meta(&column.meta.join(" "))) },
+                        name: column.name,
+                        att_type,
+                        optional,
+                        position: nowhere(),
+                    }
+                })
+                .collect();
+            let keys = table.keys
+                .into_iter()
+                .map(|attributes| fio::RelationKey { attributes, position: nowhere() })
+                .collect();
+            let relation = fio::RelationType {
+                heading: fio::Heading { attributes, position: nowhere(), allow_extra: None },
+                keys,
+                position: nowhere(),
+            };
+            fio::TypeDef {
+                meta: if table.meta.is_empty() { None } else { Some(meta(&table.meta.join(" "))) },
+                name: table.type_name,
+                target: fio::Type::RelationType(relation),
+                position: nowhere(),
+            }
+        })
+        .collect();
+
+    Imported {
+        schema: fio::Schema { imports: vec![], type_defs },
+        warnings: importer.warnings,
+    }
+}
+
+/// The parameter of the constraints about a column: its name, without the
+/// underscores parameters cannot have
+fn param(column: &str) -> String {
+    column.chars().filter(|c| *c != '_').collect()
+}
+
+/// A sub type, constraint parts are joined by `&&`
+fn constrain(base: fio::Type, param: &str, parts: Vec<String>) -> fio::Type {
+    if parts.is_empty() {
+        return base;
+    }
+    let expr = if parts.len() == 1 {
+        parts[0].clone()
+    } else {
+        parts.iter().map(|p| format!("({})", p)).collect::<Vec<_>>().join(" && ")
+    };
+    fio::Type::SubType(fio::SubType {
+        base: Box::new(base),
+        constraints: vec![fio::Constraint { param: param.to_string(), expr: format!(" {}", expr), position: nowhere() }],
+        position: nowhere(),
+    })
+}
+
+impl Importer {
+    fn warn(&mut self, table: &str, message: String) {
+        self.warnings.push(Warning { table: table.to_string(), message });
+    }
+
+    fn unique_name(&mut self, name: &str) -> String {
+        let mut unique = name.to_string();
+        let mut index = 1;
+        while unique == "Nil" || self.names.contains(&unique) {
+            index += 1;
+            unique = format!("{}{}", name, index);
+        }
+        self.names.insert(unique.clone());
+        unique
+    }
+
+    fn statement(&mut self, tokens: &[Token]) {
+        // CREATE [TEMPORARY | UNLOGGED ...] TABLE [IF NOT EXISTS] name (...)
+        let table_at = match tokens.iter().take(4).position(|t| t.is_keyword("table")) {
+            Some(i) if tokens[0].is_keyword("create") => i,
+            _ => return,
+        };
+        let mut i = table_at + 1;
+        if tokens.get(i).is_some_and(|t| t.is_keyword("if")) {
+            i += 3;
+        }
+        // Only the last part of a qualified name matters
+        let mut name = None;
+        while let Some(token) = tokens.get(i) {
+            match token.identifier() {
+                Some(part) => name = Some(part.to_string()),
+                None if token.is_symbol(".") => {},
+                None => break,
+            }
+            i += 1;
+        }
+        let (name, close) = match (name, tokens.get(i)) {
+            (Some(name), Some(open)) if open.is_symbol("(") => match closing(tokens, i) {
+                Some(close) => (name, close),
+                None => return,
+            },
+            (name, _) => {
+                let name = name.unwrap_or_default();
+                self.warn(&name, String::from("only tables with column definitions are imported"));
+                return;
+            },
+        };
+
+        let mut type_name = pascal_case(&name);
+        if !type_name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            type_name.insert(0, 'T');
+        }
+        let type_name = self.unique_name(&type_name);
+        let mut table = Table { name: name.clone(), type_name, columns: vec![], keys: vec![], meta: vec![] };
+        let mut checks = vec![];
+        for item in split(&tokens[i + 1..close], ",") {
+            self.item(&mut table, item, &mut checks);
+        }
+        for check in checks {
+            self.check(&mut table, &check);
+        }
+        self.tables.push(table);
+    }
+
+    /// A column definition or a table constraint
+    fn item(&mut self, table: &mut Table, item: &[Token], checks: &mut Vec<Vec<Token>>) {
+        let mut item = item;
+        // CONSTRAINT name ...
+        if item[0].is_keyword("constraint") && item.len() > 2 {
+            item = &item[2..];
+        }
+        let first = &item[0];
+        if first.is_keyword("primary") || first.is_keyword("unique") {
+            if let Some(open) = item.iter().position(|t| t.is_symbol("(")) {
+                let close = closing(item, open).unwrap_or(item.len());
+                let columns = identifiers(&item[open + 1..close]);
+                if first.is_keyword("primary") {
+                    for column in table.columns.iter_mut().filter(|c| columns.contains(&c.name)) {
+                        column.nullable = false;
+                    }
+                    table.keys.insert(0, columns);
+                } else {
+                    table.keys.push(columns);
+                }
+            }
+            return;
+        }
+        if first.is_keyword("check") {
+            checks.push(item[1..].to_vec());
+            return;
+        }
+        if first.is_keyword("foreign") || first.is_keyword("exclude") {
+            self.warn(&table.name, format!("`{}` ignored", text(item)));
+            return;
+        }
+
+        let name = match first.identifier() {
+            Some(name) => name.to_string(),
+            None => {
+                self.warn(&table.name, format!("`{}` ignored", text(item)));
+                return;
+            },
+        };
+        if !is_identifier(&name) {
+            self.warn(&table.name, format!("column `{}` is not a valid attribute name, skipped", name));
+            return;
+        }
+
+        // The type runs until the first constraint keyword
+        let mut end = 1;
+        while end < item.len() {
+            let token = &item[end];
+            if CONSTRAINT_KEYWORDS.iter().any(|k| token.is_keyword(k)) {
+                break;
+            }
+            end += 1;
+        }
+        let (att_type, parts) = self.column_type(&table.name, &name, &item[1..end]);
+        let mut column = Column { name: name.clone(), att_type, parts, nullable: true, meta: vec![] };
+
+        let mut i = end;
+        while i < item.len() {
+            let token = &item[i];
+            if token.is_keyword("not") && item.get(i + 1).is_some_and(|t| t.is_keyword("null")) {
+                column.nullable = false;
+                i += 1;
+            } else if token.is_keyword("primary") {
+                column.nullable = false;
+                table.keys.insert(0, vec![name.clone()]);
+            } else if token.is_keyword("unique") {
+                table.keys.push(vec![name.clone()]);
+            } else if token.is_keyword("check") && item.get(i + 1).is_some_and(|t| t.is_symbol("(")) {
+                let close = closing(item, i + 1).unwrap_or(item.len() - 1);
+                checks.push(item[i + 1..=close].to_vec());
+                i = close;
+            } else if token.is_keyword("references") {
+                self.warn(&table.name, format!("the foreign key of column `{}` is ignored", name));
+            } else if token.is_symbol("(") {
+                // Skips the arguments of DEFAULT and GENERATED clauses
+                i = closing(item, i).unwrap_or(item.len());
+            }
+            i += 1;
+        }
+        table.columns.push(column);
+    }
+
+    /// The attribute type of a column type, and the parts of its constraint
+    fn column_type(&mut self, table: &str, column: &str, tokens: &[Token]) -> (fio::Type, Vec<String>) {
+        if tokens.is_empty() {
+            return (any(), vec![]);
+        }
+        // `int[]` and `int ARRAY` are sequences
+        let array = tokens.iter().any(|t| t.is_symbol("[") || t.is_keyword("array"));
+        let words: Vec<String> = tokens
+            .iter()
+            .take_while(|t| !t.is_symbol("(") && !t.is_symbol("[") && !t.is_keyword("array"))
+            .filter_map(|t| match t {
+                Token::Word(word) => Some(word.to_ascii_lowercase()),
+                _ => None,
+            })
+            .collect();
+        let name = words.join(" ");
+        let size = tokens
+            .iter()
+            .position(|t| t.is_symbol("("))
+            .and_then(|open| match tokens.get(open + 1) {
+                Some(Token::Number(n)) => Some(n.clone()),
+                _ => None,
+            });
+
+        let param = param(column);
+        let mut parts = vec![];
+        let t = match name.as_str() {
+            "int" | "integer" | "smallint" | "bigint" | "tinyint" | "mediumint" | "int2" | "int4" | "int8"
+            | "serial" | "smallserial" | "bigserial" | "serial2" | "serial4" | "serial8" | "unsigned big int" => {
+                parts.push(format!("{} % 1 == 0", param));
+                builtin("Number")
+            },
+            "real" | "float" | "float4" | "float8" | "double" | "double precision" | "numeric" | "decimal"
+            | "number" | "money" => builtin("Number"),
+            "text" | "varchar" | "char" | "character" | "character varying" | "nchar" | "nvarchar"
+            | "varying character" | "native character" | "clob" | "string" | "citext" | "uuid" | "time"
+            | "time with time zone" | "time without time zone" | "timetz" | "interval" => {
+                let is_sized = ["varchar", "char", "character", "character varying", "nchar", "nvarchar", "varying character"]
+                    .contains(&name.as_str());
+                if let (Some(size), true) = (&size, is_sized) {
+                    parts.push(format!("len({}) <= {}", param, size));
+                }
+                builtin("String")
+            },
+            "boolean" | "bool" => builtin("Boolean"),
+            "date" => builtin("Date"),
+            "timestamp" | "timestamptz" | "datetime" | "timestamp with time zone" | "timestamp without time zone" => {
+                builtin("DateTime")
+            },
+            "json" | "jsonb" => any(),
+            _ => {
+                self.warn(table, format!("column `{}` has an unknown type `{}`, imported as `.`", column, text(tokens)));
+                any()
+            },
+        };
+        if array {
+            // The constraints are about the elements
+            let elm = constrain(t, &param, parts);
+            return (fio::Type::SeqType(fio::SeqType { elm_type: Box::new(elm), position: nowhere() }), vec![]);
+        }
+        (t, parts)
+    }
+
+    /// Makes a `CHECK` clause a constraint of the column it is about, or
+    /// keeps it in its meta (the meta of the table when it is about several
+    /// columns)
+    fn check(&mut self, table: &mut Table, check: &[Token]) {
+        let columns: Vec<String> = table.columns.iter().map(|c| c.name.clone()).collect();
+        let sql = format!("CHECK {}", text(check));
+        let mut referenced = vec![];
+        for token in check.iter() {
+            if let Some(name) = token.identifier() {
+                if let Some(column) = columns.iter().find(|c| c.eq_ignore_ascii_case(name)) {
+                    if !referenced.contains(column) {
+                        referenced.push(column.clone());
+                    }
+                }
+            }
+        }
+        let column = match referenced.as_slice() {
+            [column] => column.clone(),
+            _ => {
+                self.warn(&table.name, format!("`{}` is not about a single column, kept in the meta of the relation", sql));
+                table.meta.push(sql);
+                return;
+            },
+        };
+
+        let expr = translate(check, &column).filter(|expr| {
+            let mut constraint = Constraint::new(param(&column), expr.clone(), nowhere());
+            constraint.compile().is_ok()
+        });
+        let column = table.columns.iter_mut().find(|c| c.name == column).unwrap();
+        match expr {
+            Some(expr) if !matches!(column.att_type, fio::Type::SeqType(_)) => column.parts.push(expr),
+            _ => {
+                column.meta.push(sql.clone());
+                let message = format!("`{}` cannot be translated, kept in the meta of `{}`", sql, column.name);
+                self.warn(&table.name, message);
+            },
+        }
+    }
+
+    /// Applies `COMMENT ON TABLE` and `COMMENT ON COLUMN` statements
+    fn comment(&mut self, tokens: &[Token]) {
+        if tokens.len() < 4 || !tokens[0].is_keyword("comment") || !tokens[1].is_keyword("on") {
+            return;
+        }
+        let is_at = match tokens.iter().position(|t| t.is_keyword("is")) {
+            Some(is_at) => is_at,
+            None => return,
+        };
+        let comment = match tokens.get(is_at + 1) {
+            Some(Token::String(comment)) => comment.clone(),
+            _ => return,
+        };
+        let names: Vec<&str> = tokens[3..is_at].iter().filter_map(Token::identifier).collect();
+        if tokens[2].is_keyword("table") {
+            if let Some(table) = names.last().and_then(|name| self.tables.iter_mut().find(|t| t.name == *name)) {
+                table.meta.insert(0, comment);
+            }
+        } else if tokens[2].is_keyword("column") && names.len() >= 2 {
+            let (table, column) = (names[names.len() - 2], names[names.len() - 1]);
+            let column = self.tables
+                .iter_mut()
+                .filter(|t| t.name == table)
+                .flat_map(|t| t.columns.iter_mut())
+                .find(|c| c.name == column);
+            if let Some(column) = column {
+                column.meta.insert(0, comment);
+            }
+        }
+    }
+}
+
+/// A `CHECK` clause as a finitio expression about `column`, which it refers
+/// to by its [`param`], None if it uses
+/// anything else than comparisons, `IN`, `BETWEEN`, boolean operators and
+/// `length`
+fn translate(tokens: &[Token], column: &str) -> Option<String> {
+    let mut parts: Vec<String> = vec![];
+    let mut i = 0;
+    let operand = |token: &Token| -> Option<String> {
+        match token {
+            Token::Number(n) => Some(n.clone()),
+            Token::String(s) if !s.contains(['"', '\\']) => Some(format!("\"{}\"", s)),
+            Token::Word(w) if w.eq_ignore_ascii_case("true") || w.eq_ignore_ascii_case("false") => {
+                Some(w.to_ascii_lowercase())
+            },
+            t if t.identifier().is_some_and(|name| name.eq_ignore_ascii_case(column)) => Some(param(column)),
+            _ => None,
+        }
+    };
+    while i < tokens.len() {
+        let token = &tokens[i];
+        let negated = token.is_keyword("not") && tokens.get(i + 1).is_some_and(|t| t.is_keyword("in") || t.is_keyword("between"));
+        let keyword_at = if negated { i + 1 } else { i };
+        if tokens[keyword_at].is_keyword("in") {
+            // x IN (a, b): the operand is the last part
+            let subject = parts.pop()?;
+            let close = closing(tokens, keyword_at + 1)?;
+            let values: Option<Vec<String>> = split(&tokens[keyword_at + 2..close], ",")
+                .iter()
+                .map(|value| match value {
+                    [value] => operand(value),
+                    _ => None,
+                })
+                .collect();
+            let (op, join) = if negated { ("!=", " && ") } else { ("==", " || ") };
+            let comparisons: Vec<String> = values?.iter().map(|v| format!("{} {} {}", subject, op, v)).collect();
+            parts.push(format!("({})", comparisons.join(join)));
+            i = close + 1;
+        } else if tokens[keyword_at].is_keyword("between") {
+            let subject = parts.pop()?;
+            let low = operand(tokens.get(keyword_at + 1)?)?;
+            if !tokens.get(keyword_at + 2)?.is_keyword("and") {
+                return None;
+            }
+            let high = operand(tokens.get(keyword_at + 3)?)?;
+            let between = format!("{} >= {} && {} <= {}", subject, low, subject, high);
+            parts.push(if negated { format!("!({})", between) } else { format!("({})", between) });
+            i = keyword_at + 4;
+        } else {
+            let part = match token {
+                t if t.is_keyword("and") => String::from("&&"),
+                t if t.is_keyword("or") => String::from("||"),
+                t if t.is_keyword("not") => String::from("!"),
+                t if ["length", "char_length", "character_length"].iter().any(|f| t.is_keyword(f)) => {
+                    String::from("len")
+                },
+                Token::Symbol(s) => match s.as_str() {
+                    "=" => String::from("=="),
+                    "<>" => String::from("!="),
+                    "(" | ")" | "<" | ">" | "<=" | ">=" | "!=" | "+" | "-" | "*" | "/" | "%" => s.clone(),
+                    _ => return None,
+                },
+                t => operand(t)?,
+            };
+            parts.push(part);
+            i += 1;
+        }
+    }
+
+    // Parentheses are glued to what they enclose, and to `len`
+    let parts = conjunctions(&parts);
+    let mut expr = String::new();
+    for (index, part) in parts.iter().enumerate() {
+        let previous = if index > 0 { parts[index - 1].as_str() } else { "" };
+        let glued = index == 0 || part == ")" || previous == "(" || previous == "!" || (part == "(" && previous == "len");
+        if !glued {
+            expr.push(' ');
+        }
+        expr.push_str(part);
+    }
+    Some(unparenthesize(&expr).to_string())
+}
+
+/// The parts of an expression with its `&&` groups parenthesized when they
+/// are mixed with `||`, which finitio does not give a lower precedence
+fn conjunctions(parts: &[String]) -> Vec<String> {
+    let mut alternatives: Vec<Vec<String>> = vec![vec![]];
+    let mut i = 0;
+    while i < parts.len() {
+        match parts[i].as_str() {
+            "(" => {
+                let mut depth = 0;
+                let close = (i..parts.len())
+                    .find(|&j| {
+                        match parts[j].as_str() {
+                            "(" => depth += 1,
+                            ")" => depth -= 1,
+                            _ => {},
+                        }
+                        depth == 0
+                    })
+                    .unwrap_or(parts.len() - 1);
+                let current = alternatives.last_mut().unwrap();
+                current.push(parts[i].clone());
+                current.extend(conjunctions(&parts[i + 1..close]));
+                if close > i {
+                    current.push(parts[close].clone());
+                }
+                i = close + 1;
+            },
+            "||" => {
+                alternatives.push(vec![]);
+                i += 1;
+            },
+            _ => {
+                alternatives.last_mut().unwrap().push(parts[i].clone());
+                i += 1;
+            },
+        }
+    }
+    if alternatives.len() == 1 {
+        return alternatives.remove(0);
+    }
+    let mut grouped = vec![];
+    for (index, alternative) in alternatives.into_iter().enumerate() {
+        if index > 0 {
+            grouped.push(String::from("||"));
+        }
+        if alternative.iter().any(|part| part == "&&") {
+            grouped.push(String::from("("));
+            grouped.extend(alternative);
+            grouped.push(String::from(")"));
+        } else {
+            grouped.extend(alternative);
+        }
+    }
+    grouped
+}
+
+/// An expression without the parentheses enclosing all of it
+fn unparenthesize(expr: &str) -> &str {
+    let inner = match expr.strip_prefix('(').and_then(|e| e.strip_suffix(')')) {
+        Some(inner) => inner,
+        None => return expr,
+    };
+    let mut depth = 0;
+    for c in inner.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return expr,
+            ')' => depth -= 1,
+            _ => {},
+        }
+    }
+    unparenthesize(inner)
+}
+
+#[cfg(test)]
+use crate::schema::TypeInclude;
+
+#[cfg(test)]
+const PEOPLE: &str = r#"
+CREATE TABLE people (
+  id SERIAL PRIMARY KEY,
+  name VARCHAR(100) NOT NULL CHECK (length(name) > 0),
+  email TEXT UNIQUE,
+  status TEXT NOT NULL CHECK (status IN ('active', 'left')),
+  manager_id INTEGER REFERENCES people (id),
+  code TEXT CHECK (code ~ '^[A-Z]+$'),
+  CHECK (id <> manager_id)
+);
+COMMENT ON TABLE people IS 'The people we know';
+COMMENT ON COLUMN people.name IS 'The full name';
+"#;
+
+#[cfg(test)]
+fn import_source(sql: &str, nullable: Nullable) -> Imported {
+    let imported = import(sql, &Options { nullable });
+    // The imported schema is a valid one
+    crate::schema::schema::test_schema(&imported.to_fio());
+    imported
+}
+
+#[test]
+fn test_import_optional() {
+    let imported = import_source(PEOPLE, Nullable::Optional);
+    assert_eq!(imported.to_fio(), r#"/- The people we know CHECK (id <> manager_id) -/
+People = {{
+  id: .Number(id | id % 1 == 0)
+  /- The full name -/
+  name: .String(name | (len(name) <= 100) && (len(name) > 0))
+  email :? .String
+  status: .String(status | status == "active" || status == "left")
+  manager_id :? .Number(managerid | managerid % 1 == 0)
+  /- CHECK (code ~ '^[A-Z]+$') -/
+  code :? .String
+}} key(id) key(email)
+"#);
+    assert_eq!(
+        imported.warnings.iter().map(Warning::to_string).collect::<Vec<_>>(),
+        vec![
+            "people: the foreign key of column `manager_id` is ignored",
+            "people: `CHECK (code ~ '^[A-Z]+$')` cannot be translated, kept in the meta of `code`",
+            "people: `CHECK (id <> manager_id)` is not about a single column, kept in the meta of the relation",
+        ]
+    );
+}
+
+#[test]
+fn test_import_mixed_operators() {
+    let sql = "CREATE TABLE t (
+  x INTEGER NOT NULL CHECK (x > 10 AND x < 0 OR x = 5),
+  y INTEGER NOT NULL CHECK (y = 1 OR (y > 2 AND y < 4 OR y = 9) AND y <> 3),
+  z INTEGER NOT NULL CHECK (z > 0 AND z < 10)
+);";
+    let imported = import_source(sql, Nullable::Optional);
+    assert_eq!(imported.to_fio(), r#"T = {{
+  x: .Number(x | (x % 1 == 0) && ((x > 10 && x < 0) || x == 5))
+  y: .Number(y | (y % 1 == 0) && (y == 1 || (((y > 2 && y < 4) || y == 9) && y != 3)))
+  z: .Number(z | (z % 1 == 0) && (z > 0 && z < 10))
+}}
+"#);
+
+    // `&&` groups are checked as SQL checks them
+    let schema = crate::schema::schema::test_schema(&imported.to_fio());
+    let t = &schema.types["T"];
+    assert!(t.include(&serde_json::json!([{ "x": 5, "y": 9, "z": 1 }])).is_ok());
+    assert!(t.include(&serde_json::json!([{ "x": 5, "y": 9.5, "z": 1 }])).is_err());
+    assert!(t.include(&serde_json::json!([{ "x": 5, "y": 1, "z": 1 }])).is_ok());
+    assert!(t.include(&serde_json::json!([{ "x": 11, "y": 1, "z": 1 }])).is_err());
+}
+
+#[test]
+fn test_import_nil() {
+    let sql = "CREATE TABLE IF NOT EXISTS events (at TIMESTAMP, tags TEXT[], payload JSONB NOT NULL, x GEOMETRY);";
+    let imported = import_source(sql, Nullable::Nil);
+    assert_eq!(imported.to_fio(), r#"Events = {{
+  at: .DateTime | Nil
+  tags: [.String] | Nil
+  payload: .
+  x: . | Nil
+}}
+"#);
+    assert_eq!(
+        imported.warnings.iter().map(Warning::to_string).collect::<Vec<_>>(),
+        vec!["events: column `x` has an unknown type `GEOMETRY`, imported as `.`"]
+    );
+}
@@ -0,0 +1,69 @@
+//! Conversions between finitio relation types and SQL tables
+
+use std::fmt;
+use std::str::FromStr;
+
+mod export;
+mod import;
+
+pub use export::{export, Export, Untranslated};
+pub use import::{import, Imported, Nullable, Options, Warning};
+
+/// The SQL dialects tables are exported to
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Dialect {
+    #[default]
+    Postgres,
+    Sqlite,
+}
+
+impl Dialect {
+    pub const ALL: [Dialect; 2] = [Dialect::Postgres, Dialect::Sqlite];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Dialect::Postgres => "postgres",
+            Dialect::Sqlite => "sqlite",
+        }
+    }
+}
+
+impl fmt::Display for Dialect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Dialect {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Dialect::ALL
+            .iter()
+            .find(|d| d.name() == name)
+            .copied()
+            .ok_or_else(|| format!("Unknown SQL dialect: {}", name))
+    }
+}
+
+/// A quoted identifier, so that names never clash with the (many, dialect
+/// specific) reserved words
+fn identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// A string literal
+fn string(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
+
+#[test]
+fn test_identifiers() {
+    assert_eq!(identifier("people"), "\"people\"");
+    assert_eq!(identifier("first_name"), "\"first_name\"");
+    assert_eq!(identifier("when"), "\"when\"");
+    assert_eq!(identifier("firstName"), "\"firstName\"");
+    assert_eq!(identifier("order"), "\"order\"");
+    assert_eq!(identifier("a\"b"), "\"a\"\"b\"");
+    assert_eq!(string("it's"), "'it''s'");
+}